@@ -0,0 +1,71 @@
+//! Range queries on `FilesDB` over a wide and deep tree
+
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+// The crate has no library target, so the modules are compiled into the benchmark directly.
+// They're linted with the binary, where their unit tests run as well.
+#[allow(unused, clippy::all)]
+#[path = "../src/file_info.rs"]
+mod file_info;
+#[allow(unused, clippy::all)]
+#[path = "../src/files_db.rs"]
+mod files_db;
+
+use file_info::{FileMeta, Permissions};
+use files_db::FilesDB;
+
+fn meta(is_dir: bool) -> FileMeta {
+    FileMeta {
+        is_dir,
+        size: Some(4096),
+        allocated: None,
+        touched: None,
+        accessed: None,
+        permissions: Permissions::default(),
+    }
+}
+
+/// `/root` with 100 projects, each holding 20 packages of 50 files
+fn build_db() -> FilesDB {
+    let mut db = FilesDB::new();
+    let root = PathBuf::from("/root");
+    db.add(root.clone(), meta(true));
+    for project in 0..100 {
+        let project = root.join(format!("project-{project}"));
+        db.add(project.clone(), meta(true));
+        db.add(project.join("README.md"), meta(false));
+        for package in 0..20 {
+            let package = project.join(format!("package-{package}"));
+            db.add(package.clone(), meta(true));
+            for file in 0..50 {
+                db.add(package.join(format!("file-{file}.rs")), meta(false));
+            }
+        }
+    }
+    db
+}
+
+fn bench_range_queries(c: &mut Criterion) {
+    let db = build_db();
+    let root = Path::new("/root");
+    let project = Path::new("/root/project-50");
+
+    c.bench_function("iter_level root", |b| {
+        b.iter(|| db.iter_level(black_box(root)).count())
+    });
+    c.bench_function("iter_level project", |b| {
+        b.iter(|| db.iter_level(black_box(project)).count())
+    });
+    c.bench_function("iter_dir project", |b| {
+        b.iter(|| db.iter_dir(black_box(project)).count())
+    });
+    c.bench_function("iter_directories root", |b| {
+        b.iter(|| db.iter_directories(black_box(root)).count())
+    });
+}
+
+criterion_group!(benches, bench_range_queries);
+criterion_main!(benches);
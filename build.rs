@@ -0,0 +1,35 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+/// Run a command and return its trimmed output, if it succeeded
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    Some(text.trim().to_string())
+}
+
+fn main() {
+    // Build metadata for `ddc --version`; useful when triaging platform-specific issues
+    let target = env::var("TARGET").unwrap_or_else(|_| String::from("unknown"));
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let rustc_version =
+        command_output(&rustc, &["--version"]).unwrap_or_else(|| String::from("unknown"));
+    let git_hash = command_output("git", &["rev-parse", "--short", "HEAD"])
+        .unwrap_or_else(|| String::from("unknown"));
+
+    println!("cargo:rustc-env=DDC_BUILD_TARGET={target}");
+    println!("cargo:rustc-env=DDC_BUILD_RUSTC_VERSION={rustc_version}");
+    println!("cargo:rustc-env=DDC_BUILD_GIT_HASH={git_hash}");
+
+    println!("cargo:rerun-if-changed=build.rs");
+    // A missing path would rerun the script on every build, e.g. in a source tarball
+    for path in [".git/HEAD", ".git/refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+}
@@ -1,15 +1,28 @@
-use std::io::{self, Write};
-use std::path::Path;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use humansize::{DECIMAL, format_size};
 use owo_colors::OwoColorize;
-use tracing::error;
+use tracing::{error, warn};
 
 use crate::cli::{AnalyzeArgs, UiConfig};
-use crate::config::{ConfigError, load_config_file};
+use crate::config::{ConfigError, check_config_paths, load_config_file_or_default};
 use crate::discovery::{
-    DiscoveryManager, ExternalDiscoveryDefinition, default_discovery_definitions,
+    DiscoveryManager, DiscoveryResultEnvelop, default_discovery_definitions, is_updated_since,
+    resolve_symlinks,
 };
-use crate::display::{display_progress_bar, print_results};
+use crate::display::{
+    OutputFormat, TableOptions, display_progress, language_summary, print_detector_matches,
+    print_discovered, print_duplicates, print_jsonl, print_language_summary, print_largest_dirs,
+    print_results, print_tree,
+};
+use crate::duplicates::find_duplicates;
+use crate::history::{load_history, update_history};
+use crate::largest_dirs::largest_dirs;
+use crate::loader::LoaderError;
+use crate::venv_postprocess::venv_postprocess;
 
 #[derive(thiserror::Error, Debug)]
 pub enum AnalyzeError {
@@ -18,46 +31,199 @@ pub enum AnalyzeError {
         #[from]
         inner: ConfigError,
     },
+    #[error("{inner}")]
+    LoaderError {
+        #[from]
+        inner: LoaderError,
+    },
     #[error("No results found. Do you use one of the supported languages?")]
     NoResultsFound,
+    #[error(
+        "Total size {} exceeds the limit of {}",
+        format_size(*total, DECIMAL),
+        format_size(*limit, DECIMAL)
+    )]
+    SizeLimitExceeded { total: u64, limit: u64 },
+    #[error("Unable to write results to '{}': {inner}", path.display())]
+    CannotWriteOutput { path: PathBuf, inner: io::Error },
+}
+
+impl AnalyzeError {
+    /// Exit code used when the `--fail-over` limit is exceeded
+    pub const SIZE_LIMIT_EXCEEDED_EXIT_CODE: i32 = 3;
 }
 
 pub fn analyze(
     cmd_args: &AnalyzeArgs,
     ui_config: &UiConfig,
-    home_dir: &Path,
+    home_dir: Option<&Path>,
 ) -> Result<(), AnalyzeError> {
-    analyze_inner(&mut io::stdout(), cmd_args, ui_config, home_dir)
+    let Some(path) = cmd_args.output.as_deref() else {
+        return analyze_inner(&mut io::stdout(), cmd_args, ui_config, home_dir);
+    };
+
+    let cannot_write = |inner| AnalyzeError::CannotWriteOutput {
+        path: path.to_path_buf(),
+        inner,
+    };
+    let mut out = BufWriter::new(File::create(path).map_err(cannot_write)?);
+    // Report the size limit only after the results are safely written
+    let result = analyze_inner(&mut out, cmd_args, ui_config, home_dir);
+    out.flush().map_err(cannot_write)?;
+    result
 }
 
 fn analyze_inner<W: Write>(
     out: &mut W,
     cmd_args: &AnalyzeArgs,
     ui_config: &UiConfig,
-    home_dir: &Path,
+    home_dir: Option<&Path>,
 ) -> Result<(), AnalyzeError> {
-    let config = load_config_file(home_dir, cmd_args.shared.config.as_deref())?;
-    let definitions = config
-        .paths
-        .into_iter()
-        .map(|p| ExternalDiscoveryDefinition { path: p.path })
-        .collect::<Vec<_>>();
+    let config = load_config_file_or_default(
+        home_dir,
+        cmd_args.shared.config.as_deref(),
+        cmd_args.shared.auto_roots,
+    )?;
+    let auto_roots = cmd_args.shared.auto_roots || config.discovery.auto_roots;
+    check_config_paths(&config, home_dir, cmd_args.shared.strict)?;
+    let definitions = config.definitions(home_dir);
+    let labels = config.labels(home_dir);
 
-    let discovery_manager = DiscoveryManager::new(home_dir).add_definitions(&definitions);
+    let mut discovery_manager =
+        DiscoveryManager::with_loader(cmd_args.shared.select_loader()?, home_dir)
+            .add_definitions(&definitions);
+    if auto_roots {
+        discovery_manager = discovery_manager.add_auto_roots();
+    }
+    let discovery_manager = discovery_manager
+        .select_detectors(
+            config.discovery.enabled.as_deref(),
+            &config.discovery.disabled,
+        )
+        .use_time_source(cmd_args.shared.time_source)
+        .skip_hidden(cmd_args.shared.skip_hidden(config.discovery.skip_hidden))
+        .one_file_system(cmd_args.shared.one_file_system)
+        .git_aware(cmd_args.shared.git_aware)
+        .files_only_update(cmd_args.shared.ignore_dir_times)
+        .profile(cmd_args.shared.profile)
+        .max_results(cmd_args.shared.max_results())
+        .keep_empty_tools(cmd_args.all);
 
-    if ui_config.show_progress {
+    let mut excluded = cmd_args.shared.excluded_paths(home_dir);
+    if cmd_args.resolve_symlinks {
+        excluded = excluded.iter().map(|p| resolve_symlinks(p)).collect();
+    }
+    let stale_filter = cmd_args.shared.stale_filter();
+    let now = SystemTime::now();
+    let keep = |result: &DiscoveryResultEnvelop| {
+        !result.is_excluded(&excluded)
+            && stale_filter.is_none_or(|filter| filter.is_stale(&now, &result.last_update()))
+            && cmd_args
+                .since
+                .is_none_or(|since| is_updated_since(&result.last_update(), since))
+    };
+    let jsonl = cmd_args.format == OutputFormat::Jsonl;
+    if ui_config.show_progress && !cmd_args.stream && !jsonl && !cmd_args.quiet {
         let progress_channel = discovery_manager.subscribe();
         rayon::spawn(move || {
-            display_progress_bar(progress_channel);
+            display_progress(progress_channel);
         });
     }
 
-    let discovery_results = discovery_manager.collect();
-    if discovery_results.projects.is_empty() && discovery_results.tools.len() == 1 {
+    let mut discovery_results = discovery_manager.collect_with(|result| {
+        if cmd_args.resolve_symlinks {
+            result.resolve_symlinks();
+        }
+        if !keep(result) {
+            return;
+        }
+        if jsonl {
+            print_jsonl(out, result)
+        } else if cmd_args.stream {
+            print_discovered(out, result, cmd_args.unit)
+        }
+    });
+    if discovery_results.truncated
+        && let Some(max) = cmd_args.shared.max_results()
+    {
+        warn!("Results truncated at {max}; narrow your configuration or raise --max-results.");
+    }
+    discovery_results.exclude_paths(&excluded);
+    venv_postprocess(&mut discovery_results.projects);
+    if cmd_args.explain && !jsonl && !cmd_args.quiet {
+        print_detector_matches(out, &discovery_results.detector_matches);
+    }
+    let found_tools = discovery_results.tools.iter().filter(|r| r.size != 0);
+    if discovery_results.projects.is_empty() && found_tools.count() == 1 {
         error!("No results found.");
         return Err(AnalyzeError::NoResultsFound);
     }
-    print_results(out, discovery_results);
+    if let Some(filter) = stale_filter {
+        discovery_results.retain_stale(&filter, &now);
+    }
+    if let Some(since) = cmd_args.since {
+        discovery_results.retain_since(since);
+    }
+    let total: u64 = discovery_results
+        .projects
+        .iter()
+        .map(|r| r.size)
+        .chain(discovery_results.tools.iter().map(|r| r.size))
+        .sum();
+    let history = if cmd_args.shared.read_only {
+        load_history(home_dir)
+    } else {
+        update_history(home_dir, &discovery_results, &now)
+    };
+    if cmd_args.quiet {
+        writeln!(out, "{total}").expect("Cannot write to stdout");
+    } else if !jsonl {
+        // Computed upfront, as printing the tables consumes the results
+        let summary = cmd_args
+            .by_language
+            .then(|| language_summary(&discovery_results));
+        let duplicates = discovery_results
+            .db
+            .as_ref()
+            .filter(|_| cmd_args.find_duplicates)
+            .map(|db| find_duplicates(db, cmd_args.duplicates_min_size));
+        let largest = discovery_results
+            .db
+            .as_ref()
+            .zip(cmd_args.largest_dirs)
+            .map(|(db, count)| largest_dirs(db, &discovery_results.scan_roots, count));
+        let options = TableOptions {
+            time_format: ui_config.time_format,
+            group_by: cmd_args.group_by,
+            sort: cmd_args.sort,
+            color_mode: cmd_args.color_mode,
+            top: cmd_args.top,
+            hide_tooling: cmd_args.no_tooling,
+            hide_projects: cmd_args.no_projects,
+            with_commands: cmd_args.with_commands,
+            unit: cmd_args.unit,
+        };
+        if cmd_args.format == OutputFormat::Tree {
+            print_tree(out, &discovery_results, &options);
+        } else {
+            print_results(out, discovery_results, &options, &history, &labels);
+        }
+        if let Some(summary) = summary {
+            print_language_summary(out, &summary, options.unit);
+        }
+        if let Some(duplicates) = duplicates {
+            print_duplicates(out, &duplicates, cmd_args.top, options.unit);
+        }
+        if let Some(largest) = largest {
+            print_largest_dirs(out, &largest, options.unit);
+        }
+    }
+
+    if let Some(limit) = cmd_args.fail_over
+        && total > limit
+    {
+        return Err(AnalyzeError::SizeLimitExceeded { total, limit });
+    }
 
     Ok(())
 }
@@ -87,12 +253,13 @@ mod tests {
     use std::fs;
 
     use super::*;
+    use crate::test_utils::CurrentDir;
 
     #[test]
     fn test_show_default_definitions() {
         let tmp = tempfile::tempdir().unwrap();
         let root_dir = tmp.path();
-        std::env::set_current_dir(&root_dir).unwrap();
+        let _cwd = CurrentDir::set(root_dir);
 
         let mut buffer = Vec::new();
         show_default_definitions_inner(&mut buffer, root_dir);
@@ -114,7 +281,7 @@ mod tests {
     fn test_analyze_discovery() {
         let tmp = tempfile::tempdir().unwrap();
         let root_path = tmp.path();
-        std::env::set_current_dir(&root_path).unwrap();
+        let _cwd = CurrentDir::set(root_path);
 
         fs::create_dir_all(root_path.join("projects/python/venv/bin")).unwrap();
         fs::write(
@@ -141,7 +308,7 @@ discovery = true
             &mut buffer,
             &AnalyzeArgs::default(),
             &UiConfig::default(),
-            root_path,
+            Some(root_path),
         );
         assert_eq!(result.unwrap(), ());
 
@@ -157,4 +324,248 @@ discovery = true
         );
         assert!(output.contains(root_path.join(".cache/uv").display().to_string().as_str()));
     }
+
+    #[test]
+    fn test_analyze_fails_over_size_limit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+
+        fs::create_dir_all(root_path.join("projects/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("projects/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "projects/"
+        "#;
+        fs::write(root_path.join("custom.toml"), cfg_data).unwrap();
+
+        let mut cmd_args = AnalyzeArgs::default();
+        cmd_args.shared.config = Some(root_path.join("custom.toml"));
+
+        cmd_args.fail_over = Some(1_000_000);
+        let result = analyze_inner(
+            &mut Vec::new(),
+            &cmd_args,
+            &UiConfig::default(),
+            Some(root_path),
+        );
+        assert!(result.is_ok());
+
+        cmd_args.fail_over = Some(10);
+        let result = analyze_inner(
+            &mut Vec::new(),
+            &cmd_args,
+            &UiConfig::default(),
+            Some(root_path),
+        );
+        assert!(matches!(
+            result,
+            Err(AnalyzeError::SizeLimitExceeded { limit: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn test_analyze_quiet_prints_total() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+
+        fs::create_dir_all(root_path.join("projects/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("projects/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "projects/"
+        "#;
+        fs::write(root_path.join("custom.toml"), cfg_data).unwrap();
+
+        let mut cmd_args = AnalyzeArgs::default();
+        cmd_args.shared.config = Some(root_path.join("custom.toml"));
+        cmd_args.quiet = true;
+
+        let mut buffer = Vec::new();
+        let result = analyze_inner(
+            &mut buffer,
+            &cmd_args,
+            &UiConfig::default(),
+            Some(root_path),
+        );
+        assert!(result.is_ok());
+
+        let output = String::from_utf8(buffer).unwrap();
+        let total: u64 = output.trim_end().parse().unwrap();
+        assert!(total >= "Python executable mock".len() as u64);
+        assert_eq!(output.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_analyze_streams_results() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+
+        fs::create_dir_all(root_path.join("projects/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("projects/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "projects/"
+        "#;
+        fs::write(root_path.join("custom.toml"), cfg_data).unwrap();
+
+        let mut cmd_args = AnalyzeArgs::default();
+        cmd_args.shared.config = Some(root_path.join("custom.toml"));
+        cmd_args.stream = true;
+
+        let mut buffer = Vec::new();
+        let result = analyze_inner(
+            &mut buffer,
+            &cmd_args,
+            &UiConfig::default(),
+            Some(root_path),
+        );
+        assert!(result.is_ok());
+
+        let output = String::from_utf8(buffer).unwrap();
+        let venv_path = root_path.join("projects/python/venv").display().to_string();
+        let streamed = output.find(&format!("found: 🐍 {venv_path}")).unwrap();
+        let in_table = output.rfind(&venv_path).unwrap();
+        assert!(streamed < in_table);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_analyze_resolves_symlinked_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = fs::canonicalize(tmp.path()).unwrap();
+
+        fs::create_dir_all(root_path.join("real/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("real/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(root_path.join("real"), root_path.join("link")).unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "link/"
+        "#;
+        fs::write(root_path.join("custom.toml"), cfg_data).unwrap();
+
+        let mut cmd_args = AnalyzeArgs::default();
+        cmd_args.shared.config = Some(root_path.join("custom.toml"));
+        cmd_args.stream = true;
+
+        let run = |cmd_args: &AnalyzeArgs| {
+            let mut buffer = Vec::new();
+            let result = analyze_inner(
+                &mut buffer,
+                cmd_args,
+                &UiConfig::default(),
+                Some(&root_path),
+            );
+            assert!(result.is_ok());
+            String::from_utf8(buffer).unwrap()
+        };
+        let real_path = root_path.join("real/python/venv").display().to_string();
+        let link_path = root_path.join("link/python/venv").display().to_string();
+
+        let output = run(&cmd_args);
+        assert!(output.contains(&format!("found: 🐍 {link_path}")));
+
+        cmd_args.resolve_symlinks = true;
+        let output = run(&cmd_args);
+        assert!(output.contains(&format!("found: 🐍 {real_path}")));
+        assert!(!output.contains(&link_path));
+    }
+
+    #[test]
+    fn test_analyze_jsonl_output() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+
+        fs::create_dir_all(root_path.join("projects/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("projects/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "projects/"
+        "#;
+        fs::write(root_path.join("custom.toml"), cfg_data).unwrap();
+
+        let mut cmd_args = AnalyzeArgs::default();
+        cmd_args.shared.config = Some(root_path.join("custom.toml"));
+        cmd_args.format = OutputFormat::Jsonl;
+
+        let mut buffer = Vec::new();
+        let result = analyze_inner(
+            &mut buffer,
+            &cmd_args,
+            &UiConfig::default(),
+            Some(root_path),
+        );
+        assert!(result.is_ok());
+
+        let output = String::from_utf8(buffer).unwrap();
+        let records = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        let venv = records
+            .iter()
+            .find(|r| r["kind"] == "project")
+            .expect("The venv is reported as a project");
+        assert_eq!(
+            venv["path"],
+            root_path.join("projects/python/venv").display().to_string()
+        );
+        assert_eq!(venv["language"], "python");
+        assert!(venv["size"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_analyze_writes_to_output_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+
+        fs::create_dir_all(root_path.join("projects/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("projects/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "projects/"
+        "#;
+        fs::write(root_path.join("custom.toml"), cfg_data).unwrap();
+
+        let mut cmd_args = AnalyzeArgs::default();
+        cmd_args.shared.config = Some(root_path.join("custom.toml"));
+        cmd_args.output = Some(root_path.join("report.txt"));
+
+        let result = analyze(&cmd_args, &UiConfig::default(), Some(root_path));
+        assert!(result.is_ok());
+
+        let output = fs::read_to_string(root_path.join("report.txt")).unwrap();
+        let venv_path = root_path.join("projects/python/venv").display().to_string();
+        assert!(output.contains(&venv_path));
+
+        cmd_args.output = Some(root_path.join("missing/report.txt"));
+        let result = analyze(&cmd_args, &UiConfig::default(), Some(root_path));
+        assert!(matches!(
+            result,
+            Err(AnalyzeError::CannotWriteOutput { path, .. }) if path == root_path.join("missing/report.txt")
+        ));
+    }
 }
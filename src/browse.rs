@@ -1,16 +1,22 @@
 use std::io;
 use std::path::Path;
+use std::time::SystemTime;
 
 use crossbeam::sync::WaitGroup;
-use tracing::error;
+use ratatui::crossterm::{cursor, execute};
+use tracing::{error, warn};
 
-use crate::browse_tui::App;
+use crate::browse_tui::{App, AppSettings};
 use crate::cli::{BrowseArgs, UiConfig};
-use crate::config::{ConfigError, load_config_file};
+use crate::config::{ConfigError, check_config_paths, load_config_file_or_default};
 use crate::discovery::DiscoveryResults;
-use crate::discovery::{DiscoveryManager, ExternalDiscoveryDefinition};
-use crate::display::display_progress_bar;
-use crate::vcs_postprocess::vcs_postprocess;
+use crate::discovery::{DiscoveryManager, WalkOptions};
+use crate::display::display_progress;
+use crate::display_tools::Labels;
+use crate::history::{History, load_history, update_history};
+use crate::loader::LoaderError;
+use crate::vcs_postprocess::{assign_vcs_sizes, vcs_postprocess};
+use crate::venv_postprocess::venv_postprocess;
 
 #[derive(thiserror::Error, Debug)]
 pub enum BrowseError {
@@ -19,6 +25,11 @@ pub enum BrowseError {
         #[from]
         inner: ConfigError,
     },
+    #[error("{inner}")]
+    LoaderError {
+        #[from]
+        inner: LoaderError,
+    },
     #[error("No results found. Do you use one of the supported languages?")]
     NoResultsFound,
     #[error(
@@ -35,47 +46,139 @@ pub enum BrowseError {
 pub fn browse(
     cmd_args: &BrowseArgs,
     ui_config: &UiConfig,
-    home_dir: &Path,
+    home_dir: Option<&Path>,
 ) -> Result<(), BrowseError> {
-    let config = load_config_file(home_dir, cmd_args.shared.config.as_deref())?;
-    let definitions = config
-        .paths
-        .into_iter()
-        .map(|p| ExternalDiscoveryDefinition { path: p.path })
-        .collect::<Vec<_>>();
+    let (discovery_results, walk_options, labels) = collect_results(cmd_args, ui_config, home_dir)?;
+    let history = if cmd_args.shared.read_only {
+        load_history(home_dir)
+    } else {
+        update_history(home_dir, &discovery_results, &SystemTime::now())
+    };
+
+    start_tui(
+        discovery_results,
+        history,
+        AppSettings {
+            time_format: ui_config.time_format,
+            time_source: cmd_args.shared.time_source,
+            ignore_dir_times: cmd_args.shared.ignore_dir_times,
+            walk_options,
+            stale_filter: cmd_args.shared.stale_filter(),
+            browser_limit: cmd_args.limit_depth_display,
+            read_only: cmd_args.shared.read_only,
+            labels,
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Scan the configured paths for the results shown in the browser
+///
+/// Returns the results with the DB, the options used for the scan, so the browser
+/// can rescan single paths the same way, and the configured labels.
+fn collect_results(
+    cmd_args: &BrowseArgs,
+    ui_config: &UiConfig,
+    home_dir: Option<&Path>,
+) -> Result<(DiscoveryResults, WalkOptions, Labels), BrowseError> {
+    let config = load_config_file_or_default(
+        home_dir,
+        cmd_args.shared.config.as_deref(),
+        cmd_args.shared.auto_roots,
+    )?;
+    let auto_roots = cmd_args.shared.auto_roots || config.discovery.auto_roots;
+    check_config_paths(&config, home_dir, cmd_args.shared.strict)?;
+    let definitions = config.definitions(home_dir);
 
-    let discovery_manager = DiscoveryManager::new(home_dir).add_definitions(&definitions);
+    let mut discovery_manager =
+        DiscoveryManager::with_loader(cmd_args.shared.select_loader()?, home_dir)
+            .add_definitions(&definitions);
+    if auto_roots {
+        discovery_manager = discovery_manager.add_auto_roots();
+    }
+    // Used again for rescanning single paths in the TUI
+    let walk_options = WalkOptions {
+        skip_hidden: cmd_args.shared.skip_hidden(config.discovery.skip_hidden),
+        one_file_system: cmd_args.shared.one_file_system,
+        profile: false,
+    };
+    let discovery_manager = discovery_manager
+        .select_detectors(
+            config.discovery.enabled.as_deref(),
+            &config.discovery.disabled,
+        )
+        .use_time_source(cmd_args.shared.time_source)
+        .skip_hidden(walk_options.skip_hidden)
+        .one_file_system(walk_options.one_file_system)
+        .git_aware(cmd_args.shared.git_aware)
+        .files_only_update(cmd_args.shared.ignore_dir_times)
+        .profile(cmd_args.shared.profile)
+        .max_results(cmd_args.shared.max_results());
 
     let wg = WaitGroup::new();
     if ui_config.show_progress {
         let pg_worker = wg.clone();
         let progress_channel = discovery_manager.subscribe();
         rayon::spawn(move || {
-            display_progress_bar(progress_channel);
+            display_progress(progress_channel);
             drop(pg_worker);
         });
     }
 
-    let discovery_results = discovery_manager.collect();
+    let mut discovery_results = discovery_manager.collect();
+    discovery_results.exclude_paths(&cmd_args.shared.excluded_paths(home_dir));
     // Don't remove the following check, or rewrite .unwrap() lines in start_tui
     if discovery_results.db.is_none() {
         return Err(BrowseError::ProgrammerError);
     }
 
     wg.wait();
+    if discovery_results.truncated
+        && let Some(max) = cmd_args.shared.max_results()
+    {
+        warn!("Results truncated at {max}; narrow your configuration or raise --max-results.");
+    }
 
     if discovery_results.projects.is_empty() && discovery_results.tools.is_empty() {
         error!("No results found.");
         return Err(BrowseError::NoResultsFound);
     }
 
-    start_tui(discovery_results)?;
+    Ok((discovery_results, walk_options, config.labels(home_dir)))
+}
 
-    Ok(())
+/// The cursor hidden by the app isn't shown again by `ratatui::restore`
+fn show_cursor() {
+    let _ = execute!(io::stdout(), cursor::Show);
 }
 
-fn start_tui(discovery_results: DiscoveryResults) -> io::Result<()> {
+/// Exit code of a process interrupted by SIGINT, as used by shells
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+fn start_tui(
+    mut discovery_results: DiscoveryResults,
+    history: History,
+    settings: AppSettings,
+) -> io::Result<()> {
+    assign_vcs_sizes(&mut discovery_results.projects, &discovery_results.vcs);
+    venv_postprocess(&mut discovery_results.projects);
     let vcs_results = vcs_postprocess(&discovery_results.projects, discovery_results.vcs);
+    // Ctrl-C is handled by the app itself, but the process can still be interrupted or
+    // terminated from the outside
+    if let Err(e) = ctrlc::set_handler(|| {
+        ratatui::restore();
+        show_cursor();
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }) {
+        warn!("Unable to handle interrupts, the terminal may be left broken: {e}");
+    }
+    // ratatui restores the terminal on panic and calls this hook afterward
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        show_cursor();
+        hook(info);
+    }));
     ratatui::run(|terminal| {
         App::new(
             discovery_results.projects,
@@ -83,7 +186,89 @@ fn start_tui(discovery_results: DiscoveryResults) -> io::Result<()> {
             vcs_results,
             // Already checked in browse()
             discovery_results.db.unwrap(),
+            history,
+            settings,
         )
         .run(terminal)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_utils::CurrentDir;
+
+    #[test]
+    fn test_collect_results() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+        let _cwd = CurrentDir::set(root_path);
+
+        fs::create_dir_all(root_path.join("projects/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("projects/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+        fs::create_dir_all(root_path.join("projects/python/.git")).unwrap();
+        fs::create_dir_all(root_path.join(".cache/uv")).unwrap();
+        fs::write(
+            root_path.join(".cache/uv/CACHEDIR.TAG"),
+            "Signature: 8a477f597d28d172789f06886806bc55",
+        )
+        .unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "projects/"
+discovery = true
+
+[[labels]]
+path = "projects/python"
+label = "Python playground"
+        "#;
+        fs::write(root_path.join(".ddc.toml"), cfg_data).unwrap();
+
+        let (results, walk_options, labels) = collect_results(
+            &BrowseArgs::default(),
+            &UiConfig::default(),
+            Some(root_path),
+        )
+        .unwrap();
+
+        let project_paths: Vec<_> = results.projects.iter().map(|p| p.path.clone()).collect();
+        assert_eq!(project_paths, vec![root_path.join("projects/python/venv")]);
+        assert!(
+            results
+                .tools
+                .iter()
+                .any(|t| t.path == root_path.join(".cache/uv"))
+        );
+        assert_eq!(results.vcs.len(), 1);
+        assert_eq!(results.vcs[0].path, root_path.join("projects/python"));
+        assert!(results.db.is_some());
+        assert!(!walk_options.one_file_system);
+        assert_eq!(
+            labels.get(&root_path.join("projects/python/venv")),
+            Some("Python playground")
+        );
+    }
+
+    #[test]
+    fn test_collect_results_without_results() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/empty")).unwrap();
+        fs::write(
+            root_path.join("custom.toml"),
+            "[[paths]]\npath = \"projects/\"\n",
+        )
+        .unwrap();
+        let mut cmd_args = BrowseArgs::default();
+        cmd_args.shared.config = Some(root_path.join("custom.toml"));
+
+        let result = collect_results(&cmd_args, &UiConfig::default(), Some(root_path));
+        assert!(matches!(result, Err(BrowseError::NoResultsFound)));
+    }
+}
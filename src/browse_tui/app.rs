@@ -1,10 +1,10 @@
-use std::io;
 use std::option::Option;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time::Duration;
+use std::{fs, io};
 
-use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::{
     DefaultTerminal, Frame,
@@ -15,13 +15,26 @@ use crate::browse_tui::component::Component;
 use crate::browse_tui::components::filter_bar::FilterBar;
 use crate::browse_tui::components::sort_modal::SortModal;
 use crate::browse_tui::components::{
-    DirectoryBrowser, Footer, Header, HelpModal, InfoModal, ProjectsTab, ToolingTab, VcsTab,
+    ConfirmModal, DirectoryBrowser, Footer, Header, HelpModal, InfoModal, ProjectsTab, ToolingTab,
+    VcsTab,
 };
-use crate::browse_tui::message::{AppMessage, SortBy, Tab};
-use crate::discovery::{ProjectResult, ToolingResult};
+use crate::browse_tui::helpers::empty_state;
+use crate::browse_tui::message::{AppMessage, Cleaned, Rescanned, SortBy, Tab};
+use crate::discovery::{ProjectResult, ToolingResult, WalkOptions};
+use crate::display_tools::{Labels, StaleFilter, TimeFormat};
+#[cfg(unix)]
+use crate::file_info::{Permissions, current_uid};
+use crate::file_info::{SizeMode, TimeSource};
 use crate::files_db::FilesDB;
+use crate::history::History;
+use crate::loader::reload_subtree;
 use crate::vcs_postprocess::EnrichedVcsResult;
 
+/// Time spent on computing directory sizes between two redraws
+const SIZE_RESOLVE_BUDGET: Duration = Duration::from_millis(50);
+
+const READ_ONLY_ERROR: &str = "Deleting is disabled in the read-only mode.";
+
 #[derive(Debug, Default, Eq, PartialEq)]
 enum RunningState {
     #[default]
@@ -34,6 +47,7 @@ enum Modal {
     Help(HelpModal),
     Info(InfoModal),
     Sort(SortModal),
+    Confirm(ConfirmModal),
 }
 
 #[derive(Debug)]
@@ -45,6 +59,7 @@ enum Message {
     DirectoryBrowser(<DirectoryBrowser as Component>::Message),
     InfoModal(<InfoModal as Component>::Message),
     SortModal(<SortModal as Component>::Message),
+    ConfirmModal(<ConfirmModal as Component>::Message),
     Filter(<FilterBar as Component>::Message),
 }
 
@@ -56,6 +71,24 @@ enum UiLayer {
     Modal(Modal),
 }
 
+/// Settings of the app given on the command line
+#[derive(Debug, Default)]
+pub struct AppSettings {
+    pub time_format: TimeFormat,
+    pub time_source: TimeSource,
+    /// Take the last update from files only
+    pub ignore_dir_times: bool,
+    /// Options for rescanning single paths
+    pub walk_options: WalkOptions,
+    pub stale_filter: Option<StaleFilter>,
+    /// Maximum number of items listed per directory in the browser; 0 lists all
+    pub browser_limit: usize,
+    /// Refuse any change on the disk
+    pub read_only: bool,
+    /// Configured labels of the projects
+    pub labels: Labels,
+}
+
 #[derive(Debug)]
 pub struct App {
     // Basic application state
@@ -72,8 +105,17 @@ pub struct App {
     filter: FilterBar,
     // Helper data
     error_message: Option<String>,
+    status_message: Option<String>,
     // Persisting inputs
     db: Rc<FilesDB>,
+    time_format: TimeFormat,
+    time_source: TimeSource,
+    ignore_dir_times: bool,
+    walk_options: WalkOptions,
+    browser_limit: usize,
+    read_only: bool,
+    // Toggled by the user
+    size_mode: SizeMode,
 }
 
 impl App {
@@ -82,7 +124,11 @@ impl App {
         tooling_data: Vec<ToolingResult>,
         vcs_result: Vec<EnrichedVcsResult>,
         db: FilesDB,
+        history: History,
+        settings: AppSettings,
     ) -> Self {
+        let time_format = settings.time_format;
+        let history = Rc::new(history);
         Self {
             running_state: RunningState::default(),
             layers: vec![UiLayer::Tab],
@@ -94,13 +140,27 @@ impl App {
                 vcs_result.len(),
             ),
             footer: Footer::new(),
-            projects_tab: ProjectsTab::new(projects_data),
-            tooling_tab: ToolingTab::new(tooling_data),
-            vcs_tab: VcsTab::new(vcs_result),
+            projects_tab: ProjectsTab::new(
+                projects_data,
+                time_format,
+                settings.stale_filter,
+                history.clone(),
+                settings.labels,
+            ),
+            tooling_tab: ToolingTab::new(tooling_data, time_format, history),
+            vcs_tab: VcsTab::new(vcs_result, time_format),
             browser: None,
             filter: FilterBar::new(),
             error_message: None,
+            status_message: None,
             db: Rc::new(db),
+            time_format,
+            time_source: settings.time_source,
+            ignore_dir_times: settings.ignore_dir_times,
+            walk_options: settings.walk_options,
+            browser_limit: settings.browser_limit,
+            read_only: settings.read_only,
+            size_mode: SizeMode::default(),
         }
     }
 
@@ -108,7 +168,11 @@ impl App {
         while self.running_state != RunningState::Done {
             terminal.draw(|frame| self.draw(frame))?;
 
-            let mut current_msg = self.handle_events()?;
+            let pending_work = self
+                .browser
+                .as_mut()
+                .is_some_and(|browser| browser.resolve_sizes(SIZE_RESOLVE_BUDGET));
+            let mut current_msg = self.handle_events(pending_work)?;
 
             while current_msg.is_some() {
                 current_msg = self.update(current_msg.unwrap());
@@ -118,9 +182,18 @@ impl App {
         Ok(())
     }
 
-    fn handle_events(&mut self) -> io::Result<Option<Message>> {
-        if event::poll(Duration::from_millis(100))? {
+    fn handle_events(&mut self, pending_work: bool) -> io::Result<Option<Message>> {
+        // Don't wait for input if there is work to be shown on the next redraw
+        let timeout = if pending_work {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(100)
+        };
+        if event::poll(timeout)? {
             match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press && is_interrupt(&key) => {
+                    return Ok(Some(Message::AppMessage(AppMessage::Quit)));
+                }
                 Event::Key(key) if key.kind == KeyEventKind::Press => {
                     return Ok(self.handle_key(key.code));
                 }
@@ -155,6 +228,9 @@ impl App {
             UiLayer::Modal(Modal::Sort(sort_modal)) => {
                 sort_modal.handle_key(key).map(Message::SortModal)
             }
+            UiLayer::Modal(Modal::Confirm(confirm_modal)) => {
+                confirm_modal.handle_key(key).map(Message::ConfirmModal)
+            }
         };
         if message.is_none() {
             message = match key {
@@ -170,6 +246,7 @@ impl App {
                     Some(Message::AppMessage(AppMessage::SelectTab(Tab::Vcs)))
                 }
                 KeyCode::Char('?') => Some(Message::AppMessage(AppMessage::OpenHelp)),
+                KeyCode::Char('z') => Some(Message::AppMessage(AppMessage::ToggleSizeMode)),
                 KeyCode::Esc => Some(Message::AppMessage(AppMessage::CloseModal)),
                 _ => None,
             };
@@ -178,8 +255,9 @@ impl App {
     }
 
     fn update(&mut self, message: Message) -> Option<Message> {
-        // Clear error message from previous update
+        // Clear error and status messages from previous update
         self.error_message = None;
+        self.status_message = None;
 
         match message {
             Message::AppMessage(msg) => self.handle_app_message(msg),
@@ -208,6 +286,14 @@ impl App {
                     None
                 }
             }
+            Message::ConfirmModal(msg) => {
+                if let Some(UiLayer::Modal(Modal::Confirm(confirm_modal))) = self.layers.last_mut()
+                {
+                    confirm_modal.update(msg).map(Message::AppMessage)
+                } else {
+                    None
+                }
+            }
         }
     }
 
@@ -216,10 +302,18 @@ impl App {
             AppMessage::Quit => self.quit(),
             AppMessage::Refresh => {}
             AppMessage::SetError(err) => self.error_message = Some(err),
+            AppMessage::SetStatus(status) => self.status_message = Some(status),
             AppMessage::CloseModal => self.close_modal(),
             AppMessage::OpenHelp => self.open_help(),
             AppMessage::CloseBrowser => self.close_browser(),
             AppMessage::EnterBrowser(path) => self.enter_browser(path),
+            AppMessage::RescanPath(path) => {
+                return self.rescan_path(path);
+            }
+            AppMessage::ConfirmClean { paths, size } => self.confirm_clean(paths, size),
+            AppMessage::CleanPaths(paths) => {
+                return self.clean_paths(paths);
+            }
             AppMessage::SelectTab(i) => self.select_tab(i),
             AppMessage::SelectTabWithFilter(tab, filter) => {
                 self.select_tab_with_filter(tab, filter)
@@ -234,6 +328,7 @@ impl App {
             }
             AppMessage::AcceptFilter => self.accept_filter(),
             AppMessage::DismissFilter => self.dismiss_filter(),
+            AppMessage::ToggleSizeMode => self.toggle_size_mode(),
         }
         None
     }
@@ -256,7 +351,13 @@ impl App {
     }
 
     fn enter_browser(&mut self, path: PathBuf) {
-        match DirectoryBrowser::new(self.db.clone(), path) {
+        match DirectoryBrowser::new(
+            self.db.clone(),
+            path,
+            self.time_format,
+            self.size_mode,
+            self.browser_limit,
+        ) {
             Ok(browser) => {
                 self.browser = Some(browser);
                 self.layers.push(UiLayer::Browser);
@@ -272,6 +373,111 @@ impl App {
         }
     }
 
+    /// Walk `path` again and let the current tab update its result
+    fn rescan_path(&mut self, path: PathBuf) -> Option<Message> {
+        // The browser shares the DB, but it's always closed while a tab is in control
+        let Some(db) = Rc::get_mut(&mut self.db) else {
+            self.error_message = Some(String::from("Unable to rescan while browsing."));
+            return None;
+        };
+        reload_subtree(db, &path, self.walk_options);
+
+        let rescanned = if db.exists(&path) {
+            let parent_size = path
+                .parent()
+                .filter(|parent| db.exists(parent))
+                .map(|parent| {
+                    (
+                        db.dir_size(parent),
+                        db.dir_size_in(parent, SizeMode::Allocated),
+                    )
+                });
+            Rescanned::Updated {
+                size: db.dir_size(&path),
+                allocated_size: db.dir_size_in(&path, SizeMode::Allocated),
+                last_update: db.last_update(&path, self.time_source, self.ignore_dir_times),
+                parent_size,
+                path,
+            }
+        } else {
+            Rescanned::Missing(path)
+        };
+        match self.selected_tab {
+            Tab::Projects => Some(Message::ProjectsTab(
+                <ProjectsTab as Component>::Message::ApplyRescan(rescanned),
+            )),
+            Tab::Tooling => Some(Message::ToolingTab(
+                <ToolingTab as Component>::Message::ApplyRescan(rescanned),
+            )),
+            Tab::Vcs => None,
+        }
+    }
+
+    fn confirm_clean(&mut self, paths: Vec<PathBuf>, size: u64) {
+        if self.read_only {
+            self.error_message = Some(String::from(READ_ONLY_ERROR));
+            return;
+        }
+        let needs_sudo = paths
+            .iter()
+            .filter(|path| requires_sudo(&self.db, path))
+            .cloned()
+            .collect();
+        self.layers
+            .push(UiLayer::Modal(Modal::Confirm(ConfirmModal::new(
+                paths, size, needs_sudo,
+            ))));
+    }
+
+    /// Remove `paths` from the disk and let the current tab drop their results
+    fn clean_paths(&mut self, paths: Vec<PathBuf>) -> Option<Message> {
+        self.close_modal();
+        if self.read_only {
+            self.error_message = Some(String::from(READ_ONLY_ERROR));
+            return None;
+        }
+        let Some(db) = Rc::get_mut(&mut self.db) else {
+            self.error_message = Some(String::from("Unable to clean while browsing."));
+            return None;
+        };
+
+        let mut cleaned = Cleaned::default();
+        for path in paths {
+            // Removing would fail midway and leave a partially deleted directory behind
+            if requires_sudo(db, &path) {
+                cleaned.failed.push((path, String::from("requires sudo")));
+                continue;
+            }
+            match remove_path(&path) {
+                Ok(()) => {
+                    db.remove_subtree(&path);
+                    cleaned.removed.push(path);
+                }
+                Err(e) => cleaned.failed.push((path, e.to_string())),
+            }
+        }
+        match self.selected_tab {
+            Tab::Projects => Some(Message::ProjectsTab(
+                <ProjectsTab as Component>::Message::ApplyClean(cleaned),
+            )),
+            Tab::Tooling => Some(Message::ToolingTab(
+                <ToolingTab as Component>::Message::ApplyClean(cleaned),
+            )),
+            Tab::Vcs => None,
+        }
+    }
+
+    /// Switch all the shown sizes between the apparent ones and the space on the disk
+    fn toggle_size_mode(&mut self) {
+        self.size_mode = self.size_mode.toggled();
+        self.projects_tab.set_size_mode(self.size_mode);
+        self.tooling_tab.set_size_mode(self.size_mode);
+        self.vcs_tab.set_size_mode(self.size_mode);
+        if let Some(browser) = self.browser.as_mut() {
+            browser.set_size_mode(self.size_mode);
+        }
+    }
+
     fn select_tab(&mut self, tab: Tab) {
         if self.selected_tab != tab {
             self.filter.clear();
@@ -313,6 +519,9 @@ impl App {
                     sort_by,
                 ))),
             },
+            Some(UiLayer::Browser) => Some(Message::DirectoryBrowser(
+                <DirectoryBrowser as Component>::Message::ApplySort(sort_by),
+            )),
             _ => None,
         }
     }
@@ -337,6 +546,8 @@ impl App {
     fn draw(&mut self, frame: &mut Frame) {
         // Handle data exchange among components
         self.footer.set_error(self.error_message.clone());
+        self.footer.set_status(self.status_message.clone());
+        self.footer.set_size_mode(self.size_mode);
         self.header.set_selected_tab(self.selected_tab);
         self.header
             .set_browser_path(self.browser.as_mut().and_then(|b| b.get_current_path()));
@@ -348,22 +559,15 @@ impl App {
         if let Some(browser) = self.browser.as_mut() {
             browser.render(frame, chunks[1]);
         } else {
-            match self.selected_tab {
-                Tab::Projects => {
-                    self.projects_tab.apply_filter(self.filter.get_filter());
-                    self.projects_tab.render(frame, chunks[1]);
-                }
-                Tab::Tooling => self.tooling_tab.render(frame, chunks[1]),
-                Tab::Vcs => {
-                    self.vcs_tab.apply_filter(self.filter.get_filter());
-                    self.vcs_tab.render(frame, chunks[1]);
-                }
-            }
+            self.render_results(frame, chunks[1]);
         }
         match self.layers.last_mut() {
             Some(UiLayer::Modal(Modal::Help(help_modal))) => help_modal.render(frame, chunks[1]),
             Some(UiLayer::Modal(Modal::Info(info_modal))) => info_modal.render(frame, chunks[1]),
             Some(UiLayer::Modal(Modal::Sort(sort_modal))) => sort_modal.render(frame, chunks[1]),
+            Some(UiLayer::Modal(Modal::Confirm(confirm_modal))) => {
+                confirm_modal.render(frame, chunks[1])
+            }
             _ => {}
         }
         if self.filter.is_active() {
@@ -374,6 +578,45 @@ impl App {
         }
     }
 
+    /// Render the selected tab, or a hint when it has no results at all
+    fn render_results(&mut self, frame: &mut Frame, area: Rect) {
+        match self.selected_tab {
+            Tab::Projects if self.projects_tab.is_empty() => frame.render_widget(
+                empty_state(
+                    " Projects ",
+                    "No projects found",
+                    "Add the directories with your projects to the paths in the configuration file, or try --auto-roots.",
+                ),
+                area,
+            ),
+            Tab::Projects => {
+                self.projects_tab.apply_filter(self.filter.get_filter());
+                self.projects_tab.render(frame, area);
+            }
+            Tab::Tooling if self.tooling_tab.is_empty() => frame.render_widget(
+                empty_state(
+                    " Tools ",
+                    "No tooling caches found",
+                    "None of the known tool locations exist. Run `ddc show-definitions` to list them.",
+                ),
+                area,
+            ),
+            Tab::Tooling => self.tooling_tab.render(frame, area),
+            Tab::Vcs if self.vcs_tab.is_empty() => frame.render_widget(
+                empty_state(
+                    " Version controlled ",
+                    "No repositories found",
+                    "Repositories are looked up in the configured paths.",
+                ),
+                area,
+            ),
+            Tab::Vcs => {
+                self.vcs_tab.apply_filter(self.filter.get_filter());
+                self.vcs_tab.render(frame, area);
+            }
+        }
+    }
+
     fn create_layout(&self, area: Rect, with_filter: bool) -> Vec<Rect> {
         if with_filter {
             Layout::default()
@@ -399,3 +642,185 @@ impl App {
         }
     }
 }
+
+/// Ctrl-C doesn't send SIGINT in the raw mode, it's read as a key press instead
+fn is_interrupt(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Whether the user can't remove some content of `path`, e.g. of a cache created by root
+#[cfg(unix)]
+fn requires_sudo(db: &FilesDB, path: &Path) -> bool {
+    let uid = current_uid();
+    // Removing `path` itself changes its parent, which may be outside of the scanned paths
+    let parent_writable = path.parent().is_none_or(|parent| {
+        db.iter_dir(parent)
+            .next()
+            .map(|fi| fi.permissions)
+            .or_else(|| fs::metadata(parent).ok().map(|m| Permissions::from(&m)))
+            .is_none_or(|permissions| permissions.is_writable_by(uid))
+    });
+    !parent_writable
+        || db
+            .iter_directories(path)
+            .any(|fi| !fi.permissions.is_writable_by(uid))
+}
+
+#[cfg(not(unix))]
+fn requires_sudo(_db: &FilesDB, _path: &Path) -> bool {
+    false
+}
+
+/// Remove a directory with its content, or a single file
+fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_info::{FileMeta, Permissions, get_file_meta};
+
+    #[test]
+    fn test_is_interrupt() {
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(is_interrupt(&ctrl_c));
+        // Plain `c` is left to the components
+        assert!(!is_interrupt(&KeyEvent::from(KeyCode::Char('c'))));
+        assert!(!is_interrupt(&KeyEvent::new(
+            KeyCode::Char('x'),
+            KeyModifiers::CONTROL
+        )));
+    }
+
+    #[test]
+    fn test_clean_is_refused_in_read_only_mode() {
+        let tmp = tempfile::tempdir().unwrap();
+        let target = tmp.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+        let mut db = FilesDB::new();
+        db.add(target.clone(), get_file_meta(&target).unwrap());
+        let mut app = App::new(
+            vec![],
+            vec![],
+            vec![],
+            db,
+            History::default(),
+            AppSettings {
+                time_format: TimeFormat::default(),
+                time_source: TimeSource::default(),
+                ignore_dir_times: false,
+                walk_options: WalkOptions::default(),
+                stale_filter: None,
+                browser_limit: 0,
+                read_only: true,
+                labels: Labels::default(),
+            },
+        );
+
+        app.confirm_clean(vec![target.clone()], 0);
+        assert_eq!(app.error_message.as_deref(), Some(READ_ONLY_ERROR));
+        assert!(!matches!(app.layers.last(), Some(UiLayer::Modal(_))));
+
+        app.error_message = None;
+        assert!(app.clean_paths(vec![target.clone()]).is_none());
+        assert_eq!(app.error_message.as_deref(), Some(READ_ONLY_ERROR));
+        assert!(target.exists());
+    }
+
+    #[test]
+    fn test_toggle_size_mode() {
+        let mut app = App::new(
+            vec![],
+            vec![],
+            vec![],
+            FilesDB::new(),
+            History::default(),
+            AppSettings::default(),
+        );
+        assert_eq!(app.size_mode, SizeMode::Apparent);
+
+        let message = app.handle_key(KeyCode::Char('z')).unwrap();
+        app.update(message);
+        assert_eq!(app.size_mode, SizeMode::Allocated);
+        let message = app.handle_key(KeyCode::Char('z')).unwrap();
+        app.update(message);
+        assert_eq!(app.size_mode, SizeMode::Apparent);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_requires_sudo() {
+        let uid = current_uid();
+        let dir = |uid| FileMeta {
+            is_dir: true,
+            size: None,
+            allocated: None,
+            touched: None,
+            accessed: None,
+            permissions: Permissions { uid, mode: 0o40755 },
+        };
+        let mut db = FilesDB::new();
+        db.add(PathBuf::from("/cache"), dir(uid));
+        db.add(PathBuf::from("/cache/own"), dir(uid));
+        db.add(PathBuf::from("/cache/foreign"), dir(uid + 1));
+
+        assert!(!requires_sudo(&db, Path::new("/cache/own")));
+        if uid != 0 {
+            assert!(requires_sudo(&db, Path::new("/cache/foreign")));
+            assert!(requires_sudo(&db, Path::new("/cache")));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_requires_sudo_in_foreign_parent() {
+        let uid = current_uid();
+        if uid == 0 {
+            return;
+        }
+        let dir = |uid| FileMeta {
+            is_dir: true,
+            size: None,
+            allocated: None,
+            touched: None,
+            accessed: None,
+            permissions: Permissions { uid, mode: 0o40755 },
+        };
+        let mut db = FilesDB::new();
+        db.add(PathBuf::from("/system"), dir(0));
+        db.add(PathBuf::from("/system/own"), dir(uid));
+
+        // The content could be deleted, but not the directory itself
+        assert!(requires_sudo(&db, Path::new("/system/own")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_requires_sudo_read_only_own_directory() {
+        let uid = current_uid();
+        if uid == 0 {
+            return;
+        }
+        let dir = |mode| FileMeta {
+            is_dir: true,
+            size: None,
+            allocated: None,
+            touched: None,
+            accessed: None,
+            permissions: Permissions { uid, mode },
+        };
+        let mut db = FilesDB::new();
+        db.add(PathBuf::from("/cache"), dir(0o40755));
+        db.add(PathBuf::from("/cache/read_only"), dir(0o40555));
+        db.add(PathBuf::from("/cache/read_only/build"), dir(0o40755));
+
+        assert!(requires_sudo(&db, Path::new("/cache/read_only")));
+        // Entries of the read-only directory can't be removed either
+        assert!(requires_sudo(&db, Path::new("/cache/read_only/build")));
+    }
+}
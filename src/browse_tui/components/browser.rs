@@ -1,6 +1,7 @@
-use std::path::PathBuf;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 
 use humansize::{DECIMAL, format_size};
 use ratatui::layout::{Constraint, Rect};
@@ -13,9 +14,12 @@ use ratatui::{Frame, crossterm::event::KeyCode};
 
 use crate::browse_tui::component::{Component, Navigable};
 use crate::browse_tui::helpers;
-use crate::browse_tui::helpers::{last_update_cell, now, size_cell};
-use crate::browse_tui::message::AppMessage;
-use crate::file_info::FileInfo;
+use crate::browse_tui::helpers::{
+    find_next_by_letter, last_update_cell, now, position_indicator, size_cell,
+};
+use crate::browse_tui::message::{AppMessage, SortBy, SortDirection};
+use crate::display_tools::TimeFormat;
+use crate::file_info::{FileInfo, SizeMode};
 use crate::files_db::FilesDB;
 
 #[derive(Debug)]
@@ -23,29 +27,109 @@ pub struct DirectoryBrowser {
     db: Rc<FilesDB>,
     frames: Vec<DirectoryBrowserFrame>,
     page_size: u16,
+    time_format: TimeFormat,
+    size_mode: SizeMode,
+    jump_pending: bool,
+    sort_by: SortBy,
+    sort_direction: SortDirection,
+    /// Maximum number of items listed per directory; 0 lists all of them
+    limit: usize,
 }
 
 impl DirectoryBrowser {
-    pub fn new(db: Rc<FilesDB>, path: PathBuf) -> Result<Self, String> {
-        let frame = open_path(db.clone(), path)?;
+    const SORT_OPTIONS: [SortBy; 2] = [SortBy::Name, SortBy::Size];
+
+    pub fn new(
+        db: Rc<FilesDB>,
+        path: PathBuf,
+        time_format: TimeFormat,
+        size_mode: SizeMode,
+        limit: usize,
+    ) -> Result<Self, String> {
+        let mut frame = open_path(db.clone(), path, size_mode, limit)?;
+        let sort_by = SortBy::Name;
+        let sort_direction = sort_by.default_direction();
+        sort_frame(&mut frame, sort_by, sort_direction, size_mode);
         Ok(Self {
             db,
             frames: vec![frame],
             page_size: 0,
+            time_format,
+            size_mode,
+            jump_pending: false,
+            sort_by,
+            sort_direction,
+            limit,
         })
     }
 
+    /// Compute sizes of directories which weren't sized yet
+    ///
+    /// Directory sizes are computed lazily, so entering a directory with
+    /// thousands of subdirectories doesn't block the UI. The selected item is
+    /// sized first, then the rest in the list order and the total of the hidden items
+    /// last, until the `budget` is exhausted. Returns `true` if some sizes are still missing.
+    pub fn resolve_sizes(&mut self, budget: Duration) -> bool {
+        let Some(frame) = self.frames.last_mut() else {
+            return false;
+        };
+
+        let started = Instant::now();
+        let mut resolved_any = false;
+        let order = frame
+            .state
+            .selected()
+            .into_iter()
+            .chain(0..frame.directory_list.len());
+        for idx in order {
+            if started.elapsed() >= budget {
+                break;
+            }
+            if let Some(item) = frame.directory_list.get_mut(idx)
+                && item.is_size_pending()
+            {
+                item.size = Some(self.db.dir_size(&item.path));
+                item.allocated_size = Some(self.db.dir_size_in(&item.path, SizeMode::Allocated));
+                resolved_any = true;
+            }
+        }
+        let visible_pending = frame.directory_list.iter().any(DirItem::is_size_pending);
+        if !visible_pending && frame.hidden.is_size_pending() && started.elapsed() < budget {
+            frame
+                .hidden
+                .resolve(&self.db, &frame.cwd, &frame.directory_list);
+        }
+        frame.sum = frame.size(self.size_mode);
+        if resolved_any && self.sort_by == SortBy::Size {
+            sort_frame(frame, self.sort_by, self.sort_direction, self.size_mode);
+        }
+
+        visible_pending || frame.hidden.is_size_pending()
+    }
+
+    /// Show sizes in `size_mode`; the order of the items can change
+    pub fn set_size_mode(&mut self, size_mode: SizeMode) {
+        self.size_mode = size_mode;
+        for frame in self.frames.iter_mut() {
+            frame.sum = frame.size(size_mode);
+            sort_frame(frame, self.sort_by, self.sort_direction, size_mode);
+        }
+    }
+
     pub fn get_current_path(&mut self) -> Option<PathBuf> {
         self.frames.last().map(|frame| frame.cwd.clone())
     }
 
     pub fn enter(&mut self) -> Option<AppMessage> {
         let path = if let Some(frame) = self.frames.last_mut() {
-            let Some(item) = frame
-                .state
-                .selected()
-                .and_then(|idx| frame.directory_list.get(idx))
-            else {
+            let selected = frame.state.selected();
+            if selected == Some(frame.directory_list.len()) && frame.hidden.count > 0 {
+                return Some(AppMessage::SetError(format!(
+                    "Only the first {} items are listed.",
+                    self.limit
+                )));
+            }
+            let Some(item) = selected.and_then(|idx| frame.directory_list.get(idx)) else {
                 return Some(AppMessage::SetError(String::from("No item selected.")));
             };
 
@@ -64,8 +148,14 @@ impl DirectoryBrowser {
             return Some(AppMessage::SetError(String::from("No item selected.")));
         };
 
-        match open_path(self.db.clone(), path) {
-            Ok(frame) => {
+        match open_path(self.db.clone(), path, self.size_mode, self.limit) {
+            Ok(mut frame) => {
+                sort_frame(
+                    &mut frame,
+                    self.sort_by,
+                    self.sort_direction,
+                    self.size_mode,
+                );
                 self.frames.push(frame);
                 None
             }
@@ -73,6 +163,41 @@ impl DirectoryBrowser {
         }
     }
 
+    fn request_sort(&mut self) -> Option<AppMessage> {
+        Some(AppMessage::OpenSort(&Self::SORT_OPTIONS))
+    }
+
+    fn apply_sort(&mut self, sort_by: SortBy) {
+        if self.sort_by == sort_by {
+            self.sort_direction = match self.sort_direction {
+                SortDirection::Ascending => SortDirection::Descending,
+                SortDirection::Descending => SortDirection::Ascending,
+            };
+        } else {
+            self.sort_by = sort_by;
+            self.sort_direction = sort_by.default_direction();
+        }
+
+        for frame in self.frames.iter_mut() {
+            sort_frame(frame, self.sort_by, self.sort_direction, self.size_mode);
+        }
+    }
+
+    /// Select the next item whose name starts with `letter`
+    pub fn jump_to(&mut self, letter: char) {
+        if let Some(frame) = self.frames.last_mut() {
+            let names = frame
+                .directory_list
+                .iter()
+                .map(|item| item.name.as_str())
+                .collect::<Vec<_>>();
+            if let Some(idx) = find_next_by_letter(&names, frame.state.selected(), letter) {
+                frame.state.select(Some(idx));
+                sync_scroll(frame);
+            }
+        }
+    }
+
     pub fn back(&mut self) -> Option<AppMessage> {
         self.frames.pop();
         if self.frames.is_empty() {
@@ -82,27 +207,92 @@ impl DirectoryBrowser {
     }
 }
 
-fn open_path(db: Rc<FilesDB>, path: PathBuf) -> Result<DirectoryBrowserFrame, String> {
-    let directory_list: Vec<_> = db
-        .iter_level(&path)
-        .map(|fi| DirItem::from_file_info(&fi, &db))
-        .collect();
-
-    if directory_list.is_empty() {
+/// Open `path`, listing at most `limit` of its items; 0 lists all of them
+///
+/// Only the listed items are built, the rest is summarized in a single row, so huge
+/// directories stay responsive. Directories are listed first, as their sizes aren't
+/// known upfront, and the biggest files fill up the remaining rows.
+fn open_path(
+    db: Rc<FilesDB>,
+    path: PathBuf,
+    size_mode: SizeMode,
+    limit: usize,
+) -> Result<DirectoryBrowserFrame, String> {
+    let mut entries: Vec<_> = db.iter_level(&path).collect();
+    if entries.is_empty() {
         return Err(String::from("Directory is empty."));
     }
 
-    Ok(DirectoryBrowserFrame {
+    let mut hidden = HiddenItems::default();
+    if limit != 0 && entries.len() > limit {
+        entries.select_nth_unstable_by_key(limit, |fi| {
+            (!fi.is_dir, std::cmp::Reverse(fi.size.unwrap_or_default()))
+        });
+        hidden = HiddenItems::new(&entries.split_off(limit));
+    }
+    let directory_list: Vec<_> = entries.iter().map(DirItem::from_file_info).collect();
+
+    let mut frame = DirectoryBrowserFrame {
         state: {
             let mut browser_sate = TableState::default();
             browser_sate.select(Some(0));
             browser_sate
         },
-        scroll_state: ScrollbarState::new(directory_list.len()),
+        scroll_state: ScrollbarState::new(directory_list.len() + usize::from(hidden.count > 0)),
         cwd: path.clone(),
-        sum: directory_list.iter().filter_map(|i| i.size).sum(),
+        sum: 0,
         directory_list,
-    })
+        hidden,
+    };
+    frame.sum = frame.size(size_mode);
+    Ok(frame)
+}
+
+/// Sort items of the frame while keeping the selected item selected
+///
+/// Sorting by name lists directories first, like file managers do.
+/// Directories without a computed size are sorted as empty.
+fn sort_frame(
+    frame: &mut DirectoryBrowserFrame,
+    sort_by: SortBy,
+    direction: SortDirection,
+    size_mode: SizeMode,
+) {
+    let selected_path = frame
+        .state
+        .selected()
+        .and_then(|idx| frame.directory_list.get(idx))
+        .map(|item| item.path.clone());
+
+    let by_name = |a: &DirItem, b: &DirItem| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.name.cmp(&b.name))
+    };
+    let directories_first = |a: &DirItem, b: &DirItem| b.is_directory.cmp(&a.is_directory);
+    let reverse = |ordering: Ordering| match direction {
+        SortDirection::Ascending => ordering,
+        SortDirection::Descending => ordering.reverse(),
+    };
+    let size = |item: &DirItem| item.size_in(size_mode).unwrap_or_default();
+    match sort_by {
+        SortBy::Size => frame
+            .directory_list
+            .sort_by(|a, b| reverse(size(a).cmp(&size(b))).then_with(|| by_name(a, b))),
+        _ => frame
+            .directory_list
+            .sort_by(|a, b| directories_first(a, b).then_with(|| reverse(by_name(a, b)))),
+    }
+
+    if let Some(path) = selected_path {
+        let idx = frame
+            .directory_list
+            .iter()
+            .position(|item| item.path == path);
+        frame.state.select(idx.or(Some(0)));
+    }
+    sync_scroll(frame);
 }
 
 #[derive(Debug)]
@@ -115,6 +305,9 @@ pub enum DirectoryBrowserMessage {
     End,
     Enter,
     Back,
+    JumpTo(char),
+    RequestSort,
+    ApplySort(SortBy),
 }
 
 impl Component for DirectoryBrowser {
@@ -134,16 +327,33 @@ impl Component for DirectoryBrowser {
             DirectoryBrowserMessage::Back => {
                 return self.back();
             }
+            DirectoryBrowserMessage::JumpTo(letter) => self.jump_to(letter),
+            DirectoryBrowserMessage::RequestSort => {
+                return self.request_sort();
+            }
+            DirectoryBrowserMessage::ApplySort(sort_by) => self.apply_sort(sort_by),
         }
         None
     }
 
     fn handle_key(&mut self, key: KeyCode) -> Option<Self::Message> {
+        if self.jump_pending {
+            self.jump_pending = false;
+            return match key {
+                KeyCode::Char(letter) => Some(DirectoryBrowserMessage::JumpTo(letter)),
+                _ => None,
+            };
+        }
         match key {
+            KeyCode::Char('f') => {
+                self.jump_pending = true;
+                None
+            }
             KeyCode::Up | KeyCode::Char('k') => Some(DirectoryBrowserMessage::MoveUp),
             KeyCode::Down | KeyCode::Char('j') => Some(DirectoryBrowserMessage::MoveDown),
             KeyCode::Right | KeyCode::Char('l') => Some(DirectoryBrowserMessage::Enter),
             KeyCode::Left | KeyCode::Char('h') => Some(DirectoryBrowserMessage::Back),
+            KeyCode::Char('s') => Some(DirectoryBrowserMessage::RequestSort),
             KeyCode::PageDown => Some(DirectoryBrowserMessage::PageDown),
             KeyCode::PageUp => Some(DirectoryBrowserMessage::PageUp),
             KeyCode::Home => Some(DirectoryBrowserMessage::Home),
@@ -159,63 +369,74 @@ impl Component for DirectoryBrowser {
             return;
         };
 
-        let directory_size: u64 = directory_frame
+        let directory_size = directory_frame.size(self.size_mode);
+        let widths = [
+            Constraint::Length(3),
+            Constraint::Percentage(60),
+            Constraint::Length(6),
+            Constraint::Length(20),
+            Constraint::Length(10),
+            Constraint::Length(20),
+        ];
+        let name_width = helpers::table_column_width(area, &widths, 1);
+        let mut rows: Vec<_> = directory_frame
             .directory_list
             .iter()
-            .filter_map(|di| di.size)
-            .sum();
-        let rows: Vec<_> = directory_frame
-            .directory_list
-            .iter()
-            .map(|di| create_row(di, directory_size))
+            .map(|di| {
+                create_row(
+                    di,
+                    directory_size,
+                    self.time_format,
+                    self.size_mode,
+                    name_width,
+                )
+            })
             .collect();
+        if directory_frame.hidden.count > 0 {
+            rows.push(create_hidden_row(&directory_frame.hidden, self.size_mode));
+        }
+        let rows_count = rows.len();
         let human_size = format_size(directory_frame.sum, DECIMAL);
 
-        let table = Table::new(
-            rows,
-            &[
-                Constraint::Length(3),
-                Constraint::Percentage(60),
-                Constraint::Length(6),
-                Constraint::Length(20),
-                Constraint::Length(10),
-                Constraint::Length(20),
-            ],
-        )
-        .header(
-            Row::new(vec!["", "Item", "", "", "Size", "Last modified"]).style(
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec!["", "Item", "", "", "Size", "Last modified"]).style(
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            )
+            .footer(Row::new(vec![
+                Cell::from(""),
+                Cell::from(""),
+                Cell::from(""),
+                Cell::from(""),
+                Cell::from(human_size.as_str())
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+                Cell::from(""),
+            ]))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Directory List ")
+                    .title(position_indicator(
+                        directory_frame.state.selected(),
+                        rows_count,
+                    ))
+                    .title_style(Style::default().fg(Color::LightYellow))
+                    .border_style(Style::default().fg(Color::LightYellow)),
+            )
+            .row_highlight_style(
                 Style::default()
-                    .fg(Color::Blue)
+                    .bg(Color::Blue)
+                    .fg(Color::White)
                     .add_modifier(Modifier::BOLD),
-            ),
-        )
-        .footer(Row::new(vec![
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(human_size.as_str()).style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from(""),
-        ]))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Directory List ")
-                .title_style(Style::default().fg(Color::LightYellow))
-                .border_style(Style::default().fg(Color::LightYellow)),
-        )
-        .row_highlight_style(
-            Style::default()
-                .bg(Color::Blue)
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol("► ");
+            )
+            .highlight_symbol("► ");
 
         frame.render_stateful_widget(table, area, &mut directory_frame.state);
 
-        let needs_scroll =
-            directory_frame.directory_list.len() > area.height.saturating_sub(3) as usize;
+        let needs_scroll = rows_count > area.height.saturating_sub(3) as usize;
         if needs_scroll {
             let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
                 .begin_symbol(Some("↑"))
@@ -227,15 +448,27 @@ impl Component for DirectoryBrowser {
     }
 }
 
-fn create_row<'a>(item: &'a DirItem, dir_size: u64) -> Row<'a> {
+fn create_row(
+    item: &DirItem,
+    dir_size: u64,
+    time_format: TimeFormat,
+    size_mode: SizeMode,
+    name_width: usize,
+) -> Row<'static> {
     let (icon, name_style) = if item.is_directory {
         ("📁", Style::default().fg(Color::Cyan))
     } else {
         ("📄", Style::default())
     };
-    let size = item.size.map(size_cell).unwrap_or_else(|| Cell::from("?"));
+    let size = match item.size_in(size_mode) {
+        Some(size) => size_cell(size),
+        None if item.is_directory => {
+            Cell::from("…").style(Style::default().add_modifier(Modifier::DIM))
+        }
+        None => Cell::from("?"),
+    };
     let percent = item
-        .size
+        .size_in(size_mode)
         .map(|size| {
             if dir_size == 0 {
                 0.0
@@ -249,14 +482,34 @@ fn create_row<'a>(item: &'a DirItem, dir_size: u64) -> Row<'a> {
 
     Row::new(vec![
         Cell::from(icon.to_string()),
-        Cell::from(Span::styled(&item.name, name_style)),
+        Cell::from(Span::styled(
+            helpers::truncate_middle(&item.name, name_width),
+            name_style,
+        )),
         Cell::from(Line::from(vec![
             Span::from(format!("{:>5.1}", percent)),
             Span::styled("%", Style::default().add_modifier(Modifier::DIM)),
         ])),
         Cell::from(bar),
         size,
-        last_update_cell(now(), item.last_update),
+        last_update_cell(now(), item.last_update, time_format),
+    ])
+}
+
+/// Summary row of the items over the display limit
+fn create_hidden_row(hidden: &HiddenItems, size_mode: SizeMode) -> Row<'static> {
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let size = match hidden.size_in(size_mode) {
+        Some(size) => format_size(size, DECIMAL),
+        None => String::from("…"),
+    };
+    Row::new(vec![
+        Cell::from(""),
+        Cell::from(Span::styled(format!("… and {} more", hidden.count), dim)),
+        Cell::from(""),
+        Cell::from(""),
+        Cell::from(size).style(dim),
+        Cell::from(""),
     ])
 }
 
@@ -316,39 +569,298 @@ struct DirectoryBrowserFrame {
     scroll_state: ScrollbarState,
     cwd: PathBuf,
     directory_list: Vec<DirItem>,
+    /// Items over the display limit, summarized in a single row
+    hidden: HiddenItems,
     sum: u64,
 }
 
+impl DirectoryBrowserFrame {
+    fn size(&self, size_mode: SizeMode) -> u64 {
+        self.directory_list
+            .iter()
+            .filter_map(|i| i.size_in(size_mode))
+            .chain(self.hidden.size_in(size_mode))
+            .sum()
+    }
+}
+
+/// Number and total size of the items over the display limit
+#[derive(Debug, Clone, Default)]
+struct HiddenItems {
+    count: usize,
+    /// Total size; `None` until the hidden directories are measured
+    size: Option<u64>,
+    allocated_size: Option<u64>,
+}
+
+impl HiddenItems {
+    fn new(entries: &[FileInfo]) -> Self {
+        let has_directories = entries.iter().any(|fi| fi.is_dir);
+        let total = |mode| {
+            (!has_directories).then(|| entries.iter().filter_map(|fi| fi.size_in(mode)).sum())
+        };
+        Self {
+            count: entries.len(),
+            size: total(SizeMode::Apparent),
+            allocated_size: total(SizeMode::Allocated),
+        }
+    }
+
+    fn size_in(&self, size_mode: SizeMode) -> Option<u64> {
+        match size_mode {
+            SizeMode::Apparent => self.size,
+            SizeMode::Allocated => self.allocated_size,
+        }
+    }
+
+    fn is_size_pending(&self) -> bool {
+        self.size.is_none()
+    }
+
+    /// Measure the total as the content of `cwd` not taken by the `listed` items
+    fn resolve(&mut self, db: &FilesDB, cwd: &Path, listed: &[DirItem]) {
+        let total = |mode| {
+            let listed: u64 = listed.iter().filter_map(|i| i.size_in(mode)).sum();
+            Some(db.content_size_in(cwd, mode).saturating_sub(listed))
+        };
+        self.size = total(SizeMode::Apparent);
+        self.allocated_size = total(SizeMode::Allocated);
+    }
+}
+
 #[derive(Debug, Clone)]
 struct DirItem {
     name: String,
     path: PathBuf,
     is_directory: bool,
+    /// Size of the item; for directories, `None` means "not computed yet"
     size: Option<u64>,
+    /// Space taken on the disk, computed along with `size`
+    allocated_size: Option<u64>,
     last_update: Option<SystemTime>,
 }
 
 impl DirItem {
-    fn from_file_info(file_info: &FileInfo, db: &FilesDB) -> Self {
+    fn from_file_info(file_info: &FileInfo) -> Self {
         Self {
+            // Lossy conversion keeps non-UTF-8 names distinguishable;
+            // the real path is kept for any operation.
             name: file_info
                 .path
                 .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("?")
-                .to_string(),
-            path: file_info.path.clone(),
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| String::from("?")),
+            path: file_info.path.to_path_buf(),
             is_directory: file_info.is_dir,
             size: if file_info.is_dir {
-                Some(
-                    db.iter_dir(file_info.path)
-                        .filter_map(|item| item.size)
-                        .sum(),
-                )
+                None
             } else {
                 file_info.size
             },
+            allocated_size: if file_info.is_dir {
+                None
+            } else {
+                file_info.size_in(SizeMode::Allocated)
+            },
             last_update: file_info.touched,
         }
     }
+
+    fn size_in(&self, size_mode: SizeMode) -> Option<u64> {
+        match size_mode {
+            SizeMode::Apparent => self.size,
+            SizeMode::Allocated => self.allocated_size,
+        }
+    }
+
+    fn is_size_pending(&self) -> bool {
+        self.is_directory && self.size.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{ProgressEvent, ProgressReporter};
+
+    #[derive(Clone)]
+    struct MockReporter;
+
+    impl ProgressReporter for MockReporter {
+        fn report(&self, _event: ProgressEvent) {}
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_names_are_listed_distinctly() {
+        use std::ffi::OsStr;
+        use std::fs;
+        use std::os::unix::ffi::OsStrExt;
+
+        use crate::discovery::{PathLoader, WalkOptions};
+        use crate::loader::FullyParallelLoader;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path().to_path_buf();
+        let first = root_path.join(OsStr::from_bytes(b"first\xff"));
+        let second = root_path.join(OsStr::from_bytes(b"second\xfe"));
+        fs::write(&first, "Hello").unwrap();
+        fs::write(&second, "World").unwrap();
+
+        let db = FullyParallelLoader.load_multiple_paths(
+            &[(root_path.clone(), None)],
+            WalkOptions::default(),
+            Some(MockReporter),
+        );
+        let frame = open_path(Rc::new(db), root_path, SizeMode::Apparent, 0).unwrap();
+
+        let mut names = frame
+            .directory_list
+            .iter()
+            .map(|item| item.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["first\u{FFFD}", "second\u{FFFD}"]);
+
+        let paths = frame
+            .directory_list
+            .iter()
+            .map(|item| item.path.clone())
+            .collect::<Vec<_>>();
+        assert!(paths.contains(&first));
+        assert!(paths.contains(&second));
+    }
+
+    #[test]
+    fn test_sort_frame() {
+        let item = |name: &str, is_directory: bool, size: Option<u64>| DirItem {
+            name: String::from(name),
+            path: PathBuf::from("/root").join(name),
+            is_directory,
+            size,
+            allocated_size: size,
+            last_update: None,
+        };
+        let mut frame = DirectoryBrowserFrame {
+            state: TableState::default().with_selected(Some(0)),
+            scroll_state: ScrollbarState::new(4),
+            cwd: PathBuf::from("/root"),
+            directory_list: vec![
+                item("b.txt", false, Some(10)),
+                item("Zeta", true, Some(5)),
+                item("a.txt", false, Some(30)),
+                item("alpha", true, None),
+            ],
+            hidden: HiddenItems::default(),
+            sum: 45,
+        };
+        let names = |frame: &DirectoryBrowserFrame| {
+            frame
+                .directory_list
+                .iter()
+                .map(|item| item.name.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        sort_frame(
+            &mut frame,
+            SortBy::Name,
+            SortDirection::Ascending,
+            SizeMode::Apparent,
+        );
+        assert_eq!(names(&frame), "alpha,Zeta,a.txt,b.txt");
+        // The selected item follows the sort
+        assert_eq!(frame.state.selected(), Some(3));
+
+        sort_frame(
+            &mut frame,
+            SortBy::Name,
+            SortDirection::Descending,
+            SizeMode::Apparent,
+        );
+        assert_eq!(names(&frame), "Zeta,alpha,b.txt,a.txt");
+
+        sort_frame(
+            &mut frame,
+            SortBy::Size,
+            SortDirection::Descending,
+            SizeMode::Apparent,
+        );
+        assert_eq!(names(&frame), "a.txt,b.txt,Zeta,alpha");
+        assert_eq!(frame.state.selected(), Some(1));
+
+        // A sparse file takes less space on the disk than its length
+        frame.directory_list[0].allocated_size = Some(4);
+        sort_frame(
+            &mut frame,
+            SortBy::Size,
+            SortDirection::Descending,
+            SizeMode::Allocated,
+        );
+        assert_eq!(names(&frame), "b.txt,Zeta,a.txt,alpha");
+    }
+
+    #[test]
+    fn test_limit_summarizes_hidden_items() {
+        use crate::file_info::{FileMeta, Permissions};
+
+        let meta = |is_dir: bool, size: Option<u64>| FileMeta {
+            is_dir,
+            size,
+            allocated: size,
+            touched: None,
+            accessed: None,
+            permissions: Permissions::default(),
+        };
+        let mut db = FilesDB::new();
+        db.add(PathBuf::from("/root"), meta(true, None));
+        for (name, size) in [("a.txt", 10), ("b.txt", 30), ("c.txt", 20)] {
+            db.add(PathBuf::from("/root").join(name), meta(false, Some(size)));
+        }
+        db.add(PathBuf::from("/root/dir"), meta(true, None));
+        db.add(PathBuf::from("/root/dir/file"), meta(false, Some(5)));
+        db.add(PathBuf::from("/root/other"), meta(true, None));
+        db.add(PathBuf::from("/root/other/file"), meta(false, Some(7)));
+        let db = Rc::new(db);
+        let names = |frame: &DirectoryBrowserFrame| {
+            let mut names = frame
+                .directory_list
+                .iter()
+                .map(|item| item.name.as_str())
+                .collect::<Vec<_>>();
+            names.sort();
+            names.join(",")
+        };
+
+        let frame = open_path(db.clone(), PathBuf::from("/root"), SizeMode::Apparent, 0).unwrap();
+        assert_eq!(frame.directory_list.len(), 5);
+        assert_eq!(frame.hidden.count, 0);
+
+        // Directories are listed first, the hidden files are summed right away
+        let frame = open_path(db.clone(), PathBuf::from("/root"), SizeMode::Apparent, 3).unwrap();
+        assert_eq!(names(&frame), "b.txt,dir,other");
+        assert_eq!(frame.hidden.count, 2);
+        assert_eq!(frame.hidden.size, Some(30));
+
+        // Hidden directories are measured at once, after the listed items
+        let mut browser = DirectoryBrowser::new(
+            db,
+            PathBuf::from("/root"),
+            TimeFormat::default(),
+            SizeMode::Apparent,
+            1,
+        )
+        .unwrap();
+        let frame = browser.frames.last().unwrap();
+        assert_eq!(frame.directory_list.len(), 1);
+        assert_eq!(frame.hidden.count, 4);
+        assert!(frame.hidden.is_size_pending());
+
+        assert!(!browser.resolve_sizes(Duration::from_secs(60)));
+        let frame = browser.frames.last().unwrap();
+        let listed = frame.directory_list[0].size.unwrap();
+        assert_eq!(frame.hidden.size, Some(72 - listed));
+        assert_eq!(frame.sum, 72);
+    }
 }
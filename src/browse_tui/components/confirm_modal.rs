@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use humansize::{DECIMAL, format_size};
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Padding, Wrap};
+use ratatui::{Frame, crossterm::event::KeyCode, widgets::Paragraph};
+
+use crate::browse_tui::component::Component;
+use crate::browse_tui::helpers;
+use crate::browse_tui::message::AppMessage;
+
+/// Asks for a confirmation before the marked paths are removed from the disk
+#[derive(Debug)]
+pub struct ConfirmModal {
+    paths: Vec<PathBuf>,
+    size: u64,
+    /// Paths the user can't remove; they are skipped
+    needs_sudo: Vec<PathBuf>,
+}
+
+impl ConfirmModal {
+    pub fn new(paths: Vec<PathBuf>, size: u64, needs_sudo: Vec<PathBuf>) -> Self {
+        Self {
+            paths,
+            size,
+            needs_sudo,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfirmModalMessage {
+    Confirm,
+    Cancel,
+}
+
+impl Component for ConfirmModal {
+    type Message = ConfirmModalMessage;
+
+    fn update(&mut self, message: Self::Message) -> Option<AppMessage> {
+        match message {
+            ConfirmModalMessage::Confirm => {
+                Some(AppMessage::CleanPaths(std::mem::take(&mut self.paths)))
+            }
+            ConfirmModalMessage::Cancel => Some(AppMessage::CloseModal),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyCode) -> Option<Self::Message> {
+        match key {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                Some(ConfirmModalMessage::Confirm)
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => Some(ConfirmModalMessage::Cancel),
+            // Don't let other keys through to the app, e.g. to quit
+            _ => Some(ConfirmModalMessage::Cancel),
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let area = helpers::popup_area_clamped(area, 70, 150, 80, 14, 40, 60);
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw("Permanently remove "),
+                Span::styled(
+                    format!("{} marked paths", self.paths.len()),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" ("),
+                Span::styled(
+                    format_size(self.size, DECIMAL),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(")?"),
+            ]),
+            Line::from(""),
+        ];
+        if !self.needs_sudo.is_empty() {
+            lines.push(
+                Line::from(format!(
+                    "{} of them require sudo and will be skipped.",
+                    self.needs_sudo.len()
+                ))
+                .style(Style::default().fg(Color::LightRed)),
+            );
+            lines.push(Line::from(""));
+        }
+        lines.extend(self.paths.iter().map(|path| {
+            if self.needs_sudo.contains(path) {
+                Line::from(vec![
+                    Span::raw(path.display().to_string()),
+                    Span::styled(" (requires sudo)", Style::default().fg(Color::LightRed)),
+                ])
+                .style(Style::default().add_modifier(Modifier::DIM))
+            } else {
+                Line::from(path.display().to_string())
+                    .style(Style::default().add_modifier(Modifier::DIM))
+            }
+        }));
+
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::bordered()
+                .padding(Padding::symmetric(2, 1))
+                .title_style(Style::default().fg(Color::LightRed))
+                .title(Line::from(" Clean marked ").alignment(Alignment::Left))
+                .title(
+                    Line::from(" y / n ").alignment(Alignment::Right).style(
+                        Style::default()
+                            .fg(Color::Red)
+                            .add_modifier(Modifier::ITALIC),
+                    ),
+                )
+                .border_style(Style::default().fg(Color::LightRed)),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirm_modal() {
+        let mut modal = ConfirmModal::new(vec![PathBuf::from("/projects/a/target")], 10, vec![]);
+
+        let message = modal.handle_key(KeyCode::Char('q')).unwrap();
+        assert!(matches!(
+            modal.update(message),
+            Some(AppMessage::CloseModal)
+        ));
+
+        let message = modal.handle_key(KeyCode::Char('y')).unwrap();
+        assert!(matches!(
+            modal.update(message),
+            Some(AppMessage::CleanPaths(paths)) if paths == vec![PathBuf::from("/projects/a/target")]
+        ));
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::SystemTime;
+
+use humansize::{DECIMAL, format_size};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+use crate::discovery::{Language, ProjectResult, ToolingResult};
+use crate::display_tools::{TimeFormat, format_time};
+use crate::file_info::SizeMode;
+
+/// Height of the pane including borders
+pub const DETAILS_PANE_HEIGHT: u16 = 9;
+
+/// Label and value pairs shown in the details pane
+type Details = Vec<(&'static str, String)>;
+
+pub fn project_details(result: &ProjectResult, size_mode: SizeMode, now: SystemTime) -> Details {
+    let mut details = vec![
+        ("Path", result.path.display().to_string()),
+        ("Language", language(result.lang)),
+        ("Size", exact_size(result.size_in(size_mode))),
+        ("Last update", last_update(now, result.last_update)),
+    ];
+    if let Some(parent) = result.parent.as_ref() {
+        details.push((
+            "Parent",
+            format!(
+                "{} ({})",
+                parent.path.display(),
+                format_size(parent.size_in(size_mode), DECIMAL)
+            ),
+        ));
+    }
+    if let Some(vcs_size) = result.vcs_size {
+        details.push(("VCS metadata", exact_size(vcs_size)));
+    }
+    details
+}
+
+pub fn tooling_details(result: &ToolingResult, size_mode: SizeMode, now: SystemTime) -> Details {
+    vec![
+        ("Tool", result.description.to_string()),
+        ("Path", result.path.display().to_string()),
+        ("Language", language(result.lang)),
+        ("Size", exact_size(result.size_in(size_mode))),
+        ("Last update", last_update(now, result.last_update)),
+    ]
+}
+
+fn language(lang: Language) -> String {
+    format!("{lang} {lang:?}")
+}
+
+fn exact_size(size: u64) -> String {
+    format!("{} ({size} bytes)", format_size(size, DECIMAL))
+}
+
+fn last_update(now: SystemTime, last: Option<SystemTime>) -> String {
+    match last {
+        Some(time) => format!(
+            "{} ({})",
+            format_time(&now, time, TimeFormat::Absolute),
+            format_time(&now, time, TimeFormat::Relative)
+        ),
+        None => String::from("unknown"),
+    }
+}
+
+pub fn render_details_pane(frame: &mut Frame, area: Rect, details: Option<Details>) {
+    let lines = match details {
+        Some(details) => details
+            .into_iter()
+            .map(|(label, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{label:<14}"),
+                        Style::default()
+                            .fg(Color::Blue)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(value),
+                ])
+            })
+            .collect(),
+        None => vec![Line::from("Nothing selected.").style(Style::default().fg(Color::Gray))],
+    };
+
+    let pane = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Details ")
+            .title_style(Style::default().fg(Color::LightYellow))
+            .border_style(Style::default().fg(Color::LightYellow)),
+    );
+
+    frame.render_widget(pane, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::discovery::ParentInfo;
+
+    #[test]
+    fn test_project_details() {
+        let now = SystemTime::now();
+        let mut result = ProjectResult {
+            path: PathBuf::from("/projects/rust/target"),
+            lang: Language::Rust,
+            size: 2_000_000,
+            allocated_size: 2_400_000,
+            last_update: Some(now - Duration::from_hours(3)),
+            parent: Some(ParentInfo {
+                path: PathBuf::from("/projects/rust"),
+                size: 3_000_000,
+                allocated_size: 3_000_000,
+            }),
+            vcs_size: Some(500_000),
+            orphaned: false,
+            root: None,
+        };
+
+        let details = project_details(&result, SizeMode::Apparent, now);
+        let value = |label: &str| {
+            details
+                .iter()
+                .find(|(l, _)| *l == label)
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(value("Path").unwrap(), "/projects/rust/target");
+        assert_eq!(value("Language").unwrap(), "🦀 Rust");
+        assert_eq!(value("Size").unwrap(), "2 MB (2000000 bytes)");
+        assert!(value("Last update").unwrap().ends_with("(3 hours ago)"));
+        assert_eq!(value("Parent").unwrap(), "/projects/rust (3 MB)");
+        assert_eq!(value("VCS metadata").unwrap(), "500 kB (500000 bytes)");
+
+        let details = project_details(&result, SizeMode::Allocated, now);
+        assert_eq!(
+            details[2],
+            ("Size", String::from("2.40 MB (2400000 bytes)"))
+        );
+
+        result.parent = None;
+        result.vcs_size = None;
+        result.last_update = None;
+        let details = project_details(&result, SizeMode::Apparent, now);
+        assert_eq!(details.len(), 4);
+        assert_eq!(details[3], ("Last update", String::from("unknown")));
+    }
+}
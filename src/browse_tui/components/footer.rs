@@ -6,22 +6,35 @@ use ratatui::{Frame, crossterm::event::KeyCode, widgets::Paragraph};
 
 use crate::browse_tui::component::Component;
 use crate::browse_tui::message::AppMessage;
+use crate::file_info::SizeMode;
 
 #[derive(Debug)]
 pub struct Footer {
     error_message: Option<String>,
+    status_message: Option<String>,
+    size_mode: SizeMode,
 }
 
 impl Footer {
     pub fn new() -> Self {
         Self {
             error_message: None,
+            status_message: None,
+            size_mode: SizeMode::default(),
         }
     }
 
     pub fn set_error(&mut self, error_message: Option<String>) {
         self.error_message = error_message;
     }
+
+    pub fn set_status(&mut self, status_message: Option<String>) {
+        self.status_message = status_message;
+    }
+
+    pub fn set_size_mode(&mut self, size_mode: SizeMode) {
+        self.size_mode = size_mode;
+    }
 }
 
 #[derive(Debug)]
@@ -49,6 +62,14 @@ impl Component for Footer {
                             .add_modifier(Modifier::BOLD),
                     ),
             )
+        } else if let Some(message) = &self.status_message {
+            let msg = format!(" {}", message);
+            Paragraph::new(msg).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" INFO ")
+                    .style(Style::default().fg(Color::Green)),
+            )
         } else {
             let line = vec![Line::from(vec![
                 Span::styled(
@@ -93,6 +114,13 @@ impl Component for Footer {
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" Filter  "),
+                Span::styled(
+                    "z",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" Sizes  "),
                 Span::styled(
                     "d/t/v",
                     Style::default()
@@ -123,6 +151,10 @@ impl Component for Footer {
                 Block::default()
                     .borders(Borders::ALL)
                     .title(Line::from(" Controls ").alignment(Alignment::Left))
+                    .title(
+                        Line::from(format!(" Sizes: {} ", self.size_mode.label()))
+                            .alignment(Alignment::Right),
+                    )
                     .title_style(Style::default().fg(Color::Green))
                     .title_bottom(
                         Line::from(" More controls in the help ")
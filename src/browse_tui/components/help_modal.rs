@@ -179,11 +179,76 @@ impl Component for HelpModal {
                 Span::raw("and you can use / to gain focus to the filter again."),
             ])
             .style(Style::default().add_modifier(Modifier::DIM)),
+            Line::from(vec![
+                Span::styled("g", Style::default().fg(Color::Yellow)),
+                Span::raw("         "),
+                Span::raw("In Projects tab, toggle the histogram of project sizes"),
+            ]),
+            Line::from(vec![
+                Span::styled("o", Style::default().fg(Color::Yellow)),
+                Span::raw("         "),
+                Span::raw("In Projects tab, show only projects not updated for a long time"),
+            ]),
+            Line::from(vec![
+                Span::raw("          "),
+                Span::raw("The threshold is set with --stale-days (60 days by default)."),
+            ])
+            .style(Style::default().add_modifier(Modifier::DIM)),
+            Line::from(vec![
+                Span::styled("Tab", Style::default().fg(Color::Yellow)),
+                Span::raw("       "),
+                Span::raw("In Projects and Tooling tabs, toggle details of the selected item"),
+            ]),
+            Line::from(vec![
+                Span::styled("f", Style::default().fg(Color::Yellow)),
+                Span::raw("         "),
+                Span::raw("In Projects and the directory browser, press a letter next"),
+            ]),
+            Line::from(vec![
+                Span::raw("          "),
+                Span::raw("to jump to the next item starting with it (wraps around)."),
+            ])
+            .style(Style::default().add_modifier(Modifier::DIM)),
+            Line::from(vec![
+                Span::styled("R", Style::default().fg(Color::Yellow)),
+                Span::raw("         "),
+                Span::raw("In Projects and Tooling tabs, rescan the selected item"),
+            ]),
+            Line::from(vec![
+                Span::raw("          "),
+                Span::raw("Items which don't exist anymore are removed."),
+            ])
+            .style(Style::default().add_modifier(Modifier::DIM)),
+            Line::from(vec![
+                Span::styled("Space", Style::default().fg(Color::Yellow)),
+                Span::raw("     "),
+                Span::raw("In Projects and Tooling tabs, mark or unmark the selected item"),
+            ]),
+            Line::from(vec![
+                Span::styled("X", Style::default().fg(Color::Yellow)),
+                Span::raw("         "),
+                Span::raw("In Projects and Tooling tabs, remove all marked items from the disk"),
+            ]),
+            Line::from(vec![
+                Span::raw("          "),
+                Span::raw("The removal is confirmed with y or Enter, any other key cancels it."),
+            ])
+            .style(Style::default().add_modifier(Modifier::DIM)),
             Line::from(vec![
                 Span::styled("x", Style::default().fg(Color::Yellow)),
                 Span::raw("         "),
                 Span::raw("In VCS tab, jump to Projects filtered by selected VCS root"),
             ]),
+            Line::from(vec![
+                Span::styled("z", Style::default().fg(Color::Yellow)),
+                Span::raw("         "),
+                Span::raw("Switch sizes between apparent ones and space on the disk"),
+            ]),
+            Line::from(vec![
+                Span::raw("          "),
+                Span::raw("Space on the disk matches du; VCS metadata is always apparent."),
+            ])
+            .style(Style::default().add_modifier(Modifier::DIM)),
             Line::from(""),
             Line::from(vec![
                 Span::styled("Esc", Style::default().fg(Color::Yellow)),
@@ -196,8 +261,8 @@ impl Component for HelpModal {
                 Span::raw("Show the help pop-up window"),
             ]),
             Line::from(vec![
-                Span::styled("q", Style::default().fg(Color::Yellow)),
-                Span::raw("         "),
+                Span::styled("q, Ctrl-C", Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
                 Span::raw("Quit the application"),
             ]),
         ])
@@ -1,4 +1,6 @@
 mod browser;
+mod confirm_modal;
+mod details_pane;
 pub mod filter_bar;
 mod footer;
 mod header;
@@ -10,6 +12,7 @@ mod tooling_tab;
 mod vcs_tab;
 
 pub use browser::DirectoryBrowser;
+pub use confirm_modal::ConfirmModal;
 pub use footer::Footer;
 pub use header::Header;
 pub use help_modal::HelpModal;
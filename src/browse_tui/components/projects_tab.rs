@@ -1,16 +1,31 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::rc::Rc;
+
 use humansize::{DECIMAL, format_size};
-use ratatui::layout::{Constraint, Rect};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::Line;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{
-    Block, Borders, Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table, TableState,
+    Bar, BarChart, BarGroup, Block, Borders, Cell, Row, Scrollbar, ScrollbarOrientation,
+    ScrollbarState, Table, TableState,
 };
 use ratatui::{Frame, crossterm::event::KeyCode};
 
 use crate::browse_tui::component::{Component, Navigable};
-use crate::browse_tui::helpers::{dimmed_size_cell, last_update_cell, now, size_cell};
-use crate::browse_tui::message::{AppMessage, SortBy, SortDirection};
+use crate::browse_tui::components::details_pane::{
+    DETAILS_PANE_HEIGHT, project_details, render_details_pane,
+};
+use crate::browse_tui::helpers::{
+    delta_cell, dimmed_size_cell, find_next_by_letter, last_update_cell, mark_symbol, marked_style,
+    now, position_indicator, size_cell, table_column_width, truncate_middle,
+};
+use crate::browse_tui::message::{AppMessage, Cleaned, Rescanned, SortBy, SortDirection};
 use crate::discovery::ProjectResult;
+use crate::display::ORPHANED_MARK;
+use crate::display_tools::{Labels, StaleFilter, TimeFormat};
+use crate::file_info::SizeMode;
+use crate::history::History;
 
 #[derive(Debug)]
 pub struct ProjectsTab {
@@ -23,19 +38,53 @@ pub struct ProjectsTab {
     page_size: u16,
     sort_by: Option<SortBy>,
     sort_direction: SortDirection,
+    time_format: TimeFormat,
+    size_mode: SizeMode,
     active_filter: Option<String>,
+    stale_filter: StaleFilter,
+    stale_only: bool,
+    marked: HashSet<PathBuf>,
+    history: Rc<History>,
+    labels: Labels,
+    histogram: [u64; HISTOGRAM_BUCKETS.len()],
+    show_histogram: bool,
+    show_details: bool,
+    jump_pending: bool,
 }
 
-impl ProjectsTab {
-    const SORT_OPTIONS: [SortBy; 3] = [SortBy::Project, SortBy::Size, SortBy::LastUpdate];
+/// Upper bounds (exclusive) of the histogram buckets with their labels
+const HISTOGRAM_BUCKETS: [(u64, &str); 6] = [
+    (1000 * 1000, "<1MB"),
+    (10 * 1000 * 1000, "<10MB"),
+    (100 * 1000 * 1000, "<100MB"),
+    (1000 * 1000 * 1000, "<1GB"),
+    (10 * 1000 * 1000 * 1000, "<10GB"),
+    (u64::MAX, "10GB+"),
+];
+const HISTOGRAM_HEIGHT: u16 = 8;
 
-    pub fn new(results: Vec<ProjectResult>) -> Self {
+impl ProjectsTab {
+    const SORT_OPTIONS: [SortBy; 4] = [
+        SortBy::Project,
+        SortBy::Size,
+        SortBy::LastUpdate,
+        SortBy::Language,
+    ];
+
+    /// Projects are limited to the stale ones from the start when `stale_filter` is given
+    pub fn new(
+        results: Vec<ProjectResult>,
+        time_format: TimeFormat,
+        stale_filter: Option<StaleFilter>,
+        history: Rc<History>,
+        labels: Labels,
+    ) -> Self {
         let filter_paths = results
             .iter()
             .map(|r| r.path.to_string_lossy().to_ascii_lowercase())
             .collect();
 
-        Self {
+        let mut tab = Self {
             state: {
                 let mut projects_state = TableState::default();
                 projects_state.select(Some(0));
@@ -43,14 +92,36 @@ impl ProjectsTab {
             },
             scroll_state: ScrollbarState::new(results.len()),
             sum: results.iter().map(|r| r.size).sum(),
+            histogram: size_histogram(results.iter().map(|r| r.size)),
             view: (0..results.len()).collect(),
             preprocessed_filter_paths: filter_paths,
             results,
             page_size: 0,
             sort_by: None,
             sort_direction: SortDirection::default(),
+            time_format,
+            size_mode: SizeMode::default(),
             active_filter: None,
+            stale_filter: stale_filter.unwrap_or(StaleFilter {
+                days: StaleFilter::DEFAULT_DAYS,
+                include_undated: false,
+            }),
+            stale_only: false,
+            marked: HashSet::new(),
+            history,
+            labels,
+            show_histogram: false,
+            show_details: false,
+            jump_pending: false,
+        };
+        if stale_filter.is_some() {
+            tab.toggle_stale();
         }
+        tab
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
     }
 
     pub fn apply_filter(&mut self, filter: Option<String>) {
@@ -66,6 +137,11 @@ impl ProjectsTab {
         self.refresh_view();
     }
 
+    pub fn set_size_mode(&mut self, size_mode: SizeMode) {
+        self.size_mode = size_mode;
+        self.refresh_view();
+    }
+
     fn selected_result(&self) -> Option<&ProjectResult> {
         self.state
             .selected()
@@ -86,6 +162,94 @@ impl ProjectsTab {
             .map(AppMessage::EnterBrowser)
     }
 
+    fn rescan(&mut self) -> Option<AppMessage> {
+        self.selected_result()
+            .map(|res| res.path.clone())
+            .map(AppMessage::RescanPath)
+    }
+
+    fn apply_rescan(&mut self, rescanned: Rescanned) -> Option<AppMessage> {
+        let idx = self
+            .results
+            .iter()
+            .position(|r| &r.path == rescanned.path())?;
+        let status = rescanned.status();
+        match rescanned {
+            Rescanned::Updated {
+                size,
+                allocated_size,
+                last_update,
+                parent_size,
+                ..
+            } => {
+                let result = &mut self.results[idx];
+                result.size = size;
+                result.allocated_size = allocated_size;
+                result.last_update = last_update;
+                if let (Some(parent), Some((size, allocated_size))) =
+                    (result.parent.as_mut(), parent_size)
+                {
+                    parent.size = size;
+                    parent.allocated_size = allocated_size;
+                }
+            }
+            Rescanned::Missing(_) => {
+                let removed = self.results.remove(idx);
+                self.preprocessed_filter_paths.remove(idx);
+                self.marked.remove(&removed.path);
+            }
+        }
+        self.refresh_view();
+
+        Some(AppMessage::SetStatus(status))
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(path) = self.selected_result().map(|res| res.path.clone()) else {
+            return;
+        };
+        if !self.marked.remove(&path) {
+            self.marked.insert(path);
+        }
+        self.move_down();
+    }
+
+    fn marked_size(&self) -> u64 {
+        self.results
+            .iter()
+            .filter(|r| self.marked.contains(&r.path))
+            .map(|r| r.size_in(self.size_mode))
+            .sum()
+    }
+
+    fn clean_marked(&mut self) -> Option<AppMessage> {
+        if self.marked.is_empty() {
+            return Some(AppMessage::SetError(String::from(
+                "No projects are marked. Use Space to mark them.",
+            )));
+        }
+        let mut paths = self.marked.iter().cloned().collect::<Vec<_>>();
+        paths.sort();
+        Some(AppMessage::ConfirmClean {
+            paths,
+            size: self.marked_size(),
+        })
+    }
+
+    fn apply_clean(&mut self, cleaned: Cleaned) -> Option<AppMessage> {
+        let mut freed = 0;
+        for path in cleaned.removed.iter() {
+            if let Some(idx) = self.results.iter().position(|r| &r.path == path) {
+                freed += self.results.remove(idx).size_in(self.size_mode);
+                self.preprocessed_filter_paths.remove(idx);
+            }
+        }
+        self.marked.clear();
+        self.refresh_view();
+
+        Some(cleaned.report(freed))
+    }
+
     fn request_sort(&mut self) -> Option<AppMessage> {
         Some(AppMessage::OpenSort(&Self::SORT_OPTIONS))
     }
@@ -110,6 +274,74 @@ impl ProjectsTab {
         Some(AppMessage::StartFilter)
     }
 
+    /// Select the next project whose directory name starts with `letter`
+    ///
+    /// Build directories share names like `target`, so the name of the
+    /// directory containing them is used.
+    fn jump_to(&mut self, letter: char) {
+        let names = self
+            .view
+            .iter()
+            .map(|&idx| {
+                let path = &self.results[idx].path;
+                path.parent()
+                    .and_then(|parent| parent.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        if let Some(idx) = find_next_by_letter(&names, self.state.selected(), letter) {
+            self.state.select(Some(idx));
+            self.sync_scroll();
+        }
+    }
+
+    fn toggle_stale(&mut self) {
+        self.stale_only = !self.stale_only;
+        self.refresh_view();
+    }
+
+    fn toggle_details(&mut self) {
+        self.show_details = !self.show_details;
+    }
+
+    fn toggle_histogram(&mut self) {
+        self.show_histogram = !self.show_histogram;
+    }
+
+    fn render_histogram(&self, frame: &mut Frame, area: Rect) {
+        let bars = HISTOGRAM_BUCKETS
+            .iter()
+            .zip(self.histogram)
+            .map(|((_, label), count)| {
+                Bar::default()
+                    .value(count)
+                    .label(Line::from(*label))
+                    .style(Style::default().fg(Color::LightYellow))
+            })
+            .collect::<Vec<_>>();
+
+        let chart = BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(7)
+            .bar_gap(1)
+            .value_style(
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::LightYellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Projects by size ")
+                    .title_style(Style::default().fg(Color::LightYellow))
+                    .border_style(Style::default().fg(Color::LightYellow)),
+            );
+
+        frame.render_widget(chart, area);
+    }
+
     fn sync_scroll(&mut self) {
         let selected = self.state.selected().unwrap_or(0);
         self.scroll_state = ScrollbarState::new(self.view.len()).position(selected);
@@ -125,14 +357,32 @@ impl ProjectsTab {
                 .collect(),
             None => (0..self.results.len()).collect(),
         };
+        if self.stale_only {
+            let now = now();
+            self.view.retain(|&idx| {
+                self.stale_filter
+                    .is_stale(&now, &self.results[idx].last_update)
+            });
+        }
 
         if let Some(sort_by) = self.sort_by {
             match sort_by {
                 SortBy::Project => self.view.sort_by(|a_idx, b_idx| {
                     self.results[*a_idx].path.cmp(&self.results[*b_idx].path)
                 }),
-                SortBy::Size => self.view.sort_by_key(|idx| self.results[*idx].size),
+                SortBy::Size => self.view.sort_by_key(|idx| {
+                    (
+                        self.results[*idx].size_in(self.size_mode),
+                        self.results[*idx].lang,
+                    )
+                }),
                 SortBy::LastUpdate => self.view.sort_by_key(|idx| self.results[*idx].last_update),
+                SortBy::Language => self.view.sort_by_key(|idx| {
+                    (
+                        self.results[*idx].lang,
+                        std::cmp::Reverse(self.results[*idx].size_in(self.size_mode)),
+                    )
+                }),
                 _ => {}
             }
 
@@ -141,7 +391,12 @@ impl ProjectsTab {
             }
         }
 
-        self.sum = self.view.iter().map(|&idx| self.results[idx].size).sum();
+        let sizes = self
+            .view
+            .iter()
+            .map(|&idx| self.results[idx].size_in(self.size_mode));
+        self.sum = sizes.clone().sum();
+        self.histogram = size_histogram(sizes);
         self.adjust_selection_to_view();
         self.sync_scroll();
     }
@@ -171,6 +426,15 @@ pub enum ProjectsTabMessage {
     RequestSort,
     ApplySort(SortBy),
     StartFilter,
+    ToggleHistogram,
+    ToggleDetails,
+    ToggleStale,
+    JumpTo(char),
+    Rescan,
+    ApplyRescan(Rescanned),
+    ToggleMark,
+    CleanMarked,
+    ApplyClean(Cleaned),
 }
 
 impl Component for ProjectsTab {
@@ -199,12 +463,40 @@ impl Component for ProjectsTab {
             ProjectsTabMessage::StartFilter => {
                 return self.start_filter();
             }
+            ProjectsTabMessage::ToggleHistogram => self.toggle_histogram(),
+            ProjectsTabMessage::ToggleDetails => self.toggle_details(),
+            ProjectsTabMessage::ToggleStale => self.toggle_stale(),
+            ProjectsTabMessage::Rescan => {
+                return self.rescan();
+            }
+            ProjectsTabMessage::ApplyRescan(rescanned) => {
+                return self.apply_rescan(rescanned);
+            }
+            ProjectsTabMessage::JumpTo(letter) => self.jump_to(letter),
+            ProjectsTabMessage::ToggleMark => self.toggle_mark(),
+            ProjectsTabMessage::CleanMarked => {
+                return self.clean_marked();
+            }
+            ProjectsTabMessage::ApplyClean(cleaned) => {
+                return self.apply_clean(cleaned);
+            }
         }
         None
     }
 
     fn handle_key(&mut self, key: KeyCode) -> Option<Self::Message> {
+        if self.jump_pending {
+            self.jump_pending = false;
+            return match key {
+                KeyCode::Char(letter) => Some(ProjectsTabMessage::JumpTo(letter)),
+                _ => None,
+            };
+        }
         match key {
+            KeyCode::Char('f') => {
+                self.jump_pending = true;
+                None
+            }
             KeyCode::Up | KeyCode::Char('k') => Some(ProjectsTabMessage::MoveUp),
             KeyCode::Down | KeyCode::Char('j') => Some(ProjectsTabMessage::MoveDown),
             KeyCode::Right | KeyCode::Char('l') => Some(ProjectsTabMessage::Enter),
@@ -215,66 +507,131 @@ impl Component for ProjectsTab {
             KeyCode::End => Some(ProjectsTabMessage::End),
             KeyCode::Char('s') => Some(ProjectsTabMessage::RequestSort),
             KeyCode::Char('/') => Some(ProjectsTabMessage::StartFilter),
+            KeyCode::Char('g') => Some(ProjectsTabMessage::ToggleHistogram),
+            KeyCode::Char('o') => Some(ProjectsTabMessage::ToggleStale),
+            KeyCode::Char('R') => Some(ProjectsTabMessage::Rescan),
+            KeyCode::Char(' ') => Some(ProjectsTabMessage::ToggleMark),
+            KeyCode::Char('X') => Some(ProjectsTabMessage::CleanMarked),
+            KeyCode::Tab => Some(ProjectsTabMessage::ToggleDetails),
             _ => None,
         }
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let area = if self.show_histogram {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(HISTOGRAM_HEIGHT), Constraint::Min(0)])
+                .split(area);
+            self.render_histogram(frame, chunks[0]);
+            chunks[1]
+        } else {
+            area
+        };
+        let area = if self.show_details {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(DETAILS_PANE_HEIGHT)])
+                .split(area);
+            let details = self
+                .selected_result()
+                .map(|result| project_details(result, self.size_mode, now()));
+            render_details_pane(frame, chunks[1], details);
+            chunks[0]
+        } else {
+            area
+        };
         self.page_size = area.height.saturating_sub(3);
 
+        let widths = [
+            Constraint::Length(5),
+            Constraint::Percentage(60),
+            Constraint::Length(10),
+            Constraint::Length(14),
+            Constraint::Length(20),
+            Constraint::Length(11),
+            Constraint::Length(14),
+        ];
+        let path_width = table_column_width(area, &widths, 1);
         let rows: Vec<_> = self
             .view
             .iter()
             .filter_map(|idx| self.results.get(*idx))
-            .map(create_row)
+            .map(|r| {
+                create_row(
+                    r,
+                    self.time_format,
+                    self.size_mode,
+                    self.marked.contains(&r.path),
+                    self.history.delta(&r.path, r.size),
+                    self.labels.get(&r.path),
+                    path_width,
+                )
+            })
             .collect();
         let human_size = format_size(self.sum, DECIMAL);
+        let marked_title = if self.marked.is_empty() {
+            Line::from("")
+        } else {
+            Line::from(format!(
+                " {} marked ({}) ",
+                self.marked.len(),
+                format_size(self.marked_size(), DECIMAL)
+            ))
+            .style(Style::default().fg(Color::LightRed))
+        };
+        let title = if self.stale_only {
+            format!(
+                " Projects (not updated for {}+ days) ",
+                self.stale_filter.days
+            )
+        } else {
+            String::from(" Projects ")
+        };
 
-        let table = Table::new(
-            rows,
-            &[
-                Constraint::Length(3),
-                Constraint::Percentage(60),
-                Constraint::Length(10),
-                Constraint::Length(20),
-                Constraint::Length(11),
-            ],
-        )
-        .header(
-            Row::new(vec![
-                "",
-                "Project",
-                "Size",
-                "Last project update",
-                "Parent size",
-            ])
-            .style(
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec![
+                    "",
+                    "Project",
+                    "Size",
+                    "of which VCS",
+                    "Last project update",
+                    "Parent size",
+                    "Since last run",
+                ])
+                .style(
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            )
+            .footer(Row::new(vec![
+                Cell::from(""),
+                Cell::from(""),
+                Cell::from(human_size.as_str())
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+                Cell::from(""),
+                Cell::from(""),
+                Cell::from(""),
+                Cell::from(""),
+            ]))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .title(marked_title)
+                    .title(position_indicator(self.state.selected(), self.view.len()))
+                    .title_style(Style::default().fg(Color::LightYellow))
+                    .border_style(Style::default().fg(Color::LightYellow)),
+            )
+            .row_highlight_style(
                 Style::default()
-                    .fg(Color::Blue)
+                    .bg(Color::Blue)
+                    .fg(Color::White)
                     .add_modifier(Modifier::BOLD),
-            ),
-        )
-        .footer(Row::new(vec![
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(human_size.as_str()).style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from(""),
-            Cell::from(""),
-        ]))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Projects ")
-                .title_style(Style::default().fg(Color::LightYellow))
-                .border_style(Style::default().fg(Color::LightYellow)),
-        )
-        .row_highlight_style(
-            Style::default()
-                .bg(Color::Blue)
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol("► ");
+            )
+            .highlight_symbol("► ");
 
         frame.render_stateful_widget(table, area, &mut self.state);
 
@@ -290,14 +647,76 @@ impl Component for ProjectsTab {
     }
 }
 
-fn create_row<'a>(result: &'a ProjectResult) -> Row<'a> {
-    Row::new(vec![
-        Cell::from(format!("{} ", result.lang)),
-        Cell::from(Line::from(result.path.display().to_string())),
-        size_cell(result.size),
-        last_update_cell(now(), result.last_update),
-        dimmed_size_cell(result.parent.as_ref().map(|p| p.size).unwrap_or_default()),
-    ])
+fn create_row<'a>(
+    result: &'a ProjectResult,
+    time_format: TimeFormat,
+    size_mode: SizeMode,
+    marked: bool,
+    delta: Option<i64>,
+    label: Option<&str>,
+    path_width: usize,
+) -> Row<'a> {
+    let row = Row::new(vec![
+        Cell::from(format!("{}{} ", mark_symbol(marked), result.lang)),
+        path_cell(result, label, path_width),
+        size_cell(result.size_in(size_mode)),
+        result
+            .vcs_size
+            .map(dimmed_size_cell)
+            .unwrap_or_else(|| Cell::from("")),
+        last_update_cell(now(), result.last_update, time_format),
+        dimmed_size_cell(
+            result
+                .parent
+                .as_ref()
+                .map(|p| p.size_in(size_mode))
+                .unwrap_or_default(),
+        ),
+        delta_cell(delta),
+    ]);
+    if marked {
+        row.style(marked_style())
+    } else {
+        row
+    }
+}
+
+/// Path of the project; orphaned virtualenvs are marked and configured labels shown after the path
+fn path_cell(result: &ProjectResult, label: Option<&str>, width: usize) -> Cell<'static> {
+    let path = result.path.display().to_string();
+    let mut marks = vec![];
+    if result.orphaned {
+        marks.push(Span::styled(
+            format!(" {ORPHANED_MARK}"),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    if let Some(label) = label {
+        marks.push(Span::styled(
+            format!(" [{label}]"),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    let marks_width: usize = marks.iter().map(|span| span.width()).sum();
+    let mut spans = vec![Span::raw(truncate_middle(
+        &path,
+        width.saturating_sub(marks_width),
+    ))];
+    spans.extend(marks);
+    Cell::from(Line::from(spans))
+}
+
+/// Count sizes falling into each of the `HISTOGRAM_BUCKETS`
+fn size_histogram(sizes: impl Iterator<Item = u64>) -> [u64; HISTOGRAM_BUCKETS.len()] {
+    let mut histogram = [0; HISTOGRAM_BUCKETS.len()];
+    for size in sizes {
+        let bucket = HISTOGRAM_BUCKETS
+            .iter()
+            .position(|(limit, _)| size < *limit)
+            .unwrap_or(HISTOGRAM_BUCKETS.len() - 1);
+        histogram[bucket] += 1;
+    }
+    histogram
 }
 
 impl Navigable for ProjectsTab {
@@ -331,3 +750,228 @@ impl Navigable for ProjectsTab {
         self.sync_scroll();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_histogram() {
+        let sizes = [
+            0,
+            999_999,
+            1_000_000,
+            50_000_000,
+            2_000_000_000,
+            20_000_000_000,
+            u64::MAX,
+        ];
+        assert_eq!(size_histogram(sizes.into_iter()), [2, 1, 1, 0, 1, 2]);
+        assert_eq!(size_histogram(std::iter::empty()), [0; 6]);
+    }
+
+    #[test]
+    fn test_jump_to_letter() {
+        let project = |path: &str| ProjectResult {
+            path: std::path::PathBuf::from(path),
+            lang: crate::discovery::Language::Rust,
+            size: 0,
+            allocated_size: 0,
+            last_update: None,
+            parent: None,
+            vcs_size: None,
+            orphaned: false,
+            root: None,
+        };
+        let mut tab = ProjectsTab::new(
+            vec![
+                project("/projects/alpha/target"),
+                project("/projects/beta/target"),
+                project("/projects/apple/target"),
+            ],
+            TimeFormat::default(),
+            None,
+            Rc::default(),
+            Labels::default(),
+        );
+
+        assert!(tab.handle_key(KeyCode::Char('f')).is_none());
+        let message = tab.handle_key(KeyCode::Char('a')).unwrap();
+        tab.update(message);
+        assert_eq!(tab.state.selected(), Some(2));
+
+        tab.update(ProjectsTabMessage::JumpTo('a'));
+        assert_eq!(tab.state.selected(), Some(0));
+
+        tab.update(ProjectsTabMessage::JumpTo('x'));
+        assert_eq!(tab.state.selected(), Some(0));
+
+        // Keys are handled normally again after the jump
+        assert!(matches!(
+            tab.handle_key(KeyCode::Char('j')),
+            Some(ProjectsTabMessage::MoveDown)
+        ));
+    }
+
+    #[test]
+    fn test_toggle_stale() {
+        let project = |path: &str, age_days: Option<u64>| ProjectResult {
+            path: std::path::PathBuf::from(path),
+            lang: crate::discovery::Language::Rust,
+            size: 1,
+            allocated_size: 1,
+            last_update: age_days.map(|days| now() - std::time::Duration::from_days(days)),
+            parent: None,
+            vcs_size: None,
+            orphaned: false,
+            root: None,
+        };
+        let mut tab = ProjectsTab::new(
+            vec![
+                project("/projects/fresh/target", Some(1)),
+                project("/projects/old/target", Some(10)),
+                project("/projects/unknown/target", None),
+            ],
+            TimeFormat::default(),
+            Some(StaleFilter {
+                days: 10,
+                include_undated: false,
+            }),
+            Rc::default(),
+            Labels::default(),
+        );
+        assert_eq!(tab.view, vec![1]);
+        assert_eq!(tab.sum, 1);
+
+        let message = tab.handle_key(KeyCode::Char('o')).unwrap();
+        tab.update(message);
+        assert_eq!(tab.view, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_apply_rescan() {
+        let project = |path: &str| ProjectResult {
+            path: std::path::PathBuf::from(path),
+            lang: crate::discovery::Language::Rust,
+            size: 10,
+            allocated_size: 10,
+            last_update: None,
+            parent: Some(crate::discovery::ParentInfo {
+                path: std::path::PathBuf::from(path)
+                    .parent()
+                    .unwrap()
+                    .to_path_buf(),
+                size: 20,
+                allocated_size: 20,
+            }),
+            vcs_size: None,
+            orphaned: false,
+            root: None,
+        };
+        let mut tab = ProjectsTab::new(
+            vec![project("/projects/a/target"), project("/projects/b/target")],
+            TimeFormat::default(),
+            None,
+            Rc::default(),
+            Labels::default(),
+        );
+
+        let message = tab.update(ProjectsTabMessage::Rescan);
+        assert!(matches!(
+            message,
+            Some(AppMessage::RescanPath(path)) if path.ends_with("a/target")
+        ));
+
+        let message = tab.update(ProjectsTabMessage::ApplyRescan(Rescanned::Updated {
+            path: std::path::PathBuf::from("/projects/a/target"),
+            size: 5,
+            allocated_size: 8,
+            last_update: Some(now()),
+            parent_size: Some((15, 16)),
+        }));
+        assert!(matches!(message, Some(AppMessage::SetStatus(_))));
+        assert_eq!(tab.results[0].size, 5);
+        assert_eq!(tab.results[0].parent.as_ref().unwrap().size, 15);
+        assert_eq!(tab.sum, 15);
+        assert_eq!(tab.results[0].parent.as_ref().unwrap().allocated_size, 16);
+
+        tab.set_size_mode(SizeMode::Allocated);
+        assert_eq!(tab.sum, 18);
+        tab.set_size_mode(SizeMode::Apparent);
+
+        tab.update(ProjectsTabMessage::ApplyRescan(Rescanned::Missing(
+            std::path::PathBuf::from("/projects/a/target"),
+        )));
+        assert_eq!(tab.results.len(), 1);
+        assert_eq!(tab.preprocessed_filter_paths, vec!["/projects/b/target"]);
+        assert_eq!(tab.view, vec![0]);
+    }
+
+    #[test]
+    fn test_mark_and_clean() {
+        let project = |path: &str, size: u64| ProjectResult {
+            path: std::path::PathBuf::from(path),
+            lang: crate::discovery::Language::Rust,
+            size,
+            allocated_size: size,
+            last_update: None,
+            parent: None,
+            vcs_size: None,
+            orphaned: false,
+            root: None,
+        };
+        let mut tab = ProjectsTab::new(
+            vec![
+                project("/projects/a/target", 1),
+                project("/projects/b/target", 2),
+                project("/projects/c/target", 4),
+            ],
+            TimeFormat::default(),
+            None,
+            Rc::default(),
+            Labels::default(),
+        );
+        assert!(matches!(
+            tab.update(ProjectsTabMessage::CleanMarked),
+            Some(AppMessage::SetError(_))
+        ));
+
+        // Marking moves to the next row
+        let message = tab.handle_key(KeyCode::Char(' ')).unwrap();
+        tab.update(message);
+        tab.update(ProjectsTabMessage::MoveDown);
+        tab.update(ProjectsTabMessage::ToggleMark);
+        assert_eq!(tab.marked.len(), 2);
+        tab.update(ProjectsTabMessage::Home);
+        tab.update(ProjectsTabMessage::ToggleMark);
+        assert_eq!(tab.marked.len(), 1);
+        tab.update(ProjectsTabMessage::Home);
+        tab.update(ProjectsTabMessage::ToggleMark);
+        assert_eq!(tab.marked.len(), 2);
+
+        let message = tab.handle_key(KeyCode::Char('X')).unwrap();
+        let Some(AppMessage::ConfirmClean { paths, size }) = tab.update(message) else {
+            panic!("Confirmation expected");
+        };
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/projects/a/target"),
+                PathBuf::from("/projects/c/target")
+            ]
+        );
+        assert_eq!(size, 5);
+
+        let message = tab.update(ProjectsTabMessage::ApplyClean(Cleaned {
+            removed: paths,
+            failed: vec![],
+        }));
+        assert!(
+            matches!(message, Some(AppMessage::SetStatus(status)) if status == "Removed 2 paths, freed 5 B")
+        );
+        assert!(tab.marked.is_empty());
+        assert_eq!(tab.preprocessed_filter_paths, vec!["/projects/b/target"]);
+        assert_eq!(tab.view, vec![0]);
+        assert_eq!(tab.sum, 2);
+    }
+}
@@ -94,9 +94,11 @@ impl Component for SortModal {
             KeyCode::Home => Some(SortModalMessage::Home),
             KeyCode::End => Some(SortModalMessage::End),
             KeyCode::Char('p') => Some(SortModalMessage::SortBy(SortBy::Project)),
+            KeyCode::Char('n') => Some(SortModalMessage::SortBy(SortBy::Name)),
             KeyCode::Char('s') => Some(SortModalMessage::SortBy(SortBy::Size)),
             KeyCode::Char('u') => Some(SortModalMessage::SortBy(SortBy::LastUpdate)),
             KeyCode::Char('d') => Some(SortModalMessage::SortBy(SortBy::DetectedProjects)),
+            KeyCode::Char('l') => Some(SortModalMessage::SortBy(SortBy::Language)),
             KeyCode::Enter => Some(SortModalMessage::SelectOption),
             _ => None,
         }
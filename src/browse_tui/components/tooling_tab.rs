@@ -1,7 +1,9 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 use humansize::{DECIMAL, format_size};
-use ratatui::layout::{Alignment, Constraint, Rect};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
@@ -10,9 +12,18 @@ use ratatui::widgets::{
 use ratatui::{Frame, crossterm::event::KeyCode};
 
 use crate::browse_tui::component::{Component, Navigable};
-use crate::browse_tui::helpers::{last_update_cell, now, size_cell};
-use crate::browse_tui::message::{AppMessage, SortBy, SortDirection};
+use crate::browse_tui::components::details_pane::{
+    DETAILS_PANE_HEIGHT, render_details_pane, tooling_details,
+};
+use crate::browse_tui::helpers::{
+    delta_cell, last_update_cell, mark_symbol, marked_style, now, position_indicator, size_cell,
+    table_column_width, truncate_middle,
+};
+use crate::browse_tui::message::{AppMessage, Cleaned, Rescanned, SortBy, SortDirection};
 use crate::discovery::ToolingResult;
+use crate::display_tools::TimeFormat;
+use crate::file_info::SizeMode;
+use crate::history::History;
 
 #[derive(Debug)]
 pub struct ToolingTab {
@@ -23,12 +34,21 @@ pub struct ToolingTab {
     page_size: u16,
     sort_by: Option<SortBy>,
     sort_direction: SortDirection,
+    time_format: TimeFormat,
+    size_mode: SizeMode,
+    marked: HashSet<PathBuf>,
+    history: Rc<History>,
+    show_details: bool,
 }
 
 impl ToolingTab {
-    const SORT_OPTIONS: [SortBy; 2] = [SortBy::Size, SortBy::LastUpdate];
+    const SORT_OPTIONS: [SortBy; 3] = [SortBy::Size, SortBy::LastUpdate, SortBy::Language];
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
 
-    pub fn new(results: Vec<ToolingResult>) -> Self {
+    pub fn new(results: Vec<ToolingResult>, time_format: TimeFormat, history: Rc<History>) -> Self {
         Self {
             state: {
                 let mut projects_state = TableState::default();
@@ -41,9 +61,24 @@ impl ToolingTab {
             page_size: 0,
             sort_by: None,
             sort_direction: SortDirection::default(),
+            time_format,
+            size_mode: SizeMode::default(),
+            marked: HashSet::new(),
+            history,
+            show_details: false,
         }
     }
 
+    pub fn set_size_mode(&mut self, size_mode: SizeMode) {
+        self.size_mode = size_mode;
+        self.sum = self.total_size();
+        self.sort_results();
+    }
+
+    fn total_size(&self) -> u64 {
+        self.results.iter().map(|r| r.size_in(self.size_mode)).sum()
+    }
+
     fn enter(&mut self) -> Option<AppMessage> {
         self.state
             .selected()
@@ -76,6 +111,109 @@ impl ToolingTab {
         }
     }
 
+    fn rescan(&mut self) -> Option<AppMessage> {
+        self.state
+            .selected()
+            .and_then(|idx| self.results.get(idx))
+            .map(|res| res.path.clone())
+            .map(AppMessage::RescanPath)
+    }
+
+    fn apply_rescan(&mut self, rescanned: Rescanned) -> Option<AppMessage> {
+        let idx = self
+            .results
+            .iter()
+            .position(|r| &r.path == rescanned.path())?;
+        let status = rescanned.status();
+        match rescanned {
+            Rescanned::Updated {
+                size,
+                allocated_size,
+                last_update,
+                ..
+            } => {
+                self.results[idx].size = size;
+                self.results[idx].allocated_size = allocated_size;
+                self.results[idx].last_update = last_update;
+            }
+            Rescanned::Missing(_) => {
+                let removed = self.results.remove(idx);
+                self.marked.remove(&removed.path);
+                self.adjust_selection();
+            }
+        }
+        self.sum = self.total_size();
+
+        Some(AppMessage::SetStatus(status))
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(path) = self
+            .state
+            .selected()
+            .and_then(|idx| self.results.get(idx))
+            .map(|res| res.path.clone())
+        else {
+            return;
+        };
+        if !self.marked.remove(&path) {
+            self.marked.insert(path);
+        }
+        self.move_down();
+    }
+
+    fn marked_size(&self) -> u64 {
+        self.results
+            .iter()
+            .filter(|r| self.marked.contains(&r.path))
+            .map(|r| r.size_in(self.size_mode))
+            .sum()
+    }
+
+    fn clean_marked(&mut self) -> Option<AppMessage> {
+        if self.marked.is_empty() {
+            return Some(AppMessage::SetError(String::from(
+                "No tools are marked. Use Space to mark them.",
+            )));
+        }
+        let mut paths = self.marked.iter().cloned().collect::<Vec<_>>();
+        paths.sort();
+        Some(AppMessage::ConfirmClean {
+            paths,
+            size: self.marked_size(),
+        })
+    }
+
+    fn apply_clean(&mut self, cleaned: Cleaned) -> Option<AppMessage> {
+        let freed = self
+            .results
+            .iter()
+            .filter(|r| cleaned.removed.contains(&r.path))
+            .map(|r| r.size_in(self.size_mode))
+            .sum();
+        self.results.retain(|r| !cleaned.removed.contains(&r.path));
+        self.marked.clear();
+        self.adjust_selection();
+        self.sum = self.total_size();
+
+        Some(cleaned.report(freed))
+    }
+
+    fn adjust_selection(&mut self) {
+        if self.results.is_empty() {
+            self.state.select(None);
+        } else if let Some(selected) = self.state.selected() {
+            self.state
+                .select(Some(selected.min(self.results.len() - 1)));
+        }
+        self.scroll_state =
+            ScrollbarState::new(self.results.len()).position(self.state.selected().unwrap_or(0));
+    }
+
+    fn toggle_details(&mut self) {
+        self.show_details = !self.show_details;
+    }
+
     fn request_sort(&mut self) -> Option<AppMessage> {
         Some(AppMessage::OpenSort(&Self::SORT_OPTIONS))
     }
@@ -90,20 +228,28 @@ impl ToolingTab {
             self.sort_by = Some(sort_by);
             self.sort_direction = sort_by.default_direction();
         }
+        self.sort_results();
 
-        if let Some(sort_by) = self.sort_by.as_ref() {
-            match sort_by {
-                SortBy::Size => self.results.sort_by_key(|r| r.size),
-                SortBy::LastUpdate => self.results.sort_by_key(|r| r.last_update),
-                _ => {}
-            }
+        None
+    }
 
-            if self.sort_direction == SortDirection::Descending {
-                self.results.reverse();
-            }
+    fn sort_results(&mut self) {
+        let Some(sort_by) = self.sort_by else {
+            return;
+        };
+        let size_mode = self.size_mode;
+        match sort_by {
+            SortBy::Size => self.results.sort_by_key(|r| (r.size_in(size_mode), r.lang)),
+            SortBy::LastUpdate => self.results.sort_by_key(|r| r.last_update),
+            SortBy::Language => self
+                .results
+                .sort_by_key(|r| (r.lang, std::cmp::Reverse(r.size_in(size_mode)))),
+            _ => {}
         }
 
-        None
+        if self.sort_direction == SortDirection::Descending {
+            self.results.reverse();
+        }
     }
 }
 
@@ -120,6 +266,12 @@ pub enum ToolingTabMessage {
     EnterParent,
     RequestSort,
     ApplySort(SortBy),
+    ToggleDetails,
+    Rescan,
+    ApplyRescan(Rescanned),
+    ToggleMark,
+    CleanMarked,
+    ApplyClean(Cleaned),
 }
 
 impl Component for ToolingTab {
@@ -148,6 +300,20 @@ impl Component for ToolingTab {
             ToolingTabMessage::ApplySort(sort_by) => {
                 return self.apply_sort(sort_by);
             }
+            ToolingTabMessage::ToggleDetails => self.toggle_details(),
+            ToolingTabMessage::Rescan => {
+                return self.rescan();
+            }
+            ToolingTabMessage::ApplyRescan(rescanned) => {
+                return self.apply_rescan(rescanned);
+            }
+            ToolingTabMessage::ToggleMark => self.toggle_mark(),
+            ToolingTabMessage::CleanMarked => {
+                return self.clean_marked();
+            }
+            ToolingTabMessage::ApplyClean(cleaned) => {
+                return self.apply_clean(cleaned);
+            }
         }
         None
     }
@@ -164,63 +330,120 @@ impl Component for ToolingTab {
             KeyCode::Home => Some(ToolingTabMessage::Home),
             KeyCode::End => Some(ToolingTabMessage::End),
             KeyCode::Char('s') => Some(ToolingTabMessage::RequestSort),
+            KeyCode::Tab => Some(ToolingTabMessage::ToggleDetails),
+            KeyCode::Char('R') => Some(ToolingTabMessage::Rescan),
+            KeyCode::Char(' ') => Some(ToolingTabMessage::ToggleMark),
+            KeyCode::Char('X') => Some(ToolingTabMessage::CleanMarked),
             _ => None,
         }
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let area = if self.show_details {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(DETAILS_PANE_HEIGHT)])
+                .split(area);
+            let details = self
+                .state
+                .selected()
+                .and_then(|idx| self.results.get(idx))
+                .map(|result| tooling_details(result, self.size_mode, now()));
+            render_details_pane(frame, chunks[1], details);
+            chunks[0]
+        } else {
+            area
+        };
         self.page_size = area.height.saturating_sub(3);
 
-        let rows: Vec<_> = self.results.iter().map(create_row).collect();
+        let widths = [
+            Constraint::Length(5),
+            Constraint::Percentage(60),
+            Constraint::Length(10),
+            Constraint::Length(20),
+            Constraint::Length(14),
+            Constraint::Length(4),
+        ];
+        let tool_width = table_column_width(area, &widths, 1);
+        let rows: Vec<_> = self
+            .results
+            .iter()
+            .map(|r| {
+                create_row(
+                    r,
+                    self.time_format,
+                    self.size_mode,
+                    self.marked.contains(&r.path),
+                    self.history.delta(&r.path, r.size),
+                    tool_width,
+                )
+            })
+            .collect();
         let human_size = format_size(self.sum, DECIMAL);
+        let marked_title = if self.marked.is_empty() {
+            Line::from("")
+        } else {
+            Line::from(format!(
+                " {} marked ({}) ",
+                self.marked.len(),
+                format_size(self.marked_size(), DECIMAL)
+            ))
+            .style(Style::default().fg(Color::LightRed))
+        };
 
-        let table = Table::new(
-            rows,
-            &[
-                Constraint::Length(3),
-                Constraint::Percentage(60),
-                Constraint::Length(10),
-                Constraint::Length(20),
-                Constraint::Length(4),
-            ],
-        )
-        .header(
-            Row::new(vec!["", "Tool", "Size", "Last project update", "Info"]).style(
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec![
+                    "",
+                    "Tool",
+                    "Size",
+                    "Last project update",
+                    "Since last run",
+                    "Info",
+                ])
+                .style(
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            )
+            .footer(Row::new(vec![
+                Cell::from(""),
+                Cell::from(""),
+                Cell::from(human_size.as_str())
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+                Cell::from(""),
+                Cell::from(""),
+                Cell::from(""),
+            ]))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Tools ")
+                    .title(marked_title)
+                    .title(position_indicator(
+                        self.state.selected(),
+                        self.results.len(),
+                    ))
+                    .title_style(Style::default().fg(Color::LightYellow))
+                    .border_style(Style::default().fg(Color::LightYellow))
+                    .title_bottom(
+                        Line::from(" Use 'i' to open info window for the selected tool ")
+                            .alignment(Alignment::Right)
+                            .style(
+                                Style::default()
+                                    .fg(Color::Gray)
+                                    .add_modifier(Modifier::ITALIC),
+                            ),
+                    ),
+            )
+            .row_highlight_style(
                 Style::default()
-                    .fg(Color::Blue)
+                    .bg(Color::Blue)
+                    .fg(Color::White)
                     .add_modifier(Modifier::BOLD),
-            ),
-        )
-        .footer(Row::new(vec![
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(human_size.as_str()).style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from(""),
-            Cell::from(""),
-        ]))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Tools ")
-                .title_style(Style::default().fg(Color::LightYellow))
-                .border_style(Style::default().fg(Color::LightYellow))
-                .title_bottom(
-                    Line::from(" Use 'i' to open info window for the selected tool ")
-                        .alignment(Alignment::Right)
-                        .style(
-                            Style::default()
-                                .fg(Color::Gray)
-                                .add_modifier(Modifier::ITALIC),
-                        ),
-                ),
-        )
-        .row_highlight_style(
-            Style::default()
-                .bg(Color::Blue)
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol("► ");
+            )
+            .highlight_symbol("► ");
 
         frame.render_stateful_widget(table, area, &mut self.state);
 
@@ -236,20 +459,38 @@ impl Component for ToolingTab {
     }
 }
 
-fn create_row<'a>(result: &'a ToolingResult) -> Row<'a> {
-    Row::new(vec![
-        Cell::from(format!("{} ", result.lang)),
+fn create_row<'a>(
+    result: &'a ToolingResult,
+    time_format: TimeFormat,
+    size_mode: SizeMode,
+    marked: bool,
+    delta: Option<i64>,
+    tool_width: usize,
+) -> Row<'a> {
+    // The description is short, so only the path is shortened
+    let path_width = tool_width.saturating_sub(result.description.chars().count() + 3);
+    let row = Row::new(vec![
+        Cell::from(format!("{}{} ", mark_symbol(marked), result.lang)),
         Cell::from(Line::from(vec![
             Span::raw(result.description),
             Span::styled(
-                format!(" ({})", result.path.display()),
+                format!(
+                    " ({})",
+                    truncate_middle(&result.path.display().to_string(), path_width)
+                ),
                 Style::default().add_modifier(Modifier::DIM),
             ),
         ])),
-        size_cell(result.size),
-        last_update_cell(now(), result.last_update),
+        size_cell(result.size_in(size_mode)),
+        last_update_cell(now(), result.last_update, time_format),
+        delta_cell(delta),
         Cell::from(Span::raw(result.info.map(|_| "📖 »").unwrap_or_default())),
-    ])
+    ]);
+    if marked {
+        row.style(marked_style())
+    } else {
+        row
+    }
 }
 
 impl Navigable for ToolingTab {
@@ -10,8 +10,12 @@ use ratatui::widgets::{
 use ratatui::{Frame, crossterm::event::KeyCode};
 
 use crate::browse_tui::component::{Component, Navigable};
-use crate::browse_tui::helpers::{dimmed_size_cell, last_update_cell, now, size_cell};
+use crate::browse_tui::helpers::{
+    dimmed_size_cell, last_update_cell, now, position_indicator, size_cell,
+};
 use crate::browse_tui::message::{AppMessage, SortBy, SortDirection, Tab};
+use crate::display_tools::TimeFormat;
+use crate::file_info::SizeMode;
 use crate::vcs_postprocess::EnrichedVcsResult;
 
 #[derive(Debug)]
@@ -25,6 +29,8 @@ pub struct VcsTab {
     page_size: u16,
     sort_by: Option<SortBy>,
     sort_direction: SortDirection,
+    time_format: TimeFormat,
+    size_mode: SizeMode,
     active_filter: Option<String>,
 }
 
@@ -36,7 +42,7 @@ impl VcsTab {
         SortBy::DetectedProjects,
     ];
 
-    pub fn new(results: Vec<EnrichedVcsResult>) -> Self {
+    pub fn new(results: Vec<EnrichedVcsResult>, time_format: TimeFormat) -> Self {
         let filter_paths = results
             .iter()
             .map(|r| r.path.to_string_lossy().to_ascii_lowercase())
@@ -56,10 +62,16 @@ impl VcsTab {
             page_size: 0,
             sort_by: None,
             sort_direction: SortDirection::default(),
+            time_format,
+            size_mode: SizeMode::default(),
             active_filter: None,
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
     pub fn apply_filter(&mut self, filter: Option<String>) {
         let normalized = filter
             .map(|raw| raw.trim().to_ascii_lowercase())
@@ -73,6 +85,11 @@ impl VcsTab {
         self.refresh_view();
     }
 
+    pub fn set_size_mode(&mut self, size_mode: SizeMode) {
+        self.size_mode = size_mode;
+        self.refresh_view();
+    }
+
     fn selected_result(&self) -> Option<&EnrichedVcsResult> {
         self.state
             .selected()
@@ -146,11 +163,14 @@ impl VcsTab {
                 SortBy::Project => self.view.sort_by(|a_idx, b_idx| {
                     self.results[*a_idx].path.cmp(&self.results[*b_idx].path)
                 }),
-                SortBy::Size => self.view.sort_by_key(|idx| self.results[*idx].size),
+                SortBy::Size => self
+                    .view
+                    .sort_by_key(|idx| self.results[*idx].size_in(self.size_mode)),
                 SortBy::LastUpdate => self.view.sort_by_key(|idx| &self.results[*idx].last_update),
                 SortBy::DetectedProjects => self
                     .view
                     .sort_by_key(|idx| self.results[*idx].matched_projects.len()),
+                SortBy::Name | SortBy::Language => {}
             }
 
             if self.sort_direction == SortDirection::Descending {
@@ -158,7 +178,11 @@ impl VcsTab {
             }
         }
 
-        self.sum = self.view.iter().map(|&idx| self.results[idx].size).sum();
+        self.sum = self
+            .view
+            .iter()
+            .map(|&idx| self.results[idx].size_in(self.size_mode))
+            .sum();
         self.adjust_selection_to_view();
         self.sync_scroll();
     }
@@ -243,14 +267,14 @@ impl Component for VcsTab {
             .view
             .iter()
             .filter_map(|idx| self.results.get(*idx))
-            .map(create_row)
+            .map(|r| create_row(r, self.time_format, self.size_mode))
             .collect();
         let human_size = format_size(self.sum, DECIMAL);
 
         let table = Table::new(
             rows,
             &[
-                Constraint::Length(3),
+                Constraint::Length(4),
                 Constraint::Percentage(60),
                 Constraint::Length(10),
                 Constraint::Length(20),
@@ -291,6 +315,7 @@ impl Component for VcsTab {
             Block::default()
                 .borders(Borders::ALL)
                 .title(" Version controlled ")
+                .title(position_indicator(self.state.selected(), self.view.len()))
                 .title_style(Style::default().fg(Color::LightYellow))
                 .border_style(Style::default().fg(Color::LightYellow))
                 .title_bottom(
@@ -325,17 +350,25 @@ impl Component for VcsTab {
     }
 }
 
-fn create_row<'a>(result: &'a EnrichedVcsResult) -> Row<'a> {
-    let detected_projects_size = result.matched_projects.iter().map(|p| p.size).sum();
+fn create_row<'a>(
+    result: &'a EnrichedVcsResult,
+    time_format: TimeFormat,
+    size_mode: SizeMode,
+) -> Row<'a> {
+    let detected_projects_size = result
+        .matched_projects
+        .iter()
+        .map(|p| p.size_in(size_mode))
+        .sum();
 
     Row::new(vec![
-        Cell::from(""),
+        Cell::from(result.kind.to_string()),
         Cell::from(Line::from(result.path.display().to_string())),
-        size_cell(result.size),
-        last_update_cell(now(), result.last_update),
+        size_cell(result.size_in(size_mode)),
+        last_update_cell(now(), result.last_update, time_format),
         size_cell(
             result
-                .size
+                .size_in(size_mode)
                 .saturating_sub(result.vcs_size)
                 .saturating_sub(detected_projects_size),
         ),
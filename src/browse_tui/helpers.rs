@@ -1,13 +1,15 @@
 use std::sync::OnceLock;
 use std::time::SystemTime;
 
-use chrono::{DateTime, Local};
 use humansize::{DECIMAL, format_size};
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::prelude::{Color, Line, Modifier, Span, Style};
-use ratatui::widgets::Cell;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Wrap};
 
-use crate::display_tools::{ColorCode, get_size_color_code, get_time_color_code};
+use crate::display_tools::{
+    ColorCode, TimeFormat, format_delta, format_last_update, get_size_color_code,
+    get_time_color_code,
+};
 
 static NOW: OnceLock<SystemTime> = OnceLock::new();
 
@@ -64,7 +66,7 @@ pub fn size_cell(size: u64) -> Cell<'static> {
 }
 
 fn size_cell_style(size: u64) -> Style {
-    let color = match get_size_color_code(size) {
+    let color = match get_size_color_code(size, None) {
         ColorCode::None => Color::Gray,
         ColorCode::Low => Color::Green,
         ColorCode::Medium => Color::Yellow,
@@ -79,14 +81,12 @@ pub fn dimmed_size_cell(size: u64) -> Cell<'static> {
     Cell::from(text).style(size_cell_style(size).add_modifier(Modifier::DIM))
 }
 
-pub fn last_update_cell(now: SystemTime, last: Option<SystemTime>) -> Cell<'static> {
-    let text = last
-        .map(|t| {
-            DateTime::<Local>::from(t)
-                .format("%Y-%m-%d %H:%M:%S")
-                .to_string()
-        })
-        .unwrap_or_default();
+pub fn last_update_cell(
+    now: SystemTime,
+    last: Option<SystemTime>,
+    time_format: TimeFormat,
+) -> Cell<'static> {
+    let text = format_last_update(&now, last, time_format).unwrap_or_default();
 
     let color = match get_time_color_code(&now, &last) {
         ColorCode::None => Color::Gray,
@@ -98,6 +98,111 @@ pub fn last_update_cell(now: SystemTime, last: Option<SystemTime>) -> Cell<'stat
     Cell::from(text).style(Style::default().fg(color))
 }
 
+/// Shorten `text` to `width` characters by replacing its middle with an ellipsis
+///
+/// The end is kept slightly longer than the beginning, as the last components
+/// of a path are usually the interesting ones, e.g. `/home/foo/pro…/node_modules`.
+pub fn truncate_middle(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let head = (width - 1) / 2;
+    let tail = width - 1 - head;
+    let mut truncated = text.chars().take(head).collect::<String>();
+    truncated.push('…');
+    truncated.extend(text.chars().skip(len - tail));
+    truncated
+}
+
+/// Width of the `column` of a bordered table with a highlight symbol rendered in `area`
+pub fn table_column_width(area: Rect, constraints: &[Constraint], column: usize) -> usize {
+    const BORDERS: u16 = 2;
+    const HIGHLIGHT_SYMBOL: u16 = 2;
+
+    let inner = Rect {
+        width: area.width.saturating_sub(BORDERS + HIGHLIGHT_SYMBOL),
+        ..area
+    };
+    Layout::horizontal(constraints)
+        .spacing(1)
+        .split(inner)
+        .get(column)
+        .map(|rect| rect.width as usize)
+        .unwrap_or_default()
+}
+
+/// Change of the size since the previous run; blank when the path is seen for the first time
+pub fn delta_cell(delta: Option<i64>) -> Cell<'static> {
+    let Some(delta) = delta else {
+        return Cell::from("");
+    };
+
+    let color = match delta.signum() {
+        1 => Color::Red,
+        -1 => Color::Green,
+        _ => Color::Gray,
+    };
+
+    Cell::from(format_delta(delta, None)).style(Style::default().fg(color))
+}
+
+/// Find the next name starting with `letter` after `current`, wrapping around
+///
+/// The comparison is case-insensitive. `current` itself is checked last,
+/// so repeated jumps cycle through all matching names.
+pub fn find_next_by_letter<S: AsRef<str>>(
+    names: &[S],
+    current: Option<usize>,
+    letter: char,
+) -> Option<usize> {
+    let letter = letter.to_lowercase().collect::<String>();
+    let start = current.map(|idx| idx + 1).unwrap_or(0);
+    (0..names.len())
+        .map(|offset| (start + offset) % names.len())
+        .find(|&idx| names[idx].as_ref().to_lowercase().starts_with(&letter))
+}
+
+/// Position of the selected row among `len` rows, e.g. ` 123/512 `
+pub fn position_indicator(selected: Option<usize>, len: usize) -> Line<'static> {
+    let position = match selected {
+        Some(idx) if len > 0 => idx.min(len - 1) + 1,
+        _ => 0,
+    };
+    Line::from(format!(" {position}/{len} ")).alignment(Alignment::Right)
+}
+
+/// Leading symbol of rows marked for cleaning
+pub fn mark_symbol(marked: bool) -> &'static str {
+    if marked { "✔" } else { " " }
+}
+
+/// Style of rows marked for cleaning
+pub fn marked_style() -> Style {
+    Style::default().fg(Color::LightRed)
+}
+
+/// Placeholder of a tab without any results, with a hint how to get some
+pub fn empty_state(
+    title: &'static str,
+    message: &'static str,
+    hint: &'static str,
+) -> Paragraph<'static> {
+    Paragraph::new(vec![
+        Line::from(""),
+        Line::from(message).style(Style::default().add_modifier(Modifier::BOLD)),
+        Line::from(""),
+        Line::from(hint).style(Style::default().fg(Color::DarkGray)),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
+    .block(Block::default().borders(Borders::ALL).title(title))
+}
+
 pub fn percent_bar(width: usize, percent: f64) -> Line<'static> {
     let filled_len = ((width as f64) * percent / 100.0).round() as usize;
 
@@ -112,3 +217,82 @@ pub fn percent_bar(width: usize, percent: f64) -> Line<'static> {
 
     Line::from(spans)
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::widgets::Widget;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_state() {
+        let area = Rect::new(0, 0, 40, 6);
+        let mut buffer = Buffer::empty(area);
+        empty_state(" Tools ", "Nothing here", "Try again").render(area, &mut buffer);
+
+        let lines = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert!(lines[0].starts_with("┌ Tools ─"));
+        assert_eq!(lines[2].trim_matches(['│', ' ']), "Nothing here");
+        assert_eq!(lines[4].trim_matches(['│', ' ']), "Try again");
+    }
+
+    #[test]
+    fn test_position_indicator() {
+        assert_eq!(position_indicator(Some(0), 512).to_string(), " 1/512 ");
+        assert_eq!(position_indicator(Some(122), 512).to_string(), " 123/512 ");
+        // Selection past the end is clamped when rendered
+        assert_eq!(position_indicator(Some(600), 512).to_string(), " 512/512 ");
+        assert_eq!(position_indicator(Some(0), 0).to_string(), " 0/0 ");
+        assert_eq!(position_indicator(None, 0).to_string(), " 0/0 ");
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        let path = "/home/foo/projects/web/node_modules";
+        assert_eq!(truncate_middle(path, 100), path);
+        assert_eq!(truncate_middle(path, path.len()), path);
+        assert_eq!(truncate_middle(path, 25), "/home/foo/pr…node_modules");
+        assert_eq!(truncate_middle(path, 26), "/home/foo/pr…/node_modules");
+        assert_eq!(truncate_middle(path, 2), "…s");
+        assert_eq!(truncate_middle(path, 1), "…");
+        assert_eq!(truncate_middle(path, 0), "");
+        assert_eq!(truncate_middle("/home/žluťoučký/kůň", 9), "/hom…/kůň");
+    }
+
+    #[test]
+    fn test_table_column_width() {
+        let area = Rect::new(0, 0, 104, 10);
+        let constraints = [
+            Constraint::Length(5),
+            Constraint::Min(0),
+            Constraint::Length(10),
+        ];
+        // 100 columns without the borders and the highlight symbol, 2 of them are spacing
+        assert_eq!(table_column_width(area, &constraints, 1), 83);
+        assert_eq!(table_column_width(area, &constraints, 5), 0);
+        assert_eq!(
+            table_column_width(Rect::new(0, 0, 2, 10), &constraints, 1),
+            0
+        );
+    }
+
+    #[test]
+    fn test_find_next_by_letter() {
+        let names = ["alpha", "Beta", "apple", "gamma"];
+
+        assert_eq!(find_next_by_letter(&names, None, 'a'), Some(0));
+        assert_eq!(find_next_by_letter(&names, Some(0), 'a'), Some(2));
+        assert_eq!(find_next_by_letter(&names, Some(2), 'a'), Some(0));
+        assert_eq!(find_next_by_letter(&names, Some(0), 'b'), Some(1));
+        assert_eq!(find_next_by_letter(&names, Some(1), 'B'), Some(1));
+        assert_eq!(find_next_by_letter(&names, Some(0), 'z'), None);
+        assert_eq!(find_next_by_letter::<&str>(&[], None, 'a'), None);
+    }
+}
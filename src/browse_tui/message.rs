@@ -1,4 +1,7 @@
 use std::path::PathBuf;
+use std::time::SystemTime;
+
+use humansize::{DECIMAL, format_size};
 
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
 pub enum Tab {
@@ -27,19 +30,83 @@ pub enum AppMessage {
     SelectTab(Tab),
     EnterBrowser(PathBuf),
     CloseBrowser,
+    RescanPath(PathBuf),
+    CleanPaths(Vec<PathBuf>),
     // Modals controls
     OpenHelp,
     OpenInfo(&'static str),
     OpenSort(&'static [SortBy]),
     RequestSort(SortBy),
+    ConfirmClean { paths: Vec<PathBuf>, size: u64 },
     CloseModal,
+    // Presentation
+    ToggleSizeMode,
     // Filtering,
     StartFilter,
     AcceptFilter,
     DismissFilter,
     SelectTabWithFilter(Tab, String),
-    // Error reporting
+    // Error and status reporting
     SetError(String),
+    SetStatus(String),
+}
+
+/// Current state of a result path after a rescan
+#[derive(Debug)]
+pub enum Rescanned {
+    Updated {
+        path: PathBuf,
+        size: u64,
+        allocated_size: u64,
+        last_update: Option<SystemTime>,
+        /// Apparent and allocated size of the parent
+        parent_size: Option<(u64, u64)>,
+    },
+    Missing(PathBuf),
+}
+
+impl Rescanned {
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            Rescanned::Updated { path, .. } => path,
+            Rescanned::Missing(path) => path,
+        }
+    }
+
+    pub fn status(&self) -> String {
+        match self {
+            Rescanned::Updated { path, .. } => format!("Rescanned {}", path.display()),
+            Rescanned::Missing(path) => {
+                format!("{} doesn't exist anymore, removed", path.display())
+            }
+        }
+    }
+}
+
+/// Outcome of removing the marked paths from the disk
+#[derive(Debug, Default)]
+pub struct Cleaned {
+    pub removed: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl Cleaned {
+    /// Message reported to the user, with the size freed by the removed paths
+    pub fn report(&self, freed: u64) -> AppMessage {
+        match self.failed.first() {
+            Some((path, error)) => AppMessage::SetError(format!(
+                "Failed to remove {} of {} paths, e.g. {}: {error}",
+                self.failed.len(),
+                self.failed.len() + self.removed.len(),
+                path.display()
+            )),
+            None => AppMessage::SetStatus(format!(
+                "Removed {} paths, freed {}",
+                self.removed.len(),
+                format_size(freed, DECIMAL)
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -52,36 +119,44 @@ pub(super) enum SortDirection {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum SortBy {
     Project,
+    Name,
     Size,
     LastUpdate,
     DetectedProjects,
+    Language,
 }
 
 impl SortBy {
     pub fn key(&self) -> char {
         match self {
             SortBy::Project => 'p',
+            SortBy::Name => 'n',
             SortBy::Size => 's',
             SortBy::LastUpdate => 'u',
             SortBy::DetectedProjects => 'd',
+            SortBy::Language => 'l',
         }
     }
 
     pub fn label(&self) -> &str {
         match self {
             SortBy::Project => "Project",
+            SortBy::Name => "Name",
             SortBy::Size => "Size",
             SortBy::LastUpdate => "Last update",
             SortBy::DetectedProjects => "Detected projects",
+            SortBy::Language => "Language",
         }
     }
 
     pub fn default_direction(&self) -> SortDirection {
         match self {
             SortBy::Project => SortDirection::Ascending,
+            SortBy::Name => SortDirection::Ascending,
             SortBy::Size => SortDirection::Descending,
             SortBy::LastUpdate => SortDirection::Ascending,
             SortBy::DetectedProjects => SortDirection::Descending,
+            SortBy::Language => SortDirection::Ascending,
         }
     }
 }
@@ -4,4 +4,4 @@ mod components;
 mod helpers;
 mod message;
 
-pub use app::App;
+pub use app::{App, AppSettings};
@@ -1,11 +1,29 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use clap::{ArgAction, Args, Parser, Subcommand};
+use chrono::{Local, NaiveDate, NaiveTime};
+use clap::error::ErrorKind;
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand};
 
+use crate::display::{GroupBy, OutputFormat, SortOrder};
+use crate::display_tools::{ColorMode, SizeUnit, StaleFilter, TimeFormat};
+use crate::file_info::TimeSource;
+use crate::loader::{LoaderError, LoaderKind, ManifestLoader, SelectedLoader};
 use crate::logging::LoggingLevel;
 
+/// Version with build metadata shown by `--version`
+const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    "\ncommit: ",
+    env!("DDC_BUILD_GIT_HASH"),
+    "\ntarget: ",
+    env!("DDC_BUILD_TARGET"),
+    "\nrustc: ",
+    env!("DDC_BUILD_RUSTC_VERSION"),
+);
+
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, long_version = LONG_VERSION, about, long_about = None)]
 pub struct CliArgs {
     /// Sets the level of verbosity (--verbose/-v, -vv for tracing output)
     #[arg(short = 'v', long = "verbose", action = ArgAction::Count)]
@@ -14,16 +32,44 @@ pub struct CliArgs {
     pub command: Option<Commands>,
 }
 
+impl CliArgs {
+    /// Check conflicts between the global and the subcommand arguments, which clap can't express
+    pub fn check_conflicts(&self) -> Result<(), clap::Error> {
+        if self.verbosity > 0
+            && let Some(Commands::Analyze(ref cmd_args)) = self.command
+            && cmd_args.quiet
+        {
+            return Err(CliArgs::command().error(
+                ErrorKind::ArgumentConflict,
+                "the argument '--quiet' cannot be used with '--verbose'",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Logging level requested by the arguments
+    pub fn logging_level(&self) -> LoggingLevel {
+        match self.command {
+            Some(Commands::Analyze(ref cmd_args)) if cmd_args.quiet => LoggingLevel::Quiet,
+            _ => LoggingLevel::from(self.verbosity),
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Generates a skeleton of the configuration file
-    GenerateConfig,
+    GenerateConfig(GenerateConfigArgs),
     /// Show default paths that are explored
     ShowDefinitions,
     /// Analyzes data (default command)
     Analyze(AnalyzeArgs),
     /// Interactive browser of the analyzed data
     Browse(BrowseArgs),
+    /// Checks the setup without scanning anything
+    Doctor(DoctorArgs),
+    /// Validates the configuration file and shows how it's interpreted
+    ConfigCheck(ConfigCheckArgs),
 }
 
 #[derive(Args, Debug, Default)]
@@ -31,47 +77,311 @@ pub struct AnalysisSharedArgs {
     /// Do not display the progress bar
     #[arg(short = 'p', long)]
     pub no_progress: bool,
-    /// Use the following config file instead of autodiscovery process
+    /// Use the following config file instead of autodiscovery process [env: DDC_CONFIG]
     #[arg(short = 'c', long, value_name = "FILE")]
     pub config: Option<PathBuf>,
+    /// Fail if any of the configured paths doesn't exist
+    #[arg(long)]
+    pub strict: bool,
+    /// Timestamp used for the last change (access time is often not updated; see `noatime`)
+    #[arg(long, value_enum, default_value_t)]
+    pub time_source: TimeSource,
+    /// Format of the last change timestamps
+    #[arg(long, value_enum, default_value_t)]
+    pub time_format: TimeFormat,
+    /// Skip hidden files and directories (may miss hidden build directories like `.next`)
+    #[arg(long, conflicts_with = "include_hidden")]
+    pub exclude_hidden: bool,
+    /// Scan hidden files and directories even if the configuration skips them
+    #[arg(long)]
+    pub include_hidden: bool,
+    /// Also scan common project roots (~/projects, ~/dev, ~/src, ~/Code, ~/workspace) if they exist
+    ///
+    /// The configuration file isn't required then.
+    #[arg(long)]
+    pub auto_roots: bool,
+    /// Hide results located in PATH (can be used multiple times)
+    #[arg(long = "exclude-path", value_name = "PATH")]
+    pub exclude_paths: Vec<PathBuf>,
+    /// Don't descend into other file systems mounted inside the scanned paths (like `du -x`)
+    #[arg(long)]
+    pub one_file_system: bool,
+    /// Count only files ignored by git (build artifacts) in the sizes of project roots and repositories
+    #[arg(long)]
+    pub git_aware: bool,
+    /// Take the last change from files only; directory timestamps change on any added or removed entry
+    #[arg(long)]
+    pub ignore_dir_times: bool,
+    /// Print how long walking directories and loading metadata took
+    #[arg(long)]
+    pub profile: bool,
+    /// Show only results not updated for at least N days
+    #[arg(long, value_name = "N")]
+    pub stale_days: Option<u64>,
+    /// With --stale-days, include results whose last update is unknown
+    #[arg(long, requires = "stale_days")]
+    pub include_undated: bool,
+    /// Never change anything on the disk: deleting in the browser is disabled and the size history isn't recorded
+    #[arg(long)]
+    pub read_only: bool,
+    /// Stop collecting results after N of them, so a misconfigured scan stays usable [default: 10000; 0 for no limit]
+    #[arg(long, value_name = "N")]
+    pub max_results: Option<usize>,
+    /// Load the paths listed in FILE (`-` for stdin) instead of walking the disk; each line is a path, optionally preceded by its size and a tab
+    #[arg(long, value_name = "FILE")]
+    pub manifest: Option<PathBuf>,
+    /// Loader used for scanning the disk (for benchmarking and debugging)
+    #[arg(long, value_enum, default_value_t, env = "DDC_LOADER", hide = true)]
+    pub loader: LoaderKind,
+}
+
+impl AnalysisSharedArgs {
+    const DEFAULT_MAX_RESULTS: usize = 10_000;
+
+    /// Resolves whether hidden entries are skipped; CLI flags take precedence over the config
+    pub fn skip_hidden(&self, configured: bool) -> bool {
+        match (self.exclude_hidden, self.include_hidden) {
+            (true, _) => true,
+            (_, true) => false,
+            _ => configured,
+        }
+    }
+
+    /// Filter requested with `--stale-days`, if any
+    pub fn stale_filter(&self) -> Option<StaleFilter> {
+        self.stale_days.map(|days| StaleFilter {
+            days,
+            include_undated: self.include_undated,
+        })
+    }
+
+    /// Maximum number of collected results; `None` when the cap is turned off with 0
+    pub fn max_results(&self) -> Option<usize> {
+        match self.max_results.unwrap_or(Self::DEFAULT_MAX_RESULTS) {
+            0 => None,
+            max => Some(max),
+        }
+    }
+
+    /// Loader for the scan; a manifest is read completely upfront
+    pub fn select_loader(&self) -> Result<SelectedLoader, LoaderError> {
+        match &self.manifest {
+            Some(path) => Ok(SelectedLoader::Manifest(ManifestLoader::open(path)?)),
+            None => Ok(SelectedLoader::Walk(self.loader)),
+        }
+    }
+
+    /// Excluded paths with `~` expanded and made absolute relative to the current directory
+    pub fn excluded_paths(&self, home_dir: Option<&Path>) -> Vec<PathBuf> {
+        self.exclude_paths
+            .iter()
+            .map(|path| {
+                let path = match (path.strip_prefix("~"), home_dir) {
+                    (Ok(rest), Some(home_dir)) => home_dir.join(rest),
+                    _ => path.clone(),
+                };
+                std::path::absolute(&path).unwrap_or(path)
+            })
+            .collect()
+    }
 }
 #[derive(Parser, Debug, Default)]
 pub struct AnalyzeArgs {
     #[command(flatten)]
     pub shared: AnalysisSharedArgs,
+    /// Exit with code 3 if the total size of the results exceeds SIZE (e.g. 500MB, 5GB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub fail_over: Option<u64>,
+    /// Aggregate the projects in the output
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub group_by: Option<GroupBy>,
+    /// Order of the results in the tables
+    #[arg(long, value_enum, default_value_t)]
+    pub sort: SortOrder,
+    /// Print how many paths each detector matched, e.g. to find out why nothing was found
+    #[arg(long)]
+    pub explain: bool,
+    /// Print also the total size of the results per language
+    #[arg(long)]
+    pub by_language: bool,
+    /// Show only the N biggest items of each table
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+    /// How sizes are colored; `relative` scales the thresholds with the results
+    #[arg(long, value_enum, default_value_t)]
+    pub color_mode: ColorMode,
+    /// Print all sizes in the tables in UNIT (e.g. `1536.0 MB`) instead of scaling them
+    #[arg(long, value_enum, value_name = "UNIT", ignore_case = true)]
+    pub unit: Option<SizeUnit>,
+    /// Output format; `jsonl` prints one JSON object per line as the results are found
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+    /// Print results as they are found before the final table (hides the progress bar)
+    #[arg(long, visible_alias = "follow")]
+    pub stream: bool,
+    /// Write the results to FILE instead of the standard output
+    #[arg(short = 'o', long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+    /// Print only the total size in bytes, e.g. for comparisons in scripts
+    #[arg(short = 'q', long, conflicts_with_all = ["stream", "format"])]
+    pub quiet: bool,
+    /// Report the real paths of the results instead of the symlinks leading to them
+    #[arg(long)]
+    pub resolve_symlinks: bool,
+    /// Show a command reclaiming the space of each result, e.g. `cargo clean`
+    #[arg(long)]
+    pub with_commands: bool,
+    /// Don't print the tooling table
+    #[arg(long, conflicts_with = "no_projects")]
+    pub no_tooling: bool,
+    /// Don't print the projects table
+    #[arg(long)]
+    pub no_projects: bool,
+    /// Report identical files found in the scanned paths and the space they waste
+    #[arg(long)]
+    pub find_duplicates: bool,
+    /// Smaller files aren't checked by `--find-duplicates`, e.g. 100KB
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, default_value = "1MB", requires = "find_duplicates")]
+    pub duplicates_min_size: u64,
+    /// Also list tooling which is empty or wasn't found, greyed out
+    #[arg(long)]
+    pub all: bool,
+    /// Also report the N largest directories of the scanned paths, whatever they contain
+    #[arg(long, value_name = "N")]
+    pub largest_dirs: Option<usize>,
+    /// Show only results updated on DATE (YYYY-MM-DD) or later; results with unknown last update are left out
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub since: Option<SystemTime>,
 }
 
 #[derive(Parser, Debug)]
 pub struct BrowseArgs {
     #[command(flatten)]
     pub shared: AnalysisSharedArgs,
+    /// Maximum number of items listed per directory in the directory browser (0 for no limit)
+    ///
+    /// Only the biggest items are listed, the rest is summarized in a single row.
+    #[arg(long, value_name = "N", default_value_t = BrowseArgs::DEFAULT_LIMIT_DEPTH_DISPLAY)]
+    pub limit_depth_display: usize,
+}
+
+impl BrowseArgs {
+    const DEFAULT_LIMIT_DEPTH_DISPLAY: usize = 1000;
+}
+
+impl Default for BrowseArgs {
+    fn default() -> Self {
+        Self {
+            shared: AnalysisSharedArgs::default(),
+            limit_depth_display: Self::DEFAULT_LIMIT_DEPTH_DISPLAY,
+        }
+    }
+}
+
+#[derive(Parser, Debug, Default)]
+pub struct GenerateConfigArgs {
+    /// Write the configuration file to FILE instead of selecting one of the default locations [env: DDC_CONFIG]
+    #[arg(short = 'c', long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    /// Print the example configuration to the standard output instead of writing a file
+    #[arg(long)]
+    pub stdout: bool,
+}
+
+#[derive(Parser, Debug, Default)]
+pub struct DoctorArgs {
+    /// Check the following config file instead of autodiscovery process [env: DDC_CONFIG]
+    #[arg(short = 'c', long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    /// Loader used for scanning the disk
+    #[arg(long, value_enum, default_value_t, env = "DDC_LOADER", hide = true)]
+    pub loader: LoaderKind,
+}
+
+#[derive(Parser, Debug, Default)]
+pub struct ConfigCheckArgs {
+    /// Check the following config file instead of autodiscovery process [env: DDC_CONFIG]
+    #[arg(short = 'c', long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
 }
 
 #[derive(Debug, Default)]
 pub struct UiConfig {
     pub show_progress: bool,
+    pub time_format: TimeFormat,
 }
 
 impl From<&CliArgs> for UiConfig {
     fn from(args: &CliArgs) -> Self {
-        let level = LoggingLevel::from(args.verbosity);
+        let level = args.logging_level();
         let show = match args.command {
             Some(Commands::Analyze(ref cmd_args)) => !cmd_args.shared.no_progress,
             Some(Commands::Browse(ref cmd_args)) => !cmd_args.shared.no_progress,
             _ => true,
         };
         let show_progress = match (level, show) {
-            (LoggingLevel::Traces, _) => false,
+            (LoggingLevel::Traces | LoggingLevel::Quiet, _) => false,
             (_, true) => true,
             (_, false) => false,
         };
+        let time_format = match args.command {
+            Some(Commands::Analyze(ref cmd_args)) => cmd_args.shared.time_format,
+            Some(Commands::Browse(ref cmd_args)) => cmd_args.shared.time_format,
+            _ => TimeFormat::default(),
+        };
 
-        Self { show_progress }
+        Self {
+            show_progress,
+            time_format,
+        }
     }
 }
 
+/// Parses a human-readable size like `5GB` or `512MiB` into bytes
+///
+/// Units without `i` are decimal to match the sizes displayed in the output.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("'{value}' is not a valid size"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000_u64.pow(2),
+        "g" | "gb" => 1000_u64.pow(3),
+        "t" | "tb" => 1000_u64.pow(4),
+        "kib" => 1024,
+        "mib" => 1024_u64.pow(2),
+        "gib" => 1024_u64.pow(3),
+        "tib" => 1024_u64.pow(4),
+        other => return Err(format!("Unknown size unit '{other}'")),
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Parses a date like `2024-05-31` into the start of that day in the local time zone
+fn parse_date(value: &str) -> Result<SystemTime, String> {
+    let date = NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("'{value}' is not a valid date (YYYY-MM-DD): {e}"))?;
+    let start = date
+        .and_time(NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| format!("The start of '{value}' doesn't exist in the local time zone"))?;
+
+    Ok(start.into())
+}
+
 #[cfg(test)]
 mod tests {
+    use clap::CommandFactory;
+
     use super::*;
 
     #[test]
@@ -95,6 +405,163 @@ mod tests {
         assert_eq!(UiConfig::from(&args).show_progress, false);
     }
 
+    #[test]
+    fn test_loader_selection() {
+        let args = CliArgs::try_parse_from(["ddc", "analyze"]).unwrap();
+        let Some(Commands::Analyze(cmd_args)) = args.command else {
+            panic!("Analyze command expected");
+        };
+        assert_eq!(cmd_args.shared.loader, LoaderKind::Parallel);
+
+        let args = CliArgs::try_parse_from(["ddc", "browse", "--loader", "base"]).unwrap();
+        let Some(Commands::Browse(cmd_args)) = args.command else {
+            panic!("Browse command expected");
+        };
+        assert_eq!(cmd_args.shared.loader, LoaderKind::Base);
+    }
+
+    #[test]
+    fn test_skip_hidden_resolution() {
+        let args = AnalysisSharedArgs::default();
+        assert!(!args.skip_hidden(false));
+        assert!(args.skip_hidden(true));
+
+        let args = CliArgs::try_parse_from(["ddc", "analyze", "--exclude-hidden"]).unwrap();
+        let Some(Commands::Analyze(cmd_args)) = args.command else {
+            panic!("Analyze command expected");
+        };
+        assert!(cmd_args.shared.skip_hidden(false));
+
+        let args = CliArgs::try_parse_from(["ddc", "browse", "--include-hidden"]).unwrap();
+        let Some(Commands::Browse(cmd_args)) = args.command else {
+            panic!("Browse command expected");
+        };
+        assert!(!cmd_args.shared.skip_hidden(true));
+
+        assert!(
+            CliArgs::try_parse_from(["ddc", "analyze", "--exclude-hidden", "--include-hidden"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_excluded_paths_normalization() {
+        let args = CliArgs::try_parse_from([
+            "ddc",
+            "analyze",
+            "--exclude-path",
+            "~/work",
+            "--exclude-path",
+            "/tmp/old",
+            "--exclude-path",
+            "relative",
+        ])
+        .unwrap();
+        let Some(Commands::Analyze(cmd_args)) = args.command else {
+            panic!("Analyze command expected");
+        };
+
+        let excluded = cmd_args.shared.excluded_paths(Some(Path::new("/home/foo")));
+        assert_eq!(excluded[0], PathBuf::from("/home/foo/work"));
+        assert_eq!(excluded[1], PathBuf::from("/tmp/old"));
+        // Other tests change the current directory, so only check the shape here
+        assert!(excluded[2].ends_with("relative"));
+    }
+
+    #[test]
+    fn test_long_version_contains_build_info() {
+        let version = CliArgs::command().render_long_version();
+        assert!(version.contains(env!("CARGO_PKG_VERSION")));
+        assert!(version.contains("target: "));
+        assert!(version.contains("rustc: "));
+        assert!(version.contains("commit: "));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("5GB"), Ok(5_000_000_000));
+        assert_eq!(parse_size("1.5 mb"), Ok(1_500_000));
+        assert_eq!(parse_size("2GiB"), Ok(2 * 1024 * 1024 * 1024));
+        assert!(parse_size("GB").is_err());
+        assert!(parse_size("5XB").is_err());
+
+        let args = CliArgs::try_parse_from(["ddc", "analyze", "--fail-over", "5GB"]).unwrap();
+        let Some(Commands::Analyze(cmd_args)) = args.command else {
+            panic!("Analyze command expected");
+        };
+        assert_eq!(cmd_args.fail_over, Some(5_000_000_000));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        use chrono::{DateTime, Datelike, Timelike};
+
+        let since: DateTime<Local> = parse_date("2024-05-31").unwrap().into();
+        assert_eq!((since.year(), since.month(), since.day()), (2024, 5, 31));
+        assert_eq!((since.hour(), since.minute(), since.second()), (0, 0, 0));
+        assert_eq!(
+            parse_date(" 2024-06-01 ").unwrap(),
+            parse_date("2024-05-31").unwrap() + std::time::Duration::from_days(1)
+        );
+
+        assert!(parse_date("2024-02-30").is_err());
+        assert!(parse_date("31.5.2024").is_err());
+        assert!(parse_date("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_unit() {
+        let args = CliArgs::try_parse_from(["ddc", "analyze", "--unit", "MB"]).unwrap();
+        let Some(Commands::Analyze(cmd_args)) = args.command else {
+            panic!("Analyze command expected");
+        };
+        assert_eq!(cmd_args.unit, Some(SizeUnit::MB));
+
+        let args = CliArgs::try_parse_from(["ddc", "analyze"]).unwrap();
+        let Some(Commands::Analyze(cmd_args)) = args.command else {
+            panic!("Analyze command expected");
+        };
+        assert_eq!(cmd_args.unit, None);
+        assert!(CliArgs::try_parse_from(["ddc", "analyze", "--unit", "TB"]).is_err());
+    }
+
+    #[test]
+    fn test_quiet_conflicts() {
+        let args = CliArgs::try_parse_from(["ddc", "analyze", "-q"]).unwrap();
+        assert!(args.check_conflicts().is_ok());
+        assert_eq!(args.logging_level(), LoggingLevel::Quiet);
+        assert!(!UiConfig::from(&args).show_progress);
+
+        let args = CliArgs::try_parse_from(["ddc", "-v", "analyze", "--quiet"]).unwrap();
+        assert_eq!(
+            args.check_conflicts().unwrap_err().kind(),
+            ErrorKind::ArgumentConflict
+        );
+
+        let err = CliArgs::try_parse_from(["ddc", "analyze", "-q", "--stream"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_follow_alias() {
+        let args = CliArgs::try_parse_from(["ddc", "analyze", "--follow"]).unwrap();
+        let Some(Commands::Analyze(cmd_args)) = args.command else {
+            panic!("Analyze command expected");
+        };
+        assert!(cmd_args.stream);
+    }
+
+    #[test]
+    fn test_hiding_both_tables_conflicts() {
+        assert!(CliArgs::try_parse_from(["ddc", "analyze", "--no-tooling"]).is_ok());
+        assert!(CliArgs::try_parse_from(["ddc", "analyze", "--no-projects"]).is_ok());
+
+        let err = CliArgs::try_parse_from(["ddc", "analyze", "--no-tooling", "--no-projects"])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
     #[test]
     fn test_ui_config_hides_progress_on_high_log_level() {
         let mut cmd_args = AnalyzeArgs::default();
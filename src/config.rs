@@ -1,17 +1,154 @@
-use std::fs;
 use std::path::{Path, PathBuf};
+use std::{env, fmt, fs};
 
 use serde::Deserialize;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
+
+use crate::discovery::{ExternalDiscoveryDefinition, Language, expand_path};
+use crate::display_tools::Labels;
 
 #[derive(Debug, Deserialize)]
 pub struct PathDefinition {
-    pub path: PathBuf,
+    #[serde(flatten)]
+    pub roots: Roots,
+    /// Languages of the detectors to run in the path; all of them when not provided
+    pub languages: Option<Vec<String>>,
+    /// Maximum depth of the scan below the path; no limit when not provided
+    pub max_depth: Option<usize>,
 }
 
+/// Either a single `path`, or several `paths` sharing the rest of the settings
 #[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Roots {
+    Single { path: PathBuf },
+    Multiple { paths: Vec<PathBuf> },
+}
+
+impl PathDefinition {
+    pub fn roots(&self) -> &[PathBuf] {
+        match &self.roots {
+            Roots::Single { path } => std::slice::from_ref(path),
+            Roots::Multiple { paths } => paths,
+        }
+    }
+
+    /// Parsed language filter of the path; unknown languages are reported and ignored
+    pub fn languages(&self) -> Option<Vec<Language>> {
+        let languages = self.languages.as_ref()?;
+        let roots = self
+            .roots()
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(
+            languages
+                .iter()
+                .filter_map(|name| {
+                    Language::try_from(name)
+                        .inspect_err(|e| warn!("{e} (configured for path {roots})"))
+                        .ok()
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DiscoveryConfig {
+    /// Detectors to run; all of them when not provided
+    pub enabled: Option<Vec<String>>,
+    /// Detectors to skip
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    /// Don't scan hidden files and directories
+    #[serde(default)]
+    pub skip_hidden: bool,
+    /// Scan common project roots (e.g. `~/projects`) in addition to the configured paths
+    #[serde(default)]
+    pub auto_roots: bool,
+}
+
+/// Command run when `ddc` is called without any subcommand
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultCommand {
+    #[default]
+    Analyze,
+    Browse,
+}
+
+impl fmt::Display for DefaultCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefaultCommand::Analyze => write!(f, "analyze"),
+            DefaultCommand::Browse => write!(f, "browse"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GeneralConfig {
+    #[serde(default)]
+    pub default_command: DefaultCommand,
+}
+
+/// Friendly label of the results inside `path`
+#[derive(Debug, Deserialize)]
+pub struct LabelDefinition {
+    pub path: PathBuf,
+    pub label: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
 pub struct Config {
     pub paths: Vec<PathDefinition>,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub general: GeneralConfig,
+    #[serde(default)]
+    pub labels: Vec<LabelDefinition>,
+}
+
+impl Config {
+    /// Discovery definitions of the configured paths; entries with several roots are expanded
+    ///
+    /// Environment variables and the leading `~` in the paths are expanded here, just once.
+    pub fn definitions(&self, home_dir: Option<&Path>) -> Vec<ExternalDiscoveryDefinition> {
+        self.paths
+            .iter()
+            .flat_map(|definition| {
+                let languages = definition.languages();
+                definition
+                    .roots()
+                    .iter()
+                    .map(move |path| ExternalDiscoveryDefinition {
+                        path: expand_path(path, home_dir),
+                        languages: languages.clone(),
+                        max_depth: definition.max_depth,
+                    })
+            })
+            .collect()
+    }
+
+    /// Configured labels with resolved paths; labels which can't be resolved are reported and ignored
+    pub fn labels(&self, home_dir: Option<&Path>) -> Labels {
+        Labels::new(self.labels.iter().filter_map(|definition| {
+            resolve_home_path(home_dir, &definition.path)
+                .inspect_err(|e| warn!("{e} (configured for label '{}')", definition.label))
+                .ok()
+                .map(|path| (path, definition.label.clone()))
+        }))
+    }
+}
+
+/// Only the `[general]` section, so the rest of the file doesn't need to be valid
+#[derive(Debug, Default, Deserialize)]
+struct GeneralSection {
+    #[serde(default)]
+    general: GeneralConfig,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -20,11 +157,22 @@ pub enum ConfigError {
         "Configuration file not found. If this is the first run, call the 'generate-config' command first."
     )]
     ConfigurationFileNotFound,
+    #[error("Requested configuration file '{0}' doesn't exist.")]
+    RequestedConfigurationFileNotFound(PathBuf),
     #[error("Configuration file can't be loaded: {inner}")]
     CantLoadConfigurationFile {
         #[from]
         inner: std::io::Error,
     },
+    #[error("Couldn't identify your home directory. Provide the configuration file with --config.")]
+    UnknownHomeDirectory,
+    #[error(
+        "Path '{}' is relative to the home directory, which couldn't be identified. Use an absolute path.",
+        .0.display()
+    )]
+    HomeRelativePath(PathBuf),
+    #[error("Configured paths don't exist: {}", format_paths(.0))]
+    NonexistentPaths(Vec<PathBuf>),
     #[error("Wrong configuration file format: {inner}")]
     CannotParseConfigurationFile {
         #[from]
@@ -32,14 +180,35 @@ pub enum ConfigError {
     },
 }
 
+/// Environment variable selecting the configuration file when `--config` isn't given
+pub const CONFIG_PATH_ENV: &str = "DDC_CONFIG";
+
+/// Configuration file requested with `--config`, or else with the `DDC_CONFIG` variable
+///
+/// A requested file is the only one used; the candidates are looked up only without it.
+pub fn requested_config_path(cli_path: Option<&Path>) -> Option<PathBuf> {
+    cli_path.map(Path::to_path_buf).or_else(|| {
+        env::var_os(CONFIG_PATH_ENV)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
 pub fn load_config_file(
-    home_dir: &Path,
+    home_dir: Option<&Path>,
     requested_path: Option<&Path>,
 ) -> Result<Config, ConfigError> {
-    let cfg_data = if let Some(path) = requested_path {
+    let cfg_data = if let Some(path) = requested_config_path(requested_path) {
+        if !path.exists() {
+            error!("Requested configuration file not found");
+            Err(ConfigError::RequestedConfigurationFileNotFound(
+                path.to_path_buf(),
+            ))?
+        }
         debug!("Using configuration file: {}", path.display());
-        fs::read_to_string(path)?
+        fs::read_to_string(&path)?
     } else {
+        let home_dir = home_dir.ok_or(ConfigError::UnknownHomeDirectory)?;
         let candidates = get_config_file_candidates(home_dir);
         let Some(cfg_path) = find_config_file(&candidates) else {
             error!("Configuration file not found");
@@ -54,7 +223,97 @@ pub fn load_config_file(
     Ok(config)
 }
 
-fn find_config_file(candidates: &[PathBuf]) -> Option<PathBuf> {
+/// Read just the `[general]` section of the configuration file, before any command runs
+///
+/// Any problem with the file falls back to the defaults; the command reports it later.
+pub fn load_general_config(
+    home_dir: Option<&Path>,
+    requested_path: Option<&Path>,
+) -> GeneralConfig {
+    let path = match requested_config_path(requested_path) {
+        Some(path) => Some(path),
+        None => {
+            home_dir.and_then(|home_dir| find_config_file(&get_config_file_candidates(home_dir)))
+        }
+    };
+    let Some(path) = path else {
+        return GeneralConfig::default();
+    };
+
+    fs::read_to_string(&path)
+        .map_err(|e| debug!("Configuration file can't be read: {e}"))
+        .and_then(|data| {
+            toml::from_str::<GeneralSection>(&data)
+                .map_err(|e| debug!("General configuration can't be parsed: {e}"))
+        })
+        .map(|section| section.general)
+        .unwrap_or_default()
+}
+
+/// Load the configuration file, or use an empty configuration if `allow_missing` is set
+///
+/// Only a missing autodiscovered file is tolerated. Explicitly requested files must exist.
+pub fn load_config_file_or_default(
+    home_dir: Option<&Path>,
+    requested_path: Option<&Path>,
+    allow_missing: bool,
+) -> Result<Config, ConfigError> {
+    match load_config_file(home_dir, requested_path) {
+        Err(ConfigError::ConfigurationFileNotFound) if allow_missing => {
+            debug!("Configuration file not found, using an empty configuration");
+            Ok(Config::default())
+        }
+        result => result,
+    }
+}
+
+/// Check that all configured paths exist
+///
+/// Missing paths are only reported as warnings unless `strict` is requested.
+pub fn check_config_paths(
+    config: &Config,
+    home_dir: Option<&Path>,
+    strict: bool,
+) -> Result<(), ConfigError> {
+    let mut missing = vec![];
+    for root in config.paths.iter().flat_map(PathDefinition::roots) {
+        let path = resolve_home_path(home_dir, root)?;
+        if !path.exists() {
+            missing.push(path);
+        }
+    }
+    missing
+        .iter()
+        .for_each(|p| warn!("Configured path doesn't exist: {}", p.display()));
+
+    if strict && !missing.is_empty() {
+        return Err(ConfigError::NonexistentPaths(missing));
+    }
+
+    Ok(())
+}
+
+/// Resolve a configured path, which is relative to the home directory unless it's absolute
+///
+/// Environment variables and the leading `~` are expanded first.
+pub fn resolve_home_path(home_dir: Option<&Path>, path: &Path) -> Result<PathBuf, ConfigError> {
+    let path = expand_path(path, home_dir);
+    match home_dir {
+        Some(home_dir) => Ok(home_dir.join(path)),
+        None if path.is_absolute() => Ok(path),
+        None => Err(ConfigError::HomeRelativePath(path)),
+    }
+}
+
+fn format_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn find_config_file(candidates: &[PathBuf]) -> Option<PathBuf> {
     for candidate in candidates {
         debug!("Looking for a configuration file: {}", candidate.display());
         if candidate.exists() {
@@ -66,21 +325,51 @@ fn find_config_file(candidates: &[PathBuf]) -> Option<PathBuf> {
 }
 
 pub fn get_config_file_candidates(home_dir: &Path) -> Vec<PathBuf> {
-    vec![
-        home_dir.join(".config").join("ddc.toml"),
+    get_config_file_candidates_inner(home_dir, env::var_os("XDG_CONFIG_HOME").map(PathBuf::from))
+}
+
+fn get_config_file_candidates_inner(
+    home_dir: &Path,
+    xdg_config_home: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let config_home = home_dir.join(".config");
+    // Relative paths are considered invalid by the XDG specification
+    let xdg_config_home = xdg_config_home.filter(|p| p.is_absolute() && *p != config_home);
+
+    let mut candidates = vec![];
+    // The XDG location takes precedence, the default one is still used as a fallback
+    if let Some(xdg_config_home) = xdg_config_home {
+        candidates.extend([
+            xdg_config_home.join("ddc.toml"),
+            xdg_config_home.join("ddc").join("config.toml"),
+        ]);
+    }
+    if cfg!(windows) {
+        let app_data = env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home_dir.join("AppData").join("Roaming"));
+        candidates.push(app_data.join("ddc").join("ddc.toml"));
+    }
+    candidates.extend([
+        config_home.join("ddc.toml"),
         home_dir.join(".ddc.toml"),
         PathBuf::from("ddc.toml"),
-    ]
+        config_home.join("ddc").join("config.toml"),
+    ]);
+
+    candidates
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{CurrentDir, is_child_test, run_with_env};
 
+    #[cfg(not(windows))]
     #[test]
     fn test_get_config_candidates_based_on_home_dir() {
         let home_path = PathBuf::from("/home/foo");
-        let candidates = get_config_file_candidates(&home_path);
+        let candidates = get_config_file_candidates_inner(&home_path, None);
         assert_eq!(
             candidates.get(0),
             Some(&PathBuf::from("/home/foo/.config/ddc.toml"))
@@ -90,14 +379,35 @@ mod tests {
             Some(&PathBuf::from("/home/foo/.ddc.toml"))
         );
         assert_eq!(candidates.get(2), Some(&PathBuf::from("ddc.toml")));
-        assert_eq!(candidates.get(3), None);
+        assert_eq!(
+            candidates.get(3),
+            Some(&PathBuf::from("/home/foo/.config/ddc/config.toml"))
+        );
+        assert_eq!(candidates.get(4), None);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_get_config_candidates_respects_xdg_config_home() {
+        let xdg_dir = PathBuf::from("/xdg/config");
+        let home_path = PathBuf::from("/home/foo");
+
+        let candidates = get_config_file_candidates_inner(&home_path, Some(xdg_dir.clone()));
+        assert_eq!(candidates[0], xdg_dir.join("ddc.toml"));
+        assert_eq!(candidates[1], xdg_dir.join("ddc/config.toml"));
+        assert!(candidates.contains(&home_path.join(".config/ddc.toml")));
+        assert!(candidates.contains(&home_path.join(".config/ddc/config.toml")));
+
+        // Relative paths must be ignored
+        let candidates = get_config_file_candidates_inner(&home_path, Some("relative".into()));
+        assert_eq!(candidates[0], home_path.join(".config/ddc.toml"));
     }
 
     #[test]
     fn test_load_config_file() {
         let tmp = tempfile::tempdir().unwrap();
         let root_dir = tmp.path();
-        std::env::set_current_dir(&root_dir).unwrap();
+        let _cwd = CurrentDir::set(root_dir);
         let cfg_data = r#"
 [[paths]]
 path = "projects/"
@@ -105,7 +415,7 @@ discovery = true
         "#;
         fs::write(&root_dir.join(".ddc.toml"), cfg_data).unwrap();
 
-        let config = load_config_file(root_dir, None).unwrap();
+        let config = load_config_file(Some(root_dir), None).unwrap();
         assert_eq!(config.paths.len(), 1);
     }
 
@@ -113,7 +423,7 @@ discovery = true
     fn test_load_config_file_from_param() {
         let tmp = tempfile::tempdir().unwrap();
         let root_dir = tmp.path();
-        std::env::set_current_dir(&root_dir).unwrap();
+        let _cwd = CurrentDir::set(root_dir);
         let cfg_data = r#"
 [[paths]]
 path = "projects/"
@@ -122,17 +432,312 @@ discovery = true
         fs::write(&root_dir.join("custom.toml"), cfg_data).unwrap();
 
         let config =
-            load_config_file(root_dir, Some(root_dir.join("custom.toml").as_path())).unwrap();
+            load_config_file(Some(root_dir), Some(root_dir.join("custom.toml").as_path())).unwrap();
         assert_eq!(config.paths.len(), 1);
     }
 
+    #[test]
+    fn test_discovery_config() {
+        let config: Config = toml::from_str("[[paths]]\npath = \"projects/\"").unwrap();
+        assert!(config.discovery.enabled.is_none());
+        assert!(config.discovery.disabled.is_empty());
+        assert!(!config.discovery.skip_hidden);
+
+        let config: Config = toml::from_str(
+            r#"
+[[paths]]
+path = "projects/"
+
+[discovery]
+enabled = ["rust", "python_venv"]
+disabled = ["js_npm"]
+skip_hidden = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.discovery.enabled,
+            Some(vec![String::from("rust"), String::from("python_venv")])
+        );
+        assert_eq!(config.discovery.disabled, vec![String::from("js_npm")]);
+        assert!(config.discovery.skip_hidden);
+    }
+
+    #[test]
+    fn test_load_general_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("ddc.toml");
+        let general = |content: &str| {
+            fs::write(&path, content).unwrap();
+            load_general_config(None, Some(&path)).default_command
+        };
+
+        assert_eq!(
+            general("[[paths]]\npath = \"projects/\""),
+            DefaultCommand::Analyze
+        );
+        assert_eq!(
+            general("[general]\ndefault_command = \"browse\""),
+            DefaultCommand::Browse
+        );
+        // Broken paths don't matter for the general section
+        assert_eq!(
+            general("[general]\ndefault_command = \"browse\"\n\n[[paths]]\npaht = 42"),
+            DefaultCommand::Browse
+        );
+        assert_eq!(
+            general("[general]\ndefault_command = \"clean\""),
+            DefaultCommand::Analyze
+        );
+        assert_eq!(
+            load_general_config(None, Some(&tmp.path().join("missing.toml"))).default_command,
+            DefaultCommand::Analyze
+        );
+    }
+
+    #[test]
+    fn test_path_languages() {
+        let config: Config = toml::from_str(
+            r#"
+[[paths]]
+path = "projects/"
+
+[[paths]]
+path = "rust/"
+languages = ["rust", "cobol", "JavaScript"]
+max_depth = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.paths[0].languages(), None);
+        assert_eq!(config.paths[0].max_depth, None);
+        assert_eq!(config.paths[1].max_depth, Some(3));
+        assert_eq!(
+            config.paths[1].languages(),
+            Some(vec![Language::Rust, Language::JS])
+        );
+    }
+
+    #[test]
+    fn test_multiple_roots() {
+        let config: Config = toml::from_str(
+            r#"
+[[paths]]
+path = "projects/"
+
+[[paths]]
+paths = ["rust/", "work/rust/"]
+languages = ["rust"]
+max_depth = 2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.paths[0].roots(), [PathBuf::from("projects/")]);
+        assert_eq!(
+            config.paths[1].roots(),
+            [PathBuf::from("rust/"), PathBuf::from("work/rust/")]
+        );
+
+        let definitions = config
+            .definitions(None)
+            .into_iter()
+            .map(|d| (d.path, d.languages, d.max_depth))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            definitions,
+            vec![
+                (PathBuf::from("projects/"), None, None),
+                (PathBuf::from("rust/"), Some(vec![Language::Rust]), Some(2)),
+                (
+                    PathBuf::from("work/rust/"),
+                    Some(vec![Language::Rust]),
+                    Some(2)
+                ),
+            ]
+        );
+
+        assert!(toml::from_str::<Config>("[[paths]]\nlanguages = [\"rust\"]").is_err());
+
+        // The leading `~` is expanded when the definitions are resolved
+        let config: Config = toml::from_str("[[paths]]\npath = \"~/projects\"").unwrap();
+        let definitions = config.definitions(Some(Path::new("/home/foo")));
+        assert_eq!(definitions[0].path, PathBuf::from("/home/foo/projects"));
+    }
+
+    #[test]
+    fn test_load_config_file_from_env() {
+        if is_child_test() {
+            let config_path = PathBuf::from(env::var_os(CONFIG_PATH_ENV).unwrap());
+            let root_dir = config_path.parent().unwrap();
+            let result = load_config_file(Some(root_dir), None);
+            if config_path.exists() {
+                assert_eq!(result.unwrap().paths[0].roots(), [PathBuf::from("custom/")]);
+                assert_eq!(
+                    load_general_config(Some(root_dir), None).default_command,
+                    DefaultCommand::Browse
+                );
+            } else {
+                assert!(matches!(
+                    result,
+                    Err(ConfigError::RequestedConfigurationFileNotFound(path)) if path == config_path
+                ));
+            }
+            return;
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        let root_dir = tmp.path();
+        fs::write(
+            root_dir.join(".ddc.toml"),
+            "[[paths]]\npath = \"projects/\"",
+        )
+        .unwrap();
+        fs::write(
+            root_dir.join("custom.toml"),
+            "[general]\ndefault_command = \"browse\"\n\n[[paths]]\npath = \"custom/\"",
+        )
+        .unwrap();
+
+        let name = "config::tests::test_load_config_file_from_env";
+        assert!(run_with_env(
+            name,
+            &[(CONFIG_PATH_ENV, root_dir.join("custom.toml"))]
+        ));
+        assert!(run_with_env(
+            name,
+            &[(CONFIG_PATH_ENV, root_dir.join("missing.toml"))]
+        ));
+    }
+
+    #[test]
+    fn test_load_config_file_from_param_doesnt_fall_back() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_dir = tmp.path();
+        fs::write(
+            root_dir.join(".ddc.toml"),
+            "[[paths]]\npath = \"projects/\"",
+        )
+        .unwrap();
+
+        let result = load_config_file(
+            Some(root_dir),
+            Some(root_dir.join("missing.toml").as_path()),
+        );
+        assert!(matches!(
+            result,
+            Err(ConfigError::RequestedConfigurationFileNotFound(path)) if path == root_dir.join("missing.toml")
+        ));
+    }
+
+    #[test]
+    fn test_load_config_file_or_default() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_dir = tmp.path();
+
+        let config = load_config_file_or_default(Some(root_dir), None, true).unwrap();
+        assert!(config.paths.is_empty());
+
+        let result = load_config_file_or_default(Some(root_dir), None, false);
+        assert!(matches!(
+            result,
+            Err(ConfigError::ConfigurationFileNotFound)
+        ));
+
+        let requested = root_dir.join("missing.toml");
+        let result = load_config_file_or_default(Some(root_dir), Some(&requested), true);
+        assert!(matches!(
+            result,
+            Err(ConfigError::RequestedConfigurationFileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_config_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_dir = tmp.path();
+        fs::create_dir_all(root_dir.join("projects")).unwrap();
+        let config: Config = toml::from_str(
+            r#"
+[[paths]]
+path = "projects/"
+
+[[paths]]
+path = "typo/"
+        "#,
+        )
+        .unwrap();
+
+        assert!(check_config_paths(&config, Some(root_dir), false).is_ok());
+        assert!(matches!(
+            check_config_paths(&config, Some(root_dir), true),
+            Err(ConfigError::NonexistentPaths(paths)) if paths == vec![root_dir.join("typo/")]
+        ));
+    }
+
+    #[test]
+    fn test_labels() {
+        let config: Config = toml::from_str(
+            r#"
+[[paths]]
+path = "projects/"
+
+[[labels]]
+path = "projects/old"
+label = "Old hobby project"
+
+[[labels]]
+path = "/srv/work"
+label = "Work"
+            "#,
+        )
+        .unwrap();
+
+        let labels = config.labels(Some(Path::new("/home/foo")));
+        assert_eq!(
+            labels.get(Path::new("/home/foo/projects/old/target")),
+            Some("Old hobby project")
+        );
+        assert_eq!(labels.get(Path::new("/srv/work/app/venv")), Some("Work"));
+        assert_eq!(labels.get(Path::new("/home/foo/projects/new")), None);
+
+        // Relative paths can't be resolved without the home directory
+        let labels = config.labels(None);
+        assert_eq!(labels.get(Path::new("/srv/work/app/venv")), Some("Work"));
+        assert_eq!(labels.get(Path::new("projects/old/target")), None);
+    }
+
+    #[test]
+    fn test_config_without_home_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_dir = tmp.path();
+        fs::create_dir_all(root_dir.join("projects")).unwrap();
+
+        assert!(matches!(
+            load_config_file(None, None),
+            Err(ConfigError::UnknownHomeDirectory)
+        ));
+
+        let absolute: Config = toml::from_str(&format!(
+            "[[paths]]\npath = {:?}",
+            root_dir.join("projects")
+        ))
+        .unwrap();
+        assert!(check_config_paths(&absolute, None, true).is_ok());
+
+        let relative: Config = toml::from_str("[[paths]]\npath = \"projects/\"").unwrap();
+        assert!(matches!(
+            check_config_paths(&relative, None, false),
+            Err(ConfigError::HomeRelativePath(path)) if path == Path::new("projects/")
+        ));
+    }
+
     #[test]
     fn test_load_config_file_detects_config() {
         let tmp = tempfile::tempdir().unwrap();
         let root_dir = tmp.path();
-        std::env::set_current_dir(&root_dir).unwrap();
+        let _cwd = CurrentDir::set(root_dir);
 
-        let result = load_config_file(root_dir, None);
+        let result = load_config_file(Some(root_dir), None);
         assert!(
             matches!(result, Err(ConfigError::ConfigurationFileNotFound)),
             "WARNING: If this test fail, there is a change that the ddc.toml exists in current working directory!"
@@ -143,10 +748,10 @@ discovery = true
     fn test_load_config_file_reads_raw_config() {
         let tmp = tempfile::tempdir().unwrap();
         let root_dir = tmp.path();
-        std::env::set_current_dir(&root_dir).unwrap();
+        let _cwd = CurrentDir::set(root_dir);
         fs::create_dir_all(root_dir.join(".ddc.toml")).unwrap();
 
-        let result = load_config_file(root_dir, None);
+        let result = load_config_file(Some(root_dir), None);
         assert!(matches!(
             result,
             Err(ConfigError::CantLoadConfigurationFile { inner: _ })
@@ -157,10 +762,10 @@ discovery = true
     fn test_load_config_file_parses_config() {
         let tmp = tempfile::tempdir().unwrap();
         let root_dir = tmp.path();
-        std::env::set_current_dir(&root_dir).unwrap();
+        let _cwd = CurrentDir::set(root_dir);
         fs::write(&root_dir.join(".ddc.toml"), "").unwrap();
 
-        let result = load_config_file(root_dir, None);
+        let result = load_config_file(Some(root_dir), None);
         assert!(matches!(
             result,
             Err(ConfigError::CannotParseConfigurationFile { inner: _ })
@@ -0,0 +1,203 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use owo_colors::OwoColorize;
+use tabled::settings::{Panel, Style};
+use tabled::{Table, Tabled};
+
+use crate::cli::ConfigCheckArgs;
+use crate::config::{
+    Config, ConfigError, find_config_file, get_config_file_candidates, load_config_file,
+    requested_config_path, resolve_home_path,
+};
+use crate::discovery::{DETECTOR_NAMES, Language};
+
+/// A configured path as it's going to be scanned
+#[derive(Tabled)]
+struct PathRecord {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Languages")]
+    languages: String,
+    #[tabled(rename = "Max depth")]
+    max_depth: String,
+    #[tabled(rename = "Exists")]
+    exists: String,
+}
+
+pub fn config_check(
+    cmd_args: &ConfigCheckArgs,
+    home_dir: Option<&Path>,
+) -> Result<(), ConfigError> {
+    config_check_inner(&mut io::stdout(), cmd_args, home_dir)
+}
+
+fn config_check_inner<W: Write>(
+    out: &mut W,
+    cmd_args: &ConfigCheckArgs,
+    home_dir: Option<&Path>,
+) -> Result<(), ConfigError> {
+    let config_path = match (requested_config_path(cmd_args.config.as_deref()), home_dir) {
+        (Some(path), _) => path,
+        (None, Some(home_dir)) => find_config_file(&get_config_file_candidates(home_dir))
+            .ok_or(ConfigError::ConfigurationFileNotFound)?,
+        (None, None) => return Err(ConfigError::UnknownHomeDirectory),
+    };
+    let config = load_config_file(home_dir, Some(&config_path))?;
+    writeln!(out, "Configuration file: {}", config_path.display().green())
+        .expect("Failed to write to stdout");
+
+    let mut problems = vec![];
+    let mut records = vec![];
+    for (definition, root) in config.paths.iter().flat_map(|definition| {
+        definition
+            .roots()
+            .iter()
+            .map(move |root| (definition, root))
+    }) {
+        let path = resolve_home_path(home_dir, root)?;
+        let languages = match definition.languages.as_ref() {
+            None => String::from("all"),
+            Some(names) => names
+                .iter()
+                .filter_map(|name| match Language::try_from(name) {
+                    Ok(lang) => Some(lang.name()),
+                    Err(e) => {
+                        problems.push(format!("{e} (configured for path {})", path.display()));
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        if !path.exists() {
+            problems.push(format!("Path {} doesn't exist", path.display()));
+        }
+        records.push(PathRecord {
+            exists: status(path.exists()),
+            path: path.display().to_string(),
+            languages,
+            max_depth: definition
+                .max_depth
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| String::from("-")),
+        });
+    }
+
+    let mut table = Table::new(&records);
+    table.with(Panel::header("Paths"));
+    table.with(Style::empty());
+    writeln!(out, "{table}").expect("Failed to write to stdout");
+
+    let detectors = selected_detectors(&config, &mut problems);
+    writeln!(out, "Detectors: {}", detectors.join(", ")).expect("Failed to write to stdout");
+    writeln!(out, "Skip hidden: {}", config.discovery.skip_hidden)
+        .expect("Failed to write to stdout");
+    writeln!(out, "Auto roots: {}", config.discovery.auto_roots)
+        .expect("Failed to write to stdout");
+    writeln!(out, "Default command: {}", config.general.default_command)
+        .expect("Failed to write to stdout");
+
+    for problem in problems.iter() {
+        writeln!(out, "{} {problem}", status(false)).expect("Failed to write to stdout");
+    }
+
+    Ok(())
+}
+
+/// Detectors enabled by the configuration; unknown names are reported as problems
+fn selected_detectors(config: &Config, problems: &mut Vec<String>) -> Vec<&'static str> {
+    let enabled = config.discovery.enabled.as_deref();
+    let disabled = &config.discovery.disabled;
+    for name in enabled.into_iter().flatten().chain(disabled) {
+        if !DETECTOR_NAMES.contains(&name.as_str()) {
+            problems.push(format!("Unknown detector '{name}'"));
+        }
+    }
+
+    DETECTOR_NAMES
+        .iter()
+        .copied()
+        .filter(|name| enabled.is_none_or(|enabled| enabled.iter().any(|e| e == name)))
+        .filter(|name| !disabled.iter().any(|d| d == name))
+        .collect()
+}
+
+fn status(ok: bool) -> String {
+    if ok {
+        "✔".green().to_string()
+    } else {
+        "✘".red().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn check(root_path: &Path, cfg_data: &str) -> (Result<(), ConfigError>, String) {
+        let config = root_path.join("custom.toml");
+        fs::write(&config, cfg_data).unwrap();
+        let cmd_args = ConfigCheckArgs {
+            config: Some(config),
+        };
+
+        let mut buffer = Vec::new();
+        let result = config_check_inner(&mut buffer, &cmd_args, Some(root_path));
+        (result, String::from_utf8(buffer).unwrap())
+    }
+
+    #[test]
+    fn test_config_check() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects")).unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "projects/"
+languages = ["rust", "cobol"]
+max_depth = 3
+
+[[paths]]
+path = "missing/"
+
+[discovery]
+disabled = ["rust", "fortran"]
+        "#;
+
+        let (result, output) = check(root_path, cfg_data);
+        assert!(result.is_ok());
+
+        let line_with = |needle: &str| {
+            output
+                .lines()
+                .find(|l| l.contains(needle))
+                .unwrap()
+                .to_string()
+        };
+        let projects = line_with(&root_path.join("projects/").display().to_string());
+        assert!(projects.contains("rust"));
+        assert!(projects.contains('3'));
+        assert!(projects.contains("✔"));
+        let missing = line_with(&root_path.join("missing/").display().to_string());
+        assert!(missing.contains("all"));
+        assert!(missing.contains("✘"));
+        assert!(output.contains("Language 'cobol' is not known"));
+        assert!(output.contains("Unknown detector 'fortran'"));
+        assert!(!line_with("Detectors:").contains("rust"));
+        assert!(output.contains("Default command: analyze"));
+    }
+
+    #[test]
+    fn test_config_check_parse_error() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        let (result, _) = check(tmp.path(), "[[paths]]\nlanguages = 1");
+        assert!(matches!(
+            result,
+            Err(ConfigError::CannotParseConfigurationFile { .. })
+        ));
+    }
+}
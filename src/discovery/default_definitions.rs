@@ -1,7 +1,7 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::discovery::Language;
-use crate::discovery::discovery_definitions::DiscoveryDefinition;
+use crate::discovery::discovery_definitions::{CleanCommand, DiscoveryDefinition};
 
 const CARGO_REGISTRY_INFO: &str = r#"It's considered to be safe to delete the whole `.cargo/registry` directory.
 
@@ -18,8 +18,70 @@ Then you can remove unnecessary instalations with: `uv python uninstall VERSION`
 const UV_CACHE_INFO: &str = r#"Use `uv cache clean` to remove all cache entries.
 
 Alternatively you can use `uv cache prune` to remove just outdated records. E.g. entries from previous uv versions."#;
+const CONDA_ENVS_INFO: &str = r#"Every conda environment contains its own Python and packages.
+
+Use `conda env list` to inspect the environments and `conda env remove -n NAME` to remove unused ones.
+`conda clean --all` removes cached packages which aren't used by any environment."#;
 const POETRY_CACHE_INFO: &str =
     r#"Use `poetry cache list` and then `poetry cache clear [--all] CACHE_NAME`"#;
+const PUB_CACHE_INFO: &str = r#"Use `dart pub cache clean` (or `flutter pub cache clean`) to remove all cached packages.
+
+The packages are downloaded again by the next `pub get`."#;
+const CABAL_STORE_INFO: &str = r#"Cabal keeps downloaded packages and the store of built dependencies here.
+
+Packages are rebuilt when needed, so it's safe to remove the `packages` and `store` subdirectories."#;
+const STACK_ROOT_INFO: &str = r#"Stack keeps downloaded GHC versions in `programs` and built snapshots in `snapshots`.
+
+Remove GHC versions and snapshots you don't use anymore. Stack installs them again when needed."#;
+const ZIG_CACHE_INFO: &str = r#"The global Zig cache is shared by all projects and can be safely deleted.
+
+Zig rebuilds the cached artifacts during the next build."#;
+const OPAM_ROOT_INFO: &str = r#"Every opam switch contains its own compiler and packages.
+
+Use `opam switch list` to inspect the switches and `opam switch remove SWITCH` to remove unused ones.
+`opam clean` removes the download caches and build logs."#;
+const JULIA_PACKAGES_INFO: &str = r#"Use `Pkg.gc()` in the Julia REPL to remove package versions which aren't used by any environment."#;
+const JULIA_COMPILED_INFO: &str = r#"Precompiled caches are regenerated when a package is loaded again.
+
+It's safe to delete the directories of Julia versions you don't use anymore."#;
+const NIM_CACHE_INFO: &str = r#"Nim keeps generated C code and object files of compiled programs here.
+
+It's safe to delete the whole directory. Nim regenerates it during the next compilation."#;
+const R_LIBRARY_INFO: &str = r#"R installs packages of the user library here, separately for every R version.
+
+Use `remove.packages()` in R to remove single packages. Libraries of R versions you don't use anymore can be deleted."#;
+const IVY_CACHE_INFO: &str = r#"Ivy keeps downloaded dependencies of sbt projects here.
+
+It's safe to delete the `cache` subdirectory. The dependencies are downloaded again during the next build."#;
+const SBT_ROOT_INFO: &str = r#"sbt keeps its own launcher versions, compiled plugins and the Zinc compiler bridges here.
+
+Remove the `boot` subdirectory to get rid of old sbt versions. sbt downloads what's needed during the next start."#;
+const MAVEN_REPOSITORY_INFO: &str = r#"The local Maven repository is shared by Maven, Gradle builds using `mavenLocal()`, Leiningen and the Clojure CLI.
+
+It's safe to delete the whole directory. The dependencies are downloaded again during the next build."#;
+const ANDROID_AVD_INFO: &str = r#"Every Android virtual device (emulator) keeps its disk images in its own `.avd` directory.
+
+Delete unused devices in the Device Manager of Android Studio, or with `avdmanager delete avd -n <name>`."#;
+const ANDROID_CACHE_INFO: &str = r#"Android tools keep downloaded repository metadata and other caches here.
+
+It's safe to delete the whole directory."#;
+const XCODE_DERIVED_DATA_INFO: &str = r#"Xcode keeps intermediate build files and indexes of all projects here.
+
+It's safe to delete the whole directory when Xcode isn't running. Xcode recreates the data during the next build."#;
+
+/// Common locations of projects, relative to the home directory
+const PROJECT_ROOTS: &[&str] = &["projects", "dev", "src", "Code", "workspace"];
+
+/// Common project roots which exist in the home directory
+///
+/// Used as additional discovery paths when automatic project roots are enabled.
+pub fn auto_project_roots(home: &Path) -> Vec<PathBuf> {
+    PROJECT_ROOTS
+        .iter()
+        .map(|root| home.join(root))
+        .filter(|path| path.is_dir())
+        .collect()
+}
 
 pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
     let mut definitions = vec![
@@ -32,6 +94,7 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "Cargo registry",
             path: ".cargo/registry".into(),
             info: Some(CARGO_REGISTRY_INFO),
+            clean: Some(CleanCommand::RemoveDir),
         },
         // Rustup toolchains directory
         DiscoveryDefinition {
@@ -40,7 +103,98 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "rustup toolchains",
             path: ".rustup".into(),
             info: Some(RUSTUP_TOOLCHAIN_INFO),
+            clean: None,
         },
+        // Julia
+        ////////////////////////////////////////
+        // Julia - installed packages
+        DiscoveryDefinition {
+            lang: Language::Julia,
+            discovery: false,
+            description: "Julia packages",
+            path: ".julia/packages".into(),
+            info: Some(JULIA_PACKAGES_INFO),
+            clean: Some(CleanCommand::Run("julia -e 'using Pkg; Pkg.gc()'")),
+        },
+        // Julia - precompiled packages
+        DiscoveryDefinition {
+            lang: Language::Julia,
+            discovery: false,
+            description: "Julia compiled",
+            path: ".julia/compiled".into(),
+            info: Some(JULIA_COMPILED_INFO),
+            clean: None,
+        },
+        // Scala
+        ////////////////////////////////////////
+        // Ivy - dependency cache used by sbt
+        DiscoveryDefinition {
+            lang: Language::Scala,
+            discovery: false,
+            description: "Ivy cache",
+            path: ".ivy2".into(),
+            info: Some(IVY_CACHE_INFO),
+            clean: None,
+        },
+        // sbt - global directory
+        DiscoveryDefinition {
+            lang: Language::Scala,
+            discovery: false,
+            description: "sbt root",
+            path: ".sbt".into(),
+            info: Some(SBT_ROOT_INFO),
+            clean: None,
+        },
+        // Clojure
+        ////////////////////////////////////////
+        // Maven - local repository, shared with other JVM build tools
+        DiscoveryDefinition {
+            lang: Language::Clojure,
+            discovery: false,
+            description: "Maven repository",
+            path: ".m2/repository".into(),
+            info: Some(MAVEN_REPOSITORY_INFO),
+            clean: Some(CleanCommand::RemoveDir),
+        },
+        // Android
+        ////////////////////////////////////////
+        // Android - virtual devices; each device is reported separately
+        DiscoveryDefinition {
+            lang: Language::Android,
+            discovery: true,
+            description: "Android virtual devices",
+            path: ".android/avd".into(),
+            info: Some(ANDROID_AVD_INFO),
+            clean: None,
+        },
+        // Android - cache
+        DiscoveryDefinition {
+            lang: Language::Android,
+            discovery: false,
+            description: "Android cache",
+            path: ".android/cache".into(),
+            info: Some(ANDROID_CACHE_INFO),
+            clean: Some(CleanCommand::RemoveDir),
+        },
+    ];
+    if cfg!(windows) {
+        definitions.extend(windows_definitions());
+    } else {
+        definitions.extend(unix_definitions());
+    }
+    if cfg!(target_os = "macos") {
+        definitions.extend(macos_definitions());
+    }
+
+    for def in definitions.iter_mut() {
+        def.path = home.join(&def.path)
+    }
+
+    definitions
+}
+
+fn unix_definitions() -> Vec<DiscoveryDefinition> {
+    vec![
         // Python
         ////////////////////////////////////////
         // Poetry on macOS - cache
@@ -50,6 +204,7 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "Poetry cache",
             path: "Library/Caches/pypoetry".into(),
             info: Some(POETRY_CACHE_INFO),
+            clean: Some(CleanCommand::Run("poetry cache clear --all pypi")),
         },
         // Poetry on macOS - default virtualenvs
         DiscoveryDefinition {
@@ -58,6 +213,7 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "Poetry virtualenvs",
             path: "Library/Caches/pypoetry/virtualenvs".into(),
             info: None,
+            clean: None,
         },
         // Poetry on Unix - cache
         DiscoveryDefinition {
@@ -66,6 +222,7 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "Poetry cache",
             path: ".cache/pypoetry".into(),
             info: Some(POETRY_CACHE_INFO),
+            clean: Some(CleanCommand::Run("poetry cache clear --all pypi")),
         },
         // Poetry on Unix - default virtualenvs
         DiscoveryDefinition {
@@ -74,6 +231,58 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "Poetry virtualenvs",
             path: ".cache/pypoetry/virtualenvs".into(),
             info: None,
+            clean: None,
+        },
+        // pipenv - virtualenvs
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: true,
+            description: "pipenv virtualenvs",
+            path: ".local/share/virtualenvs".into(),
+            info: None,
+            clean: None,
+        },
+        // virtualenvwrapper - virtualenvs
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: true,
+            description: "virtualenvwrapper virtualenvs",
+            path: ".virtualenvs".into(),
+            info: None,
+            clean: None,
+        },
+        // conda/mamba - environments of the common installations; each environment is reported separately
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: true,
+            description: "conda environments",
+            path: ".conda/envs".into(),
+            info: Some(CONDA_ENVS_INFO),
+            clean: None,
+        },
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: true,
+            description: "conda environments",
+            path: "miniconda3/envs".into(),
+            info: Some(CONDA_ENVS_INFO),
+            clean: None,
+        },
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: true,
+            description: "conda environments",
+            path: "anaconda3/envs".into(),
+            info: Some(CONDA_ENVS_INFO),
+            clean: None,
+        },
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: true,
+            description: "conda environments",
+            path: "miniforge3/envs".into(),
+            info: Some(CONDA_ENVS_INFO),
+            clean: None,
         },
         // uv on Linux/macOS - cache
         DiscoveryDefinition {
@@ -82,6 +291,7 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "uv cache",
             path: ".cache/uv".into(),
             info: Some(UV_CACHE_INFO),
+            clean: Some(CleanCommand::Run("uv cache clean")),
         },
         // uv alternative Python installation directory
         DiscoveryDefinition {
@@ -90,6 +300,7 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "uv Python downloads",
             path: ".local/share/uv/python".into(),
             info: Some(UV_PYTHON_INSTALLATIONS_INFO),
+            clean: None,
         },
         // JavaScript
         ////////////////////////////////////////
@@ -100,19 +311,247 @@ pub fn default_discovery_definitions(home: &Path) -> Vec<DiscoveryDefinition> {
             description: "NPM cache",
             path: ".npm".into(),
             info: None,
+            clean: Some(CleanCommand::Run("npm cache clean --force")),
         },
-    ];
+        // Dart
+        ////////////////////////////////////////
+        // pub - cache
+        DiscoveryDefinition {
+            lang: Language::Dart,
+            discovery: false,
+            description: "Pub cache",
+            path: ".pub-cache".into(),
+            info: Some(PUB_CACHE_INFO),
+            clean: Some(CleanCommand::Run("dart pub cache clean")),
+        },
+        // Haskell
+        ////////////////////////////////////////
+        // Cabal - packages and store
+        DiscoveryDefinition {
+            lang: Language::Haskell,
+            discovery: false,
+            description: "Cabal store",
+            path: ".cabal".into(),
+            info: Some(CABAL_STORE_INFO),
+            clean: None,
+        },
+        // Stack - root
+        DiscoveryDefinition {
+            lang: Language::Haskell,
+            discovery: false,
+            description: "Stack root",
+            path: ".stack".into(),
+            info: Some(STACK_ROOT_INFO),
+            clean: None,
+        },
+        // Zig
+        ////////////////////////////////////////
+        // Zig - global cache
+        DiscoveryDefinition {
+            lang: Language::Zig,
+            discovery: false,
+            description: "Zig cache",
+            path: ".cache/zig".into(),
+            info: Some(ZIG_CACHE_INFO),
+            clean: Some(CleanCommand::RemoveDir),
+        },
+        // OCaml
+        ////////////////////////////////////////
+        // opam - root with switches
+        DiscoveryDefinition {
+            lang: Language::OCaml,
+            discovery: false,
+            description: "opam root",
+            path: ".opam".into(),
+            info: Some(OPAM_ROOT_INFO),
+            clean: Some(CleanCommand::Run("opam clean")),
+        },
+        // Nim
+        ////////////////////////////////////////
+        // Nim - compilation cache
+        DiscoveryDefinition {
+            lang: Language::Nim,
+            discovery: false,
+            description: "Nim cache",
+            path: ".cache/nim".into(),
+            info: Some(NIM_CACHE_INFO),
+            clean: Some(CleanCommand::RemoveDir),
+        },
+        // R
+        ////////////////////////////////////////
+        // R - user package library on Linux
+        DiscoveryDefinition {
+            lang: Language::R,
+            discovery: false,
+            description: "R user library",
+            path: "R".into(),
+            info: Some(R_LIBRARY_INFO),
+            clean: None,
+        },
+    ]
+}
 
-    for def in definitions.iter_mut() {
-        def.path = home.join(&def.path)
-    }
+fn macos_definitions() -> Vec<DiscoveryDefinition> {
+    vec![
+        // Swift
+        ////////////////////////////////////////
+        // Xcode - DerivedData
+        DiscoveryDefinition {
+            lang: Language::Swift,
+            discovery: false,
+            description: "Xcode DerivedData",
+            path: "Library/Developer/Xcode/DerivedData".into(),
+            info: Some(XCODE_DERIVED_DATA_INFO),
+            clean: Some(CleanCommand::RemoveDir),
+        },
+        // R
+        ////////////////////////////////////////
+        // R - user package library
+        DiscoveryDefinition {
+            lang: Language::R,
+            discovery: false,
+            description: "R user library",
+            path: "Library/R".into(),
+            info: Some(R_LIBRARY_INFO),
+            clean: None,
+        },
+    ]
+}
 
-    definitions
+// Windows tools keep their data in `%LOCALAPPDATA%` and `%APPDATA%`,
+// which default to `AppData\Local` and `AppData\Roaming` in the home directory.
+fn windows_definitions() -> Vec<DiscoveryDefinition> {
+    vec![
+        // Python
+        ////////////////////////////////////////
+        // Poetry - cache
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: false,
+            description: "Poetry cache",
+            path: r"AppData\Local\pypoetry\Cache".into(),
+            info: Some(POETRY_CACHE_INFO),
+            clean: Some(CleanCommand::Run("poetry cache clear --all pypi")),
+        },
+        // Poetry - default virtualenvs
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: true,
+            description: "Poetry virtualenvs",
+            path: r"AppData\Local\pypoetry\Cache\virtualenvs".into(),
+            info: None,
+            clean: None,
+        },
+        // uv - cache
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: false,
+            description: "uv cache",
+            path: r"AppData\Local\uv\cache".into(),
+            info: Some(UV_CACHE_INFO),
+            clean: Some(CleanCommand::Run("uv cache clean")),
+        },
+        // uv - Python installation directory
+        DiscoveryDefinition {
+            lang: Language::Python,
+            discovery: false,
+            description: "uv Python downloads",
+            path: r"AppData\Roaming\uv\python".into(),
+            info: Some(UV_PYTHON_INSTALLATIONS_INFO),
+            clean: None,
+        },
+        // JavaScript
+        ////////////////////////////////////////
+        // npm - cache
+        DiscoveryDefinition {
+            lang: Language::JS,
+            discovery: false,
+            description: "NPM cache",
+            path: r"AppData\Local\npm-cache".into(),
+            info: None,
+            clean: Some(CleanCommand::Run("npm cache clean --force")),
+        },
+        // Dart
+        ////////////////////////////////////////
+        // pub - cache
+        DiscoveryDefinition {
+            lang: Language::Dart,
+            discovery: false,
+            description: "Pub cache",
+            path: r"AppData\Local\Pub\Cache".into(),
+            info: Some(PUB_CACHE_INFO),
+            clean: Some(CleanCommand::Run("dart pub cache clean")),
+        },
+        // Haskell
+        ////////////////////////////////////////
+        // Cabal - packages and store
+        DiscoveryDefinition {
+            lang: Language::Haskell,
+            discovery: false,
+            description: "Cabal store",
+            path: r"AppData\Roaming\cabal".into(),
+            info: Some(CABAL_STORE_INFO),
+            clean: None,
+        },
+        // Stack - root
+        DiscoveryDefinition {
+            lang: Language::Haskell,
+            discovery: false,
+            description: "Stack root",
+            path: r"AppData\Roaming\stack".into(),
+            info: Some(STACK_ROOT_INFO),
+            clean: None,
+        },
+        // Zig
+        ////////////////////////////////////////
+        // Zig - global cache
+        DiscoveryDefinition {
+            lang: Language::Zig,
+            discovery: false,
+            description: "Zig cache",
+            path: r"AppData\Local\zig".into(),
+            info: Some(ZIG_CACHE_INFO),
+            clean: Some(CleanCommand::RemoveDir),
+        },
+        // OCaml
+        ////////////////////////////////////////
+        // opam - root with switches
+        DiscoveryDefinition {
+            lang: Language::OCaml,
+            discovery: false,
+            description: "opam root",
+            path: r"AppData\Local\opam".into(),
+            info: Some(OPAM_ROOT_INFO),
+            clean: Some(CleanCommand::Run("opam clean")),
+        },
+        // Nim
+        ////////////////////////////////////////
+        // Nim - compilation cache
+        DiscoveryDefinition {
+            lang: Language::Nim,
+            discovery: false,
+            description: "Nim cache",
+            path: "nimcache".into(),
+            info: Some(NIM_CACHE_INFO),
+            clean: Some(CleanCommand::RemoveDir),
+        },
+        // R
+        ////////////////////////////////////////
+        // R - user package library
+        DiscoveryDefinition {
+            lang: Language::R,
+            discovery: false,
+            description: "R user library",
+            path: r"AppData\Local\R\win-library".into(),
+            info: Some(R_LIBRARY_INFO),
+            clean: None,
+        },
+    ]
 }
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::fs;
 
     use super::*;
 
@@ -125,4 +564,18 @@ mod tests {
             assert_eq!(def.path.starts_with("/home/foo"), true);
         }
     }
+
+    #[test]
+    fn test_auto_project_roots_only_existing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home_path = tmp.path();
+        fs::create_dir_all(home_path.join("projects")).unwrap();
+        fs::create_dir_all(home_path.join("Code")).unwrap();
+        fs::write(home_path.join("dev"), "Not a directory").unwrap();
+
+        assert_eq!(
+            auto_project_roots(home_path),
+            vec![home_path.join("projects"), home_path.join("Code")]
+        );
+    }
 }
@@ -1,17 +1,40 @@
+use std::marker::PhantomData;
 use std::path::Path;
 
 use crate::discovery::Language;
 use crate::discovery::discovery_manager::DynamicDetector;
 use crate::files_db::FilesDB;
 
+/// Names of all dynamic detectors which can be used in the configuration
+pub const DETECTOR_NAMES: &[&str] = &[
+    PythonVenvDetector::NAME,
+    RustBuildDirDetector::NAME,
+    JsNpmDetector::NAME,
+    FlutterBuildDetector::NAME,
+    HaskellBuildDetector::NAME,
+    ZigCacheDetector::NAME,
+    OcamlBuildDetector::NAME,
+    SwiftBuildDetector::NAME,
+    FrontendBuildDetector::NAME,
+    RenvDetector::NAME,
+    CrystalLibDetector::NAME,
+    SbtTargetDetector::NAME,
+    ClojureDetector::NAME,
+    AndroidAvdDetector::NAME,
+    GameEngineDetector::NAME,
+    CondaEnvDetector::NAME,
+];
+
 #[derive(Default)]
 pub struct PythonVenvDetector;
 
 impl DynamicDetector for PythonVenvDetector {
     const LANG: Language = Language::Python;
+    const NAME: &'static str = "python_venv";
 
     fn detect(&self, db: &FilesDB, path: &Path) -> bool {
-        db.exists(&path.join("bin/python"))
+        // Conda environments are reported by their own detector
+        db.exists(&path.join("bin/python")) && !CondaEnvDetector::default().detect(db, path)
     }
 }
 
@@ -20,10 +43,13 @@ pub struct RustBuildDirDetector;
 
 impl DynamicDetector for RustBuildDirDetector {
     const LANG: Language = Language::Rust;
+    const NAME: &'static str = "rust";
 
     fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        // sbt projects use `target` as well and are reported by their own detector
         path.ends_with("target")
             && (db.is_dir(&path.join("debug/build")) || db.is_dir(&path.join("release/build")))
+            && !SbtTargetDetector.detect(db, path)
     }
 }
 
@@ -32,6 +58,7 @@ pub struct JsNpmDetector;
 
 impl DynamicDetector for JsNpmDetector {
     const LANG: Language = Language::JS;
+    const NAME: &'static str = "js_npm";
 
     fn detect(&self, db: &FilesDB, path: &Path) -> bool {
         if path.ends_with("node_modules") && db.is_dir(&path.join(".bin")) {
@@ -46,12 +73,274 @@ impl DynamicDetector for JsNpmDetector {
     }
 }
 
+#[derive(Default)]
+pub struct FlutterBuildDetector;
+
+impl DynamicDetector for FlutterBuildDetector {
+    const LANG: Language = Language::Dart;
+    const NAME: &'static str = "flutter";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        (path.ends_with(".dart_tool") || path.ends_with("build"))
+            && path
+                .parent()
+                .is_some_and(|parent| db.exists(&parent.join("pubspec.yaml")))
+    }
+}
+
+#[derive(Default)]
+pub struct HaskellBuildDetector;
+
+impl DynamicDetector for HaskellBuildDetector {
+    const LANG: Language = Language::Haskell;
+    const NAME: &'static str = "haskell";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        if !(path.ends_with("dist-newstyle") || path.ends_with(".stack-work")) {
+            return false;
+        }
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+        let parent = parent.to_path_buf();
+
+        db.exists(&parent.join("stack.yaml"))
+            || db
+                .iter_level(&parent)
+                .any(|fi| fi.path.extension().is_some_and(|ext| ext == "cabal"))
+    }
+}
+
+#[derive(Default)]
+pub struct ZigCacheDetector;
+
+impl DynamicDetector for ZigCacheDetector {
+    const LANG: Language = Language::Zig;
+    const NAME: &'static str = "zig";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        // Zig 0.13 renamed `zig-cache` to `.zig-cache`
+        (path.ends_with(".zig-cache") || path.ends_with("zig-cache"))
+            && path
+                .parent()
+                .is_some_and(|parent| db.exists(&parent.join("build.zig")))
+    }
+}
+
+#[derive(Default)]
+pub struct OcamlBuildDetector;
+
+impl DynamicDetector for OcamlBuildDetector {
+    const LANG: Language = Language::OCaml;
+    const NAME: &'static str = "ocaml";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        path.ends_with("_build")
+            && path
+                .parent()
+                .is_some_and(|parent| db.exists(&parent.join("dune-project")))
+    }
+}
+
+#[derive(Default)]
+pub struct SwiftBuildDetector;
+
+impl DynamicDetector for SwiftBuildDetector {
+    const LANG: Language = Language::Swift;
+    const NAME: &'static str = "swift";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        path.ends_with(".build")
+            && path
+                .parent()
+                .is_some_and(|parent| db.exists(&parent.join("Package.swift")))
+    }
+}
+
+/// Build outputs and caches of frontend frameworks and bundlers
+const FRONTEND_BUILD_DIRS: &[&str] = &[".next", ".nuxt", ".svelte-kit", ".turbo", "dist"];
+
+#[derive(Default)]
+pub struct FrontendBuildDetector;
+
+impl DynamicDetector for FrontendBuildDetector {
+    const LANG: Language = Language::JS;
+    const NAME: &'static str = "frontend";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        if !FRONTEND_BUILD_DIRS.iter().any(|dir| path.ends_with(dir)) {
+            return false;
+        }
+        // Installed packages ship their own `dist` next to `package.json`
+        if path.components().any(|c| c.as_os_str() == "node_modules") {
+            return false;
+        }
+        // `dist` is too generic, so only directories right below the package root count
+        path.parent()
+            .is_some_and(|parent| db.exists(&parent.join("package.json")))
+    }
+}
+
+#[derive(Default)]
+pub struct RenvDetector;
+
+impl DynamicDetector for RenvDetector {
+    const LANG: Language = Language::R;
+    const NAME: &'static str = "renv";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        path.ends_with("renv/library")
+            && path
+                .parent()
+                .and_then(Path::parent)
+                .is_some_and(|project| db.exists(&project.join("renv.lock")))
+    }
+}
+
+#[derive(Default)]
+pub struct CrystalLibDetector;
+
+impl DynamicDetector for CrystalLibDetector {
+    const LANG: Language = Language::Crystal;
+    const NAME: &'static str = "crystal";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        // `lib` is a very common name; only shards installs it next to `shard.yml`
+        path.ends_with("lib")
+            && path
+                .parent()
+                .is_some_and(|parent| db.exists(&parent.join("shard.yml")))
+    }
+}
+
+#[derive(Default)]
+pub struct SbtTargetDetector;
+
+impl DynamicDetector for SbtTargetDetector {
+    const LANG: Language = Language::Scala;
+    const NAME: &'static str = "scala";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        path.ends_with("target")
+            && path
+                .parent()
+                .is_some_and(|parent| db.exists(&parent.join("build.sbt")))
+    }
+}
+
+#[derive(Default)]
+pub struct ClojureDetector;
+
+impl DynamicDetector for ClojureDetector {
+    const LANG: Language = Language::Clojure;
+    const NAME: &'static str = "clojure";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+        if path.ends_with(".cpcache") {
+            return db.exists(&parent.join("deps.edn"));
+        }
+        // Both the Clojure CLI and Leiningen build into `target`
+        path.ends_with("target")
+            && (db.exists(&parent.join("deps.edn")) || db.exists(&parent.join("project.clj")))
+    }
+}
+
+#[derive(Default)]
+pub struct AndroidAvdDetector;
+
+impl DynamicDetector for AndroidAvdDetector {
+    const LANG: Language = Language::Android;
+    const NAME: &'static str = "android_avd";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        // Every virtual device lives in its own `<name>.avd` directory
+        path.extension().is_some_and(|ext| ext == "avd") && db.exists(&path.join("config.ini"))
+    }
+}
+
+/// Generated directories of Unity projects, next to `Assets` and `ProjectSettings`
+const UNITY_GENERATED_DIRS: &[&str] = &["Library", "Temp", "Obj"];
+/// Generated directories of Unreal projects, next to the `*.uproject` file
+///
+/// `Saved` is left out on purpose, as it holds also configs and autosaves.
+const UNREAL_GENERATED_DIRS: &[&str] = &["Intermediate", "DerivedDataCache"];
+
+#[derive(Default)]
+pub struct GameEngineDetector;
+
+impl DynamicDetector for GameEngineDetector {
+    const LANG: Language = Language::GameDev;
+    const NAME: &'static str = "game_engine";
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+        // Names like `Library` or `Temp` are far too common, so the project markers must be present
+        if UNITY_GENERATED_DIRS.iter().any(|dir| path.ends_with(dir)) {
+            return db.is_dir(&parent.join("Assets")) && db.is_dir(&parent.join("ProjectSettings"));
+        }
+        if UNREAL_GENERATED_DIRS.iter().any(|dir| path.ends_with(dir)) {
+            let parent = parent.to_path_buf();
+            return db
+                .iter_level(&parent)
+                .any(|fi| !fi.is_dir && fi.path.extension().is_some_and(|ext| ext == "uproject"));
+        }
+        false
+    }
+}
+
+/// Directory whose immediate children are independent units, e.g. environments or toolchains
+pub trait ChildrenContainer: Send + Sync + 'static {
+    const LANG: Language;
+    const NAME: &'static str;
+    /// Name of the directory holding the units
+    const CONTAINER: &'static str;
+    /// Path which must exist inside a child for it to be reported
+    const MARKER: &'static str;
+}
+
+/// Reports every immediate child of a [`ChildrenContainer`] directory separately
+pub struct ImmediateChildrenDetector<C>(PhantomData<C>);
+
+impl<C> Default for ImmediateChildrenDetector<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: ChildrenContainer> DynamicDetector for ImmediateChildrenDetector<C> {
+    const LANG: Language = C::LANG;
+    const NAME: &'static str = C::NAME;
+
+    fn detect(&self, db: &FilesDB, path: &Path) -> bool {
+        path.parent()
+            .is_some_and(|parent| parent.ends_with(C::CONTAINER))
+            && db.exists(&path.join(C::MARKER))
+    }
+}
+
+/// Conda (and mamba) environments in `<installation>/envs`
+pub struct CondaEnvs;
+
+impl ChildrenContainer for CondaEnvs {
+    const LANG: Language = Language::Python;
+    const NAME: &'static str = "conda_env";
+    const CONTAINER: &'static str = "envs";
+    const MARKER: &'static str = "conda-meta";
+}
+
+pub type CondaEnvDetector = ImmediateChildrenDetector<CondaEnvs>;
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
     use super::*;
-    use crate::file_info::FileMeta;
+    use crate::file_info::{FileMeta, Permissions};
 
     fn add_record(db: &mut FilesDB, path: &str) {
         db.add(
@@ -59,7 +348,10 @@ mod tests {
             FileMeta {
                 is_dir: true,
                 size: None,
+                allocated: None,
                 touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
             },
         );
     }
@@ -96,6 +388,118 @@ mod tests {
             &mut db,
             "projects/node/node_modules/.bin/foo/node_modules/.bin",
         );
+        add_record(&mut db, "projects/flutter");
+        add_record(&mut db, "projects/flutter/pubspec.yaml");
+        add_record(&mut db, "projects/flutter/.dart_tool");
+        add_record(&mut db, "projects/flutter/build");
+        add_record(&mut db, "projects/generic");
+        add_record(&mut db, "projects/generic/build");
+        add_record(&mut db, "projects/cabal");
+        add_record(&mut db, "projects/cabal/dist-newstyle");
+        add_record(&mut db, "projects/cabal/foo.cabal");
+        add_record(&mut db, "projects/stack");
+        add_record(&mut db, "projects/stack/.stack-work");
+        add_record(&mut db, "projects/stack/stack.yaml");
+        add_record(&mut db, "projects/stack/dist-newstyle");
+        add_record(&mut db, "projects/no_haskell");
+        add_record(&mut db, "projects/no_haskell/dist-newstyle");
+        add_record(&mut db, "projects/no_haskell/nested");
+        add_record(&mut db, "projects/no_haskell/nested/foo.cabal");
+        add_record(&mut db, "projects/zig");
+        add_record(&mut db, "projects/zig/build.zig");
+        add_record(&mut db, "projects/zig/.zig-cache");
+        add_record(&mut db, "projects/zig_old");
+        add_record(&mut db, "projects/zig_old/build.zig");
+        add_record(&mut db, "projects/zig_old/zig-cache");
+        add_record(&mut db, "projects/no_zig");
+        add_record(&mut db, "projects/no_zig/zig-cache");
+        add_record(&mut db, "projects/ocaml");
+        add_record(&mut db, "projects/ocaml/dune-project");
+        add_record(&mut db, "projects/ocaml/_build");
+        add_record(&mut db, "projects/ocaml/_build/default");
+        add_record(&mut db, "projects/no_ocaml");
+        add_record(&mut db, "projects/no_ocaml/_build");
+        add_record(&mut db, "projects/swift");
+        add_record(&mut db, "projects/swift/Package.swift");
+        add_record(&mut db, "projects/swift/.build");
+        add_record(&mut db, "projects/no_swift");
+        add_record(&mut db, "projects/no_swift/.build");
+        add_record(&mut db, "projects/frontend");
+        add_record(&mut db, "projects/frontend/package.json");
+        add_record(&mut db, "projects/frontend/.next");
+        add_record(&mut db, "projects/frontend/dist");
+        add_record(&mut db, "projects/frontend/dist/assets");
+        add_record(&mut db, "projects/frontend/dist/assets/dist");
+        add_record(&mut db, "projects/frontend/node_modules/foo");
+        add_record(&mut db, "projects/frontend/node_modules/foo/package.json");
+        add_record(&mut db, "projects/frontend/node_modules/foo/dist");
+        add_record(&mut db, "projects/no_frontend");
+        add_record(&mut db, "projects/no_frontend/dist");
+        add_record(&mut db, "projects/r");
+        add_record(&mut db, "projects/r/renv.lock");
+        add_record(&mut db, "projects/r/renv");
+        add_record(&mut db, "projects/r/renv/library");
+        add_record(&mut db, "projects/no_r");
+        add_record(&mut db, "projects/no_r/renv");
+        add_record(&mut db, "projects/no_r/renv/library");
+        add_record(&mut db, "projects/crystal");
+        add_record(&mut db, "projects/crystal/shard.yml");
+        add_record(&mut db, "projects/crystal/lib");
+        add_record(&mut db, "projects/c");
+        add_record(&mut db, "projects/c/Makefile");
+        add_record(&mut db, "projects/c/lib");
+        add_record(&mut db, "projects/scala");
+        add_record(&mut db, "projects/scala/build.sbt");
+        add_record(&mut db, "projects/scala/target");
+        add_record(&mut db, "projects/scala/target/debug");
+        add_record(&mut db, "projects/scala/target/debug/build");
+        add_record(&mut db, "projects/scala/project/target");
+        add_record(&mut db, "projects/clojure");
+        add_record(&mut db, "projects/clojure/deps.edn");
+        add_record(&mut db, "projects/clojure/.cpcache");
+        add_record(&mut db, "projects/clojure/target");
+        add_record(&mut db, "projects/lein");
+        add_record(&mut db, "projects/lein/project.clj");
+        add_record(&mut db, "projects/lein/target");
+        add_record(&mut db, "projects/lein/.cpcache");
+        add_record(&mut db, ".android/avd");
+        add_record(&mut db, ".android/avd/Pixel_8.avd");
+        add_record(&mut db, ".android/avd/Pixel_8.avd/config.ini");
+        add_record(&mut db, ".android/avd/Pixel_8.ini");
+        add_record(&mut db, ".android/avd/broken.avd");
+        add_record(&mut db, "projects/unity");
+        add_record(&mut db, "projects/unity/Assets");
+        add_record(&mut db, "projects/unity/Library");
+        add_record(&mut db, "projects/unity/ProjectSettings");
+        add_record(&mut db, "projects/unity/Temp");
+        add_record(&mut db, "projects/unity_no_settings");
+        add_record(&mut db, "projects/unity_no_settings/Assets");
+        add_record(&mut db, "projects/unity_no_settings/Library");
+        db.add(
+            PathBuf::from("projects/unreal/Shooter.uproject"),
+            FileMeta {
+                is_dir: false,
+                size: Some(1),
+                allocated: None,
+                touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
+            },
+        );
+        add_record(&mut db, "projects/unreal/Intermediate");
+        add_record(&mut db, "projects/unreal/Saved");
+        add_record(&mut db, "projects/unreal_no_project");
+        add_record(&mut db, "projects/unreal_no_project/Intermediate");
+        add_record(&mut db, "projects/unreal_no_project/Nested.uproject");
+        add_record(&mut db, "projects/unreal_no_project/Nested.uproject/file");
+        add_record(&mut db, "miniconda3/conda-meta");
+        add_record(&mut db, "miniconda3/bin/python");
+        add_record(&mut db, "miniconda3/envs/ml/conda-meta");
+        add_record(&mut db, "miniconda3/envs/ml/bin/python");
+        add_record(&mut db, "miniconda3/envs/web/conda-meta");
+        add_record(&mut db, "miniconda3/envs/web/bin/python");
+        add_record(&mut db, "miniconda3/envs/not_an_env/bin/python");
+        add_record(&mut db, ".conda/envs/data/conda-meta");
 
         db
     }
@@ -112,6 +516,8 @@ mod tests {
             detector.detect(&db, &PathBuf::from("projects/python/wrong_venv")),
             false
         );
+        assert!(detector.detect(&db, &PathBuf::from("miniconda3/envs/not_an_env")));
+        assert!(!detector.detect(&db, &PathBuf::from("miniconda3/envs/ml")));
     }
 
     #[test]
@@ -186,4 +592,157 @@ mod tests {
             false
         );
     }
+
+    #[test]
+    fn test_flutter_detector() {
+        let db = get_virtual_layout();
+        let detector = FlutterBuildDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/flutter/.dart_tool")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/flutter/build")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/generic/build")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/flutter")));
+    }
+
+    #[test]
+    fn test_haskell_detector() {
+        let db = get_virtual_layout();
+        let detector = HaskellBuildDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/cabal/dist-newstyle")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/stack/.stack-work")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/stack/dist-newstyle")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/no_haskell/dist-newstyle")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/cabal")));
+    }
+
+    #[test]
+    fn test_zig_detector() {
+        let db = get_virtual_layout();
+        let detector = ZigCacheDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/zig/.zig-cache")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/zig_old/zig-cache")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/no_zig/zig-cache")));
+    }
+
+    #[test]
+    fn test_ocaml_detector() {
+        let db = get_virtual_layout();
+        let detector = OcamlBuildDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/ocaml/_build")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/ocaml/_build/default")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/no_ocaml/_build")));
+    }
+
+    #[test]
+    fn test_swift_detector() {
+        let db = get_virtual_layout();
+        let detector = SwiftBuildDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/swift/.build")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/no_swift/.build")));
+    }
+
+    #[test]
+    fn test_frontend_detector() {
+        let db = get_virtual_layout();
+        let detector = FrontendBuildDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/frontend/.next")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/frontend/dist")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/frontend/dist/assets/dist")));
+        assert!(!detector.detect(
+            &db,
+            &PathBuf::from("projects/frontend/node_modules/foo/dist")
+        ));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/no_frontend/dist")));
+    }
+
+    #[test]
+    fn test_renv_detector() {
+        let db = get_virtual_layout();
+        let detector = RenvDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/r/renv/library")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/r/renv")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/no_r/renv/library")));
+    }
+
+    #[test]
+    fn test_crystal_lib_detector() {
+        let db = get_virtual_layout();
+        let detector = CrystalLibDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/crystal/lib")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/c/lib")));
+    }
+
+    #[test]
+    fn test_sbt_target_detector() {
+        let db = get_virtual_layout();
+        let detector = SbtTargetDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/scala/target")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/scala/project/target")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/rust/target")));
+        // The same directory isn't reported as Rust build too
+        assert!(!RustBuildDirDetector.detect(&db, &PathBuf::from("projects/scala/target")));
+    }
+
+    #[test]
+    fn test_clojure_detector() {
+        let db = get_virtual_layout();
+        let detector = ClojureDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/clojure/.cpcache")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/clojure/target")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/lein/target")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/lein/.cpcache")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/scala/target")));
+    }
+
+    #[test]
+    fn test_android_avd_detector() {
+        let db = get_virtual_layout();
+        let detector = AndroidAvdDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from(".android/avd/Pixel_8.avd")));
+        assert!(!detector.detect(&db, &PathBuf::from(".android/avd/Pixel_8.ini")));
+        assert!(!detector.detect(&db, &PathBuf::from(".android/avd/broken.avd")));
+        assert!(!detector.detect(&db, &PathBuf::from(".android/avd")));
+    }
+
+    #[test]
+    fn test_game_engine_detector() {
+        let db = get_virtual_layout();
+        let detector = GameEngineDetector;
+
+        assert!(detector.detect(&db, &PathBuf::from("projects/unity/Library")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/unity/Temp")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/unity/Assets")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/unity_no_settings/Library")));
+        assert!(detector.detect(&db, &PathBuf::from("projects/unreal/Intermediate")));
+        assert!(!detector.detect(&db, &PathBuf::from("projects/unreal/Saved")));
+        // A directory named like a project file doesn't count
+        assert!(!detector.detect(
+            &db,
+            &PathBuf::from("projects/unreal_no_project/Intermediate")
+        ));
+    }
+
+    #[test]
+    fn test_conda_env_detector() {
+        let db = get_virtual_layout();
+        let detector = CondaEnvDetector::default();
+
+        assert!(detector.detect(&db, &PathBuf::from("miniconda3/envs/ml")));
+        assert!(detector.detect(&db, &PathBuf::from("miniconda3/envs/web")));
+        assert!(detector.detect(&db, &PathBuf::from(".conda/envs/data")));
+        assert!(!detector.detect(&db, &PathBuf::from("miniconda3")));
+        assert!(!detector.detect(&db, &PathBuf::from("miniconda3/envs")));
+        assert!(!detector.detect(&db, &PathBuf::from("miniconda3/envs/not_an_env")));
+        assert!(!detector.detect(&db, &PathBuf::from("miniconda3/envs/ml/conda-meta")));
+    }
 }
@@ -1,4 +1,8 @@
-use std::path::PathBuf;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
 
 use crate::discovery::Language;
 
@@ -15,9 +19,121 @@ pub struct DiscoveryDefinition {
     pub description: &'static str,
     pub lang: Language,
     pub info: Option<&'static str>,
+    pub clean: Option<CleanCommand>,
+}
+
+/// How the content of a tool's location can be cleaned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanCommand {
+    /// The tool's own command, e.g. `uv cache clean`
+    Run(&'static str),
+    /// The whole directory can be removed
+    RemoveDir,
 }
 
 #[derive(Debug)]
 pub struct ExternalDiscoveryDefinition {
     pub path: PathBuf,
+    /// Run only detectors of these languages in the path; all of them when not provided
+    pub languages: Option<Vec<Language>>,
+    /// Don't descend deeper than this below the path; no limit when not provided
+    pub max_depth: Option<usize>,
+}
+
+/// Expand the leading `~` and environment variables (`$NAME` or `${NAME}`) in the path
+///
+/// Unset variables, and `~` when the home directory is unknown, are kept as they are.
+pub fn expand_path(path: &Path, home: Option<&Path>) -> PathBuf {
+    expand_path_inner(path, home, |name| env::var_os(name))
+}
+
+fn expand_path_inner<F>(path: &Path, home: Option<&Path>, lookup: F) -> PathBuf
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    let Some(raw) = path.to_str() else {
+        return path.to_path_buf();
+    };
+
+    let mut expanded = OsString::new();
+    let mut rest = raw;
+    if let Some(home) = home
+        && let Some(after) = rest.strip_prefix('~')
+        && (after.is_empty() || after.starts_with('/'))
+    {
+        expanded.push(home);
+        rest = after;
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remainder) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", after),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+
+        match (name.is_empty(), lookup(name)) {
+            (false, Some(value)) => expanded.push(value),
+            (false, None) => {
+                warn!("Environment variable '{name}' in path {raw} is not set");
+                expanded.push(&rest[start..rest.len() - remainder.len()]);
+            }
+            (true, _) => expanded.push("$"),
+        }
+        rest = if name.is_empty() { after } else { remainder };
+    }
+    expanded.push(rest);
+
+    PathBuf::from(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<OsString> {
+        match name {
+            "WORKSPACE" => Some(OsString::from("/mnt/work")),
+            "USER" => Some(OsString::from("foo")),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let home = Some(Path::new("/home/foo"));
+        for (path, expected) in [
+            ("projects/", "projects/"),
+            ("/opt/builds", "/opt/builds"),
+            ("~", "/home/foo"),
+            ("~/projects", "/home/foo/projects"),
+            ("~foo/projects", "~foo/projects"),
+            ("$WORKSPACE/builds", "/mnt/work/builds"),
+            ("${WORKSPACE}/builds", "/mnt/work/builds"),
+            ("builds/$USER-cache", "builds/foo-cache"),
+            ("builds/${USER}cache", "builds/foocache"),
+            ("$MISSING/builds", "$MISSING/builds"),
+            ("${MISSING}/builds", "${MISSING}/builds"),
+            ("price$/${unclosed", "price$/${unclosed"),
+        ] {
+            assert_eq!(
+                expand_path_inner(Path::new(path), home, lookup),
+                PathBuf::from(expected),
+                "{path}"
+            );
+        }
+        assert_eq!(
+            expand_path_inner(Path::new("~/projects"), None, lookup),
+            PathBuf::from("~/projects")
+        );
+    }
 }
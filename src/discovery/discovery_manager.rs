@@ -1,33 +1,102 @@
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 use crossbeam::channel;
 use crossbeam::channel::{Receiver, Sender};
-use tracing::{debug_span, instrument, warn};
+use tracing::{debug, debug_span, instrument, warn};
 
 use crate::discovery::ExternalDiscoveryDefinition;
-use crate::discovery::Language;
-use crate::discovery::default_definitions::default_discovery_definitions;
-use crate::discovery::detectors::{JsNpmDetector, PythonVenvDetector, RustBuildDirDetector};
+use crate::discovery::default_definitions::{auto_project_roots, default_discovery_definitions};
+use crate::discovery::detectors::{
+    AndroidAvdDetector, ClojureDetector, CondaEnvDetector, CrystalLibDetector, DETECTOR_NAMES,
+    FlutterBuildDetector, FrontendBuildDetector, GameEngineDetector, HaskellBuildDetector,
+    JsNpmDetector, OcamlBuildDetector, PythonVenvDetector, RenvDetector, RustBuildDirDetector,
+    SbtTargetDetector, SwiftBuildDetector, ZigCacheDetector,
+};
 use crate::discovery::discovery_definitions::DiscoveryDefinitionType;
 use crate::discovery::progress::{ProgressEvent, ProgressReporter};
 use crate::discovery::results::{
     DiscoveryResultEnvelop, DiscoveryResults, ParentInfo, ProjectResult, ToolingResult, VcsResult,
 };
+use crate::discovery::{Language, VcsKind};
+use crate::file_info::{SizeMode, TimeSource};
 use crate::files_db::FilesDB;
+use crate::git_size::{objects_size, reclaimable_size};
 use crate::loader::FullyParallelLoader;
 
+/// Options affecting how loaders walk the scanned paths
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WalkOptions {
+    /// Don't descend into hidden files and directories (names starting with a dot)
+    pub skip_hidden: bool,
+    /// Measure time spent in the loading phases and print a summary at the end
+    pub profile: bool,
+    /// Don't cross into other file systems mounted below the scanned paths (Unix only)
+    pub one_file_system: bool,
+}
+
+/// Options affecting how the found results are measured
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MeasureOptions {
+    /// Timestamp used for the last update of the results
+    pub time_source: TimeSource,
+    /// Count only files ignored by git in the size of git repositories
+    pub git_aware: bool,
+    /// Ignore timestamps of directories for the last update, see [`FilesDB::last_update`]
+    pub files_only_update: bool,
+}
+
+impl MeasureOptions {
+    fn last_update(&self, db: &FilesDB, path: &Path) -> Option<SystemTime> {
+        db.last_update(path, self.time_source, self.files_only_update)
+    }
+}
+
+/// Number of matched paths of each dynamic detector, collected from the detector threads
+type DetectorMatches = Arc<Mutex<Vec<(&'static str, usize)>>>;
+
+/// Starts a detector thread sending its results to the given channel
+type StartDetector = Box<dyn FnOnce(Sender<DiscoveryResultEnvelop>)>;
+
+/// Detectors ready to be started once the directories they check are counted
+struct PendingDetectors {
+    matches: DetectorMatches,
+    /// Directories walked from each definition
+    dir_counts: Vec<usize>,
+    /// Directories checked by each detector, with a function starting it
+    detectors: Vec<(usize, StartDetector)>,
+}
+
+/// Checked directories are reported in batches, as there are a lot of them
+const ADVANCE_BATCH: usize = 1000;
+
+/// Projects, tools, VCS repositories, and matches per detector found by `discover`
+type Discovered = (
+    Vec<ProjectResult>,
+    Vec<ToolingResult>,
+    Vec<VcsResult>,
+    Vec<(&'static str, usize)>,
+    bool,
+);
+
+/// Path to scan with the maximum depth of the walk; `None` means no limit
+pub type ScanPath = (PathBuf, Option<usize>);
+
 pub trait PathLoader: Default {
     fn load_multiple_paths<R: ProgressReporter>(
         &self,
-        scan_paths: &[PathBuf],
+        scan_paths: &[ScanPath],
+        options: WalkOptions,
         progress: Option<R>,
     ) -> FilesDB;
 }
 
 pub trait DynamicDetector: Default + Send + Sync + 'static {
     const LANG: Language;
+    /// Name used to enable or disable the detector in the configuration
+    const NAME: &'static str;
     fn detect(&self, db: &FilesDB, path: &Path) -> bool;
 }
 
@@ -49,36 +118,55 @@ impl ProgressReporter for ChannelProgressReporter {
 }
 
 pub struct DiscoveryManager<L: PathLoader = FullyParallelLoader> {
-    home: PathBuf,
+    home: Option<PathBuf>,
     loader: L,
     db: Arc<FilesDB>,
     definitions: Arc<Vec<DiscoveryDefinitionType>>,
     progress_tx: Sender<ProgressEvent>,
     progress_rx: Receiver<ProgressEvent>,
+    enabled_detectors: Vec<&'static str>,
+    measure_options: MeasureOptions,
+    walk_options: WalkOptions,
+    max_results: Option<usize>,
+    keep_empty_tools: bool,
 }
 
 impl DiscoveryManager {
+    #[cfg(test)]
     pub fn new(home: &Path) -> Self {
-        Self::with_loader(Default::default(), home)
+        Self::with_loader(Default::default(), Some(home))
     }
 }
 
 impl<L: PathLoader> DiscoveryManager<L> {
-    pub fn with_loader(loader: L, home: &Path) -> Self {
+    /// Without the home directory, only the added definitions are used and they must be absolute
+    pub fn with_loader(loader: L, home: Option<&Path>) -> Self {
         let (progress_tx, progress_rx) = channel::bounded(100);
+        let definitions = match home {
+            Some(home) => default_discovery_definitions(home),
+            None => {
+                warn!("Home directory is unknown. Default tooling locations won't be checked.");
+                vec![]
+            }
+        };
 
         Self {
-            home: home.to_path_buf(),
+            home: home.map(Path::to_path_buf),
             loader,
             db: Arc::new(FilesDB::new()),
             definitions: Arc::new(
-                default_discovery_definitions(home)
+                definitions
                     .into_iter()
                     .map(DiscoveryDefinitionType::BuildIn)
                     .collect(),
             ),
             progress_tx,
             progress_rx,
+            enabled_detectors: DETECTOR_NAMES.to_vec(),
+            measure_options: MeasureOptions::default(),
+            walk_options: WalkOptions::default(),
+            max_results: None,
+            keep_empty_tools: false,
         }
     }
 
@@ -86,8 +174,14 @@ impl<L: PathLoader> DiscoveryManager<L> {
         let new_definitions = definitions
             .iter()
             .map(|ed| {
+                // Absolute paths are kept as they are by `join`
                 DiscoveryDefinitionType::External(ExternalDiscoveryDefinition {
-                    path: self.home.join(&ed.path),
+                    path: match &self.home {
+                        Some(home) => home.join(&ed.path),
+                        None => ed.path.clone(),
+                    },
+                    languages: ed.languages.clone(),
+                    max_depth: ed.max_depth,
                 })
             })
             .collect::<Vec<_>>();
@@ -101,6 +195,121 @@ impl<L: PathLoader> DiscoveryManager<L> {
         self
     }
 
+    /// Add existing common project roots (e.g. `~/projects`) as discovery paths
+    ///
+    /// Roots overlapping any of the already added paths are skipped, so nothing is scanned twice.
+    pub fn add_auto_roots(self) -> Self {
+        let Some(home) = self.home.as_deref() else {
+            warn!("Home directory is unknown. Automatic project roots are skipped.");
+            return self;
+        };
+        let roots = auto_project_roots(home)
+            .into_iter()
+            .filter(|root| {
+                !self.definitions.iter().any(|def| {
+                    let path = match def {
+                        DiscoveryDefinitionType::BuildIn(dd) => &dd.path,
+                        DiscoveryDefinitionType::External(ed) => &ed.path,
+                    };
+                    path.starts_with(root) || root.starts_with(path)
+                })
+            })
+            .inspect(|root| debug!("Adding automatic project root: {}", root.display()))
+            .map(|path| ExternalDiscoveryDefinition {
+                path,
+                languages: None,
+                max_depth: None,
+            })
+            .collect::<Vec<_>>();
+
+        self.add_definitions(&roots)
+    }
+
+    /// Select dynamic detectors to run
+    ///
+    /// All detectors are enabled unless `enabled` is provided. Detectors listed in `disabled`
+    /// are then removed from the selection. Unknown names are reported and ignored.
+    pub fn select_detectors(mut self, enabled: Option<&[String]>, disabled: &[String]) -> Self {
+        for name in enabled.into_iter().flatten().chain(disabled) {
+            if !DETECTOR_NAMES.contains(&name.as_str()) {
+                warn!(
+                    "Unknown detector '{name}' in configuration. Known detectors: {}",
+                    DETECTOR_NAMES.join(", ")
+                );
+            }
+        }
+
+        self.enabled_detectors = DETECTOR_NAMES
+            .iter()
+            .copied()
+            .filter(|name| enabled.is_none_or(|enabled| enabled.iter().any(|e| e == name)))
+            .filter(|name| !disabled.iter().any(|d| d == name))
+            .collect();
+        self
+    }
+
+    /// Select which timestamp is used for the last update of the results
+    pub fn use_time_source(mut self, time_source: TimeSource) -> Self {
+        self.measure_options.time_source = time_source;
+        self
+    }
+
+    /// Compute the last update of the results from the timestamps of files only
+    ///
+    /// Directories get a new timestamp whenever anything is added to them or removed
+    /// from them, which makes results look recently used even when no content changed.
+    pub fn files_only_update(mut self, files_only_update: bool) -> Self {
+        self.measure_options.files_only_update = files_only_update;
+        self
+    }
+
+    /// Measure only the files ignored by git in project roots and repositories
+    ///
+    /// Sizes of paths outside git repositories, or without git installed, are not affected.
+    pub fn git_aware(mut self, git_aware: bool) -> Self {
+        self.measure_options.git_aware = git_aware;
+        self
+    }
+
+    /// Skip hidden files and directories while loading the paths
+    ///
+    /// Note that build directories of some detectors (e.g. `.next`, `.zig-cache`) are hidden
+    /// and won't be found inside the configured paths then.
+    pub fn skip_hidden(mut self, skip_hidden: bool) -> Self {
+        self.walk_options.skip_hidden = skip_hidden;
+        self
+    }
+
+    /// Stay on the file system of each scanned path, like `du -x`
+    pub fn one_file_system(mut self, one_file_system: bool) -> Self {
+        if one_file_system && cfg!(not(unix)) {
+            warn!("Staying on one file system is supported only on Unix. The option is ignored.");
+        }
+        self.walk_options.one_file_system = one_file_system;
+        self
+    }
+
+    /// Print a breakdown of the time spent by walking directories and loading metadata
+    pub fn profile(mut self, profile: bool) -> Self {
+        self.walk_options.profile = profile;
+        self
+    }
+
+    /// Stop collecting results after `max_results`; the results are marked as truncated then
+    ///
+    /// Protects against misconfigured scans, e.g. of the whole disk, producing an unusable
+    /// wall of output.
+    pub fn max_results(mut self, max_results: Option<usize>) -> Self {
+        self.max_results = max_results;
+        self
+    }
+
+    /// Report also tooling which is empty or wasn't found, to confirm where it's looked for
+    pub fn keep_empty_tools(mut self, keep_empty_tools: bool) -> Self {
+        self.keep_empty_tools = keep_empty_tools;
+        self
+    }
+
     pub fn subscribe(&self) -> Receiver<ProgressEvent> {
         self.progress_rx.clone()
     }
@@ -109,9 +318,20 @@ impl<L: PathLoader> DiscoveryManager<L> {
         ChannelProgressReporter::new(self.progress_tx.clone())
     }
 
-    pub fn collect(mut self) -> DiscoveryResults {
-        self.load_paths();
-        let (projects, tools, vcs) = self.discover();
+    pub fn collect(self) -> DiscoveryResults {
+        self.collect_with(|_| {})
+    }
+
+    /// Collects the results and passes each of them to `on_result` as soon as it's found
+    ///
+    /// Results are stored as `on_result` leaves them, so it can also adjust them.
+    pub fn collect_with<F>(mut self, on_result: F) -> DiscoveryResults
+    where
+        F: FnMut(&mut DiscoveryResultEnvelop),
+    {
+        let scan_paths = self.scan_paths();
+        self.load_paths(&scan_paths);
+        let (projects, tools, vcs, detector_matches, truncated) = self.discover(on_result);
         drop(self.progress_tx);
 
         DiscoveryResults {
@@ -119,35 +339,87 @@ impl<L: PathLoader> DiscoveryManager<L> {
             tools,
             vcs,
             db: Arc::into_inner(self.db),
+            detector_matches,
+            truncated,
+            scan_roots: scan_paths.into_iter().map(|(path, _)| path).collect(),
         }
     }
 
-    #[instrument(level = "debug", skip(self))]
-    fn load_paths(&mut self) {
-        let paths = self
-            .definitions
+    fn scan_paths(&self) -> Vec<ScanPath> {
+        self.definitions
             .iter()
             .map(|def| match def {
-                DiscoveryDefinitionType::BuildIn(dd) => dd.path.clone(),
-                DiscoveryDefinitionType::External(ed) => ed.path.clone(),
+                DiscoveryDefinitionType::BuildIn(dd) => (dd.path.clone(), None),
+                DiscoveryDefinitionType::External(ed) => (ed.path.clone(), ed.max_depth),
             })
-            .collect::<Vec<_>>();
-        let reporter = self.create_reporter();
-        self.db = Arc::new(self.loader.load_multiple_paths(&paths, Some(reporter)));
+            .collect()
     }
 
     #[instrument(level = "debug", skip(self))]
-    fn discover(&mut self) -> (Vec<ProjectResult>, Vec<ToolingResult>, Vec<VcsResult>) {
+    fn load_paths(&mut self, paths: &[ScanPath]) {
+        let reporter = self.create_reporter();
+        self.db = Arc::new(self.loader.load_multiple_paths(
+            paths,
+            self.walk_options,
+            Some(reporter),
+        ));
+    }
+
+    #[instrument(level = "debug", skip(self, on_result))]
+    fn discover<F>(&mut self, mut on_result: F) -> Discovered
+    where
+        F: FnMut(&mut DiscoveryResultEnvelop),
+    {
         let reporter = self.create_reporter();
-        reporter.report(ProgressEvent::DiscoveryStart { count: 5 });
 
         let mut project_results = vec![];
         let mut tooling_results = vec![];
         let mut vcs_results = vec![];
         let (tx, rx) = channel::unbounded();
+        let matches = DetectorMatches::default();
+        // Detectors start only after the total is reported, so no progress is lost
+        let mut pending = PendingDetectors {
+            matches: matches.clone(),
+            dir_counts: self.count_directories(),
+            detectors: vec![],
+        };
+        self.prepare_detector(RustBuildDirDetector, &mut pending);
+        self.prepare_detector(PythonVenvDetector, &mut pending);
+        self.prepare_detector(JsNpmDetector, &mut pending);
+        self.prepare_detector(FlutterBuildDetector, &mut pending);
+        self.prepare_detector(HaskellBuildDetector, &mut pending);
+        self.prepare_detector(ZigCacheDetector, &mut pending);
+        self.prepare_detector(OcamlBuildDetector, &mut pending);
+        self.prepare_detector(SwiftBuildDetector, &mut pending);
+        self.prepare_detector(FrontendBuildDetector, &mut pending);
+        self.prepare_detector(RenvDetector, &mut pending);
+        self.prepare_detector(CrystalLibDetector, &mut pending);
+        self.prepare_detector(SbtTargetDetector, &mut pending);
+        self.prepare_detector(ClojureDetector, &mut pending);
+        self.prepare_detector(AndroidAvdDetector, &mut pending);
+        self.prepare_detector(GameEngineDetector, &mut pending);
+        self.prepare_detector(CondaEnvDetector::default(), &mut pending);
+
+        // Each directory is checked by every detector walking it, the VCS detector
+        // included, and each static definition is measured once
+        let static_count = self
+            .definitions
+            .iter()
+            .filter(|def| detection_path(def, None).is_none())
+            .count();
+        reporter.report(ProgressEvent::DiscoveryStart {
+            count: static_count
+                + candidates(&self.definitions, &pending.dir_counts, None)
+                + pending
+                    .detectors
+                    .iter()
+                    .map(|(count, _)| count)
+                    .sum::<usize>(),
+        });
         spawn_special_detector_thread(
             self.db.clone(),
             self.definitions.clone(),
+            self.measure_options,
             static_detector,
             tx.clone(),
             self.create_reporter(),
@@ -155,210 +427,418 @@ impl<L: PathLoader> DiscoveryManager<L> {
         spawn_special_detector_thread(
             self.db.clone(),
             self.definitions.clone(),
+            self.measure_options,
             vcs_detector,
             tx.clone(),
             self.create_reporter(),
         );
-        spawn_discovery_thread(
-            self.db.clone(),
-            self.definitions.clone(),
-            RustBuildDirDetector,
-            tx.clone(),
-            self.create_reporter(),
-        );
-        spawn_discovery_thread(
-            self.db.clone(),
-            self.definitions.clone(),
-            PythonVenvDetector,
-            tx.clone(),
-            self.create_reporter(),
-        );
-        spawn_discovery_thread(
-            self.db.clone(),
-            self.definitions.clone(),
-            JsNpmDetector,
-            tx.clone(),
-            self.create_reporter(),
-        );
+        pending
+            .detectors
+            .into_iter()
+            .for_each(|(_, spawn)| spawn(tx.clone()));
         drop(tx);
 
-        for res in rx.iter() {
+        let mut collected = 0;
+        let mut truncated = false;
+        for mut res in rx.iter() {
+            if !self.keep_empty_tools
+                && matches!(&res, DiscoveryResultEnvelop::Tool(r) if r.size == 0)
+            {
+                continue;
+            }
+            // The channel is still drained, so the detectors can finish
+            if self.max_results.is_some_and(|max| collected >= max) {
+                truncated = true;
+                continue;
+            }
+            collected += 1;
+            on_result(&mut res);
             match res {
                 DiscoveryResultEnvelop::Project(r) => project_results.push(r),
-                DiscoveryResultEnvelop::Tool(r) => {
-                    if r.size > 0 {
-                        tooling_results.push(r)
-                    }
-                }
+                DiscoveryResultEnvelop::Tool(r) => tooling_results.push(r),
                 DiscoveryResultEnvelop::Vcs(vcs) => vcs_results.push(vcs),
             }
         }
 
         reporter.report(ProgressEvent::DiscoveryFinished);
 
-        (project_results, tooling_results, vcs_results)
+        // All detector threads record their matches before the results channel closes
+        let mut detector_matches = std::mem::take(&mut *matches.lock().unwrap());
+        detector_matches.sort_by_key(|(name, _)| DETECTOR_NAMES.iter().position(|n| n == name));
+        debug!(
+            "Matches per detector: {}",
+            detector_matches
+                .iter()
+                .map(|(name, count)| format!("{name}: {count}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        (
+            project_results,
+            tooling_results,
+            vcs_results,
+            detector_matches,
+            truncated,
+        )
+    }
+
+    /// Number of directories walked from each definition, counted once for all detectors
+    ///
+    /// It's a single walk of the tree, while the detection walks it once per detector.
+    fn count_directories(&self) -> Vec<usize> {
+        self.definitions
+            .iter()
+            .map(|def| {
+                detection_path(def, None)
+                    .map(|path| self.db.iter_directories(path).count())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    fn prepare_detector<D: DynamicDetector>(&self, detector: D, pending: &mut PendingDetectors) {
+        if !self.enabled_detectors.contains(&D::NAME) {
+            debug!("Detector '{}' is disabled", D::NAME);
+            return;
+        }
+        let count = candidates(&self.definitions, &pending.dir_counts, Some(D::LANG));
+        let db = self.db.clone();
+        let definitions = self.definitions.clone();
+        let options = self.measure_options;
+        let progress = self.create_reporter();
+        let matches = pending.matches.clone();
+        pending.detectors.push((
+            count,
+            Box::new(move |tx| {
+                spawn_discovery_thread(db, definitions, options, detector, tx, progress, matches)
+            }),
+        ));
     }
 }
 
 fn spawn_special_detector_thread<D, R>(
     db: Arc<FilesDB>,
     definitions: Arc<Vec<DiscoveryDefinitionType>>,
+    options: MeasureOptions,
     detector: D,
     tx: Sender<DiscoveryResultEnvelop>,
     progress: R,
 ) where
-    D: FnOnce(Arc<FilesDB>, Arc<Vec<DiscoveryDefinitionType>>, Sender<DiscoveryResultEnvelop>, R)
-        + Send
+    D: FnOnce(
+            Arc<FilesDB>,
+            Arc<Vec<DiscoveryDefinitionType>>,
+            MeasureOptions,
+            Sender<DiscoveryResultEnvelop>,
+            R,
+        ) + Send
         + 'static,
     R: ProgressReporter,
 {
     rayon::spawn(move || {
-        detector(db, definitions, tx, progress);
+        detector(db, definitions, options, tx, progress);
     });
 }
 
 fn spawn_discovery_thread<D, R>(
     db: Arc<FilesDB>,
     definitions: Arc<Vec<DiscoveryDefinitionType>>,
+    options: MeasureOptions,
     detector: D,
     tx: Sender<DiscoveryResultEnvelop>,
     progress: R,
+    matches: DetectorMatches,
 ) where
     D: DynamicDetector,
     R: ProgressReporter,
 {
     rayon::spawn(move || {
         let _guard = debug_span!("discovery_thread", lang = ?D::LANG).entered();
-        discovery_thread(db, definitions, detector, tx, progress);
+        let count = discovery_thread(db, definitions, options, detector, &tx, progress);
+        matches.lock().unwrap().push((D::NAME, count));
+        // Keep the sender until the count is recorded, so `discover` sees all of them
+        drop(tx);
     });
 }
 
 fn static_detector<R>(
     db: Arc<FilesDB>,
     definitions: Arc<Vec<DiscoveryDefinitionType>>,
+    options: MeasureOptions,
     tx: Sender<DiscoveryResultEnvelop>,
     progress: R,
 ) where
     R: ProgressReporter,
 {
     let _guard = debug_span!("static_thread").entered();
+    let mut advance = BatchedAdvance::new(progress);
     for definition in definitions.iter() {
         if let DiscoveryDefinitionType::BuildIn(dd) = definition
             && !dd.discovery
         {
-            let size = db.iter_dir(&dd.path).filter_map(|fi| fi.size).sum();
-            let last_update = db.iter_dir(&dd.path).filter_map(|fi| fi.touched).max();
+            let size = subtree_size(&db, &dd.path, SizeMode::Apparent);
+            let allocated_size = subtree_size(&db, &dd.path, SizeMode::Allocated);
+            let last_update = options.last_update(&db, &dd.path);
             let r = DiscoveryResultEnvelop::Tool(ToolingResult {
                 description: dd.description,
                 lang: dd.lang,
                 path: dd.path.clone(),
                 last_update,
                 size,
+                allocated_size,
                 info: dd.info,
+                clean: dd.clean,
             });
             tx.send(r).unwrap();
+            advance.add();
         }
     }
-    progress.report(ProgressEvent::DiscoveryAdvance)
 }
 
 fn vcs_detector<R>(
     db: Arc<FilesDB>,
     definitions: Arc<Vec<DiscoveryDefinitionType>>,
+    options: MeasureOptions,
     tx: Sender<DiscoveryResultEnvelop>,
     progress: R,
 ) where
     R: ProgressReporter,
 {
     let _guard = debug_span!("vcs_thread").entered();
+    let mut advance = BatchedAdvance::new(progress);
     for definition in definitions.iter() {
-        if let DiscoveryDefinitionType::BuildIn(dd) = definition
-            && !dd.discovery
-        {
+        let Some(path_to_detect) = detection_path(definition, None) else {
             continue;
-        }
-        let path_to_detect = match definition {
-            DiscoveryDefinitionType::BuildIn(dd) => &dd.path,
-            DiscoveryDefinitionType::External(ed) => &ed.path,
         };
-        let detected_paths: Vec<PathBuf> = db
+        let detected_paths: Vec<(PathBuf, VcsKind)> = db
             .iter_directories(path_to_detect)
-            .filter(|fi| fi.path.ends_with(".git") && db.is_dir(fi.path))
-            .filter_map(|fi| fi.path.parent().map(PathBuf::from))
+            .inspect(|_| advance.add())
+            .filter(|fi| db.is_dir(&fi.path))
+            .filter_map(|fi| {
+                let kind = VcsKind::ALL
+                    .into_iter()
+                    .find(|kind| fi.path.ends_with(kind.dir_name()))?;
+                let repo = fi.path.parent()?;
+                // Subversion before 1.7 kept `.svn` in every directory of the working copy
+                if kind == VcsKind::Subversion
+                    && repo
+                        .parent()
+                        .is_some_and(|p| db.is_dir(&p.join(kind.dir_name())))
+                {
+                    return None;
+                }
+                Some((repo.to_path_buf(), kind))
+            })
             .collect();
-        detected_paths.iter().for_each(|p| {
-            let size = db.iter_dir(p).filter_map(|fi| fi.size).sum();
-            let last_update = db.iter_dir(p).filter_map(|fi| fi.touched).max();
-            let vcs_size = db
-                .iter_dir(&p.join(PathBuf::from(".git")))
-                .filter_map(|fi| fi.size)
-                .sum();
+        detected_paths.iter().for_each(|(p, kind)| {
+            let (size, allocated_size) = measure_root(&db, p, options);
+            let last_update = options.last_update(&db, p);
+            let vcs_size = match kind {
+                VcsKind::Git => objects_size(p).map(|size| size.total()),
+                _ => None,
+            }
+            .unwrap_or_else(|| {
+                db.iter_dir(&p.join(kind.dir_name()))
+                    .filter_map(|fi| fi.size)
+                    .sum()
+            });
             let r = DiscoveryResultEnvelop::Vcs(VcsResult {
-                path: (*p).clone(),
+                path: p.clone(),
+                kind: *kind,
                 last_update,
                 size,
+                allocated_size,
                 vcs_size,
             });
             tx.send(r).unwrap();
         });
     }
-    progress.report(ProgressEvent::DiscoveryAdvance)
 }
 
 fn discovery_thread<D, R>(
     db: Arc<FilesDB>,
     discovery_definitions: Arc<Vec<DiscoveryDefinitionType>>,
+    options: MeasureOptions,
     detector: D,
-    tx: Sender<DiscoveryResultEnvelop>,
+    tx: &Sender<DiscoveryResultEnvelop>,
     progress: R,
-) where
+) -> usize
+where
     D: DynamicDetector,
     R: ProgressReporter,
 {
+    let mut count = 0;
+    let mut advance = BatchedAdvance::new(progress);
     for definition in discovery_definitions.iter() {
-        if let DiscoveryDefinitionType::BuildIn(dd) = definition
-            && !dd.discovery
-        {
+        let Some(path_to_detect) = detection_path(definition, Some(D::LANG)) else {
             continue;
-        }
-        let path_to_detect = match definition {
-            DiscoveryDefinitionType::BuildIn(dd) => &dd.path,
-            DiscoveryDefinitionType::External(ed) => &ed.path,
         };
-        let detected_paths: Vec<&PathBuf> = db
+        let detected_paths: Vec<PathBuf> = db
             .iter_directories(path_to_detect)
-            .filter(|fi| detector.detect(db.deref(), fi.path))
+            .inspect(|_| advance.add())
+            .filter(|fi| detector.detect(db.deref(), &fi.path))
             .map(|fi| fi.path)
             .collect();
+        count += detected_paths.len();
         detected_paths.iter().for_each(|p| {
-            let size = db.iter_dir(p).filter_map(|fi| fi.size).sum();
-            let last_update = db.iter_dir(p).filter_map(|fi| fi.touched).max();
+            let size = subtree_size(&db, p, SizeMode::Apparent);
+            let allocated_size = subtree_size(&db, p, SizeMode::Allocated);
+            let last_update = options.last_update(&db, p);
             let parent = p.parent().map(|p| p.to_path_buf()).filter(|p| db.exists(p));
             let r = DiscoveryResultEnvelop::Project(ProjectResult {
                 lang: D::LANG,
-                path: (*p).clone(),
+                path: p.clone(),
                 last_update,
                 size,
-                parent: parent.map(|parent_path| ParentInfo {
-                    size: db.iter_dir(&parent_path).filter_map(|fi| fi.size).sum(),
-                    path: parent_path,
+                allocated_size,
+                parent: parent.map(|parent_path| {
+                    let (size, allocated_size) = measure_root(&db, &parent_path, options);
+                    ParentInfo {
+                        path: parent_path,
+                        size,
+                        allocated_size,
+                    }
                 }),
+                vcs_size: None,
+                orphaned: false,
+                root: deepest_root(&discovery_definitions, p),
             });
             tx.send(r).unwrap();
         });
     }
-    progress.report(ProgressEvent::DiscoveryAdvance);
+    count
+}
+
+/// Path walked by detectors of `lang` for `definition`, or by the VCS detector without it
+///
+/// Static definitions aren't walked, they're only measured.
+fn detection_path(
+    definition: &DiscoveryDefinitionType,
+    lang: Option<Language>,
+) -> Option<&PathBuf> {
+    match definition {
+        DiscoveryDefinitionType::BuildIn(dd) => dd.discovery.then_some(&dd.path),
+        DiscoveryDefinitionType::External(ed) => {
+            let skipped = lang.is_some_and(|lang| {
+                ed.languages
+                    .as_ref()
+                    .is_some_and(|languages| !languages.contains(&lang))
+            });
+            (!skipped).then_some(&ed.path)
+        }
+    }
+}
+
+/// Number of directories checked by a detector of `lang`
+fn candidates(
+    definitions: &[DiscoveryDefinitionType],
+    dir_counts: &[usize],
+    lang: Option<Language>,
+) -> usize {
+    definitions
+        .iter()
+        .zip(dir_counts)
+        .filter(|(def, _)| detection_path(def, lang).is_some())
+        .map(|(_, count)| count)
+        .sum()
+}
+
+/// Reports checked directories by `ADVANCE_BATCH`, and the rest when dropped
+struct BatchedAdvance<R: ProgressReporter> {
+    progress: R,
+    pending: usize,
+}
+
+impl<R: ProgressReporter> BatchedAdvance<R> {
+    fn new(progress: R) -> Self {
+        Self {
+            progress,
+            pending: 0,
+        }
+    }
+
+    fn add(&mut self) {
+        self.pending += 1;
+        if self.pending == ADVANCE_BATCH {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.pending > 0 {
+            self.progress.report(ProgressEvent::DiscoveryAdvance {
+                count: std::mem::take(&mut self.pending),
+            });
+        }
+    }
+}
+
+impl<R: ProgressReporter> Drop for BatchedAdvance<R> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Path of the innermost definition containing `path`
+///
+/// With nested definitions, the same directory is walked from each of them, so the
+/// project is attributed to the closest one regardless of which walk found it.
+fn deepest_root(definitions: &[DiscoveryDefinitionType], path: &Path) -> Option<PathBuf> {
+    definitions
+        .iter()
+        .map(|definition| match definition {
+            DiscoveryDefinitionType::BuildIn(dd) => &dd.path,
+            DiscoveryDefinitionType::External(ed) => &ed.path,
+        })
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .cloned()
+}
+
+/// Sum of sizes of the complete content of `path`
+fn subtree_size(db: &FilesDB, path: &Path, mode: SizeMode) -> u64 {
+    db.iter_dir(path).filter_map(|fi| fi.size_in(mode)).sum()
+}
+
+/// Apparent and allocated size of a project root or a repository, limited to the files
+/// ignored by git when requested
+fn measure_root(db: &FilesDB, path: &Path, options: MeasureOptions) -> (u64, u64) {
+    options
+        .git_aware
+        .then(|| reclaimable_size(db, path))
+        .flatten()
+        .unwrap_or_else(|| {
+            (
+                subtree_size(db, path, SizeMode::Apparent),
+                subtree_size(db, path, SizeMode::Allocated),
+            )
+        })
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs;
     use std::string::String;
+    use std::time::Duration;
 
     use tempfile::tempdir;
 
     use super::*;
 
+    /// Directories to check announced upfront, and directories checked
+    fn discovery_progress(events: &[ProgressEvent]) -> (usize, usize) {
+        let mut total = 0;
+        let mut checked = 0;
+        for event in events {
+            match event {
+                ProgressEvent::DiscoveryStart { count } => total += count,
+                ProgressEvent::DiscoveryAdvance { count } => checked += count,
+                _ => {}
+            }
+        }
+        (total, checked)
+    }
+
     #[test]
     fn test_discovery_manager() {
         let tmp = tempdir().unwrap();
@@ -384,6 +864,8 @@ mod tests {
 
         let definitions = vec![ExternalDiscoveryDefinition {
             path: root_path.join("projects").to_path_buf(),
+            languages: None,
+            max_depth: None,
         }];
         let count_of_provided_definitions = definitions.len();
 
@@ -453,9 +935,574 @@ mod tests {
         // 2 files in projects/ mocked path
         assert!(progress_report.contains(&ProgressEvent::WalkAddPaths { count: 2 }));
         assert!(progress_report.contains(&ProgressEvent::WalkFinished));
-        // Current count of expected detectors 3 + 1 + 1 for non-discovery definitions
-        assert!(progress_report.contains(&ProgressEvent::DiscoveryStart { count: 5 }));
-        assert!(progress_report.contains(&ProgressEvent::DiscoveryAdvance));
+        // projects/ has 8 directories, checked by all 16 detectors and the VCS detector, and
+        // the static definitions are measured once each
+        let static_count = default_discovery_definitions(root_path)
+            .iter()
+            .filter(|dd| !dd.discovery)
+            .count();
+        let (total, checked) = discovery_progress(&progress_report);
+        assert_eq!(total, 8 * 17 + static_count);
+        assert_eq!(checked, total);
         assert!(progress_report.contains(&ProgressEvent::DiscoveryFinished));
     }
+
+    #[test]
+    fn test_discovery_manager_haskell() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/cabal/dist-newstyle/build")).unwrap();
+        fs::write(root_path.join("projects/cabal/foo.cabal"), "name: foo").unwrap();
+        fs::create_dir_all(root_path.join("projects/stack/.stack-work/dist")).unwrap();
+        fs::write(root_path.join("projects/stack/stack.yaml"), "resolver: lts").unwrap();
+        fs::create_dir_all(root_path.join(".stack/programs")).unwrap();
+        fs::write(root_path.join(".stack/programs/ghc"), "GHC mock").unwrap();
+
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let mut discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .collect();
+        discovery_results.projects.sort_by_key(|r| r.path.clone());
+
+        let projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| (r.path.clone(), r.lang))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            projects,
+            vec![
+                (
+                    root_path.join("projects/cabal/dist-newstyle"),
+                    Language::Haskell
+                ),
+                (
+                    root_path.join("projects/stack/.stack-work"),
+                    Language::Haskell
+                ),
+            ]
+        );
+        assert!(
+            discovery_results
+                .tools
+                .iter()
+                .any(|r| r.path == root_path.join(".stack") && r.lang == Language::Haskell)
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_conda_envs() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        for (env, size) in [("ml", 300), ("web", 100)] {
+            let env_path = root_path.join("miniconda3/envs").join(env);
+            fs::create_dir_all(env_path.join("conda-meta")).unwrap();
+            fs::create_dir_all(env_path.join("bin")).unwrap();
+            fs::write(env_path.join("bin/python"), vec![0; size]).unwrap();
+        }
+        fs::create_dir_all(root_path.join("miniconda3/envs/.trash")).unwrap();
+
+        let mut discovery_results = DiscoveryManager::new(root_path).collect();
+        discovery_results.projects.sort_by_key(|r| r.path.clone());
+
+        let projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| (r.path.clone(), r.lang))
+            .collect::<Vec<_>>();
+        // Each environment is reported once, by the conda detector
+        assert_eq!(
+            projects,
+            vec![
+                (root_path.join("miniconda3/envs/ml"), Language::Python),
+                (root_path.join("miniconda3/envs/web"), Language::Python),
+            ]
+        );
+        assert!(discovery_results.projects[0].size > discovery_results.projects[1].size);
+        assert!(
+            discovery_results
+                .detector_matches
+                .contains(&(CondaEnvDetector::NAME, 2))
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_max_results() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        for name in ["a", "b", "c"] {
+            fs::create_dir_all(
+                root_path
+                    .join("miniconda3/envs")
+                    .join(name)
+                    .join("conda-meta"),
+            )
+            .unwrap();
+        }
+
+        let discovery_results = DiscoveryManager::new(root_path)
+            .select_detectors(Some(&[String::from(CondaEnvDetector::NAME)]), &[])
+            .max_results(Some(2))
+            .collect();
+        assert!(discovery_results.truncated);
+        let collected = discovery_results.projects.len()
+            + discovery_results.tools.len()
+            + discovery_results.vcs.len();
+        assert_eq!(collected, 2);
+
+        let discovery_results = DiscoveryManager::new(root_path)
+            .select_detectors(Some(&[String::from(CondaEnvDetector::NAME)]), &[])
+            .collect();
+        assert!(!discovery_results.truncated);
+        assert_eq!(discovery_results.projects.len(), 3);
+    }
+
+    #[test]
+    fn test_discovery_manager_frontend() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/web/.next/cache")).unwrap();
+        fs::create_dir_all(root_path.join("projects/web/dist/assets")).unwrap();
+        fs::write(root_path.join("projects/web/package.json"), "{}").unwrap();
+        fs::create_dir_all(root_path.join("projects/web/node_modules/foo/dist")).unwrap();
+        fs::write(
+            root_path.join("projects/web/node_modules/foo/package.json"),
+            "{}",
+        )
+        .unwrap();
+        fs::create_dir_all(root_path.join("projects/other/dist")).unwrap();
+
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let mut discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .collect();
+        discovery_results.projects.sort_by_key(|r| r.path.clone());
+
+        let projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| (r.path.clone(), r.lang))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            projects,
+            vec![
+                (root_path.join("projects/web/.next"), Language::JS),
+                (root_path.join("projects/web/dist"), Language::JS),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_keep_empty_tools() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join(".cargo/registry")).unwrap();
+        fs::write(root_path.join(".cargo/registry/index"), "index").unwrap();
+
+        let discovery_results = DiscoveryManager::new(root_path).collect();
+        assert!(discovery_results.tools.iter().all(|r| r.size != 0));
+        let found = discovery_results.tools.len();
+        assert!(found > 0);
+
+        let discovery_results = DiscoveryManager::new(root_path)
+            .keep_empty_tools(true)
+            .collect();
+        assert!(discovery_results.tools.len() > found);
+        assert!(discovery_results.tools.iter().any(|r| r.size == 0));
+    }
+
+    #[test]
+    fn test_discovery_manager_files_only_update() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/a/target/debug/build")).unwrap();
+        let binary = root_path.join("projects/a/target/debug/app");
+        fs::write(&binary, "binary").unwrap();
+        // Built long ago, while the directories were touched just now
+        let built = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&binary)
+            .unwrap()
+            .set_modified(built)
+            .unwrap();
+
+        let definitions = [ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let last_update = |files_only_update| {
+            let discovery_results = DiscoveryManager::new(root_path)
+                .add_definitions(&definitions)
+                .select_detectors(Some(&[String::from(RustBuildDirDetector::NAME)]), &[])
+                .files_only_update(files_only_update)
+                .collect();
+            discovery_results
+                .projects
+                .iter()
+                .find(|p| p.path == root_path.join("projects/a/target"))
+                .unwrap()
+                .last_update
+        };
+
+        assert!(last_update(false).unwrap() > built + Duration::from_days(365));
+        assert_eq!(last_update(true), Some(built));
+    }
+
+    #[test]
+    fn test_discovery_manager_nested_roots() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/a/target/debug/build")).unwrap();
+        fs::create_dir_all(root_path.join("projects/work/b/target/debug/build")).unwrap();
+
+        let definitions =
+            [root_path.join("projects"), root_path.join("projects/work")].map(|path| {
+                ExternalDiscoveryDefinition {
+                    path,
+                    languages: None,
+                    max_depth: None,
+                }
+            });
+        let discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .select_detectors(Some(&[String::from(RustBuildDirDetector::NAME)]), &[])
+            .collect();
+
+        assert!(!discovery_results.projects.is_empty());
+        for project in &discovery_results.projects {
+            let expected = if project.path.starts_with(root_path.join("projects/work")) {
+                root_path.join("projects/work")
+            } else {
+                root_path.join("projects")
+            };
+            assert_eq!(project.root.as_ref(), Some(&expected), "{project:?}");
+        }
+        assert!(
+            discovery_results
+                .projects
+                .iter()
+                .any(|p| p.path == root_path.join("projects/a/target"))
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_path_languages() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        for dir in ["rust", "mixed"] {
+            fs::create_dir_all(root_path.join(dir).join("app/target/debug/build")).unwrap();
+            fs::create_dir_all(root_path.join(dir).join("app/venv/bin")).unwrap();
+            fs::write(root_path.join(dir).join("app/venv/bin/python"), "mock").unwrap();
+        }
+
+        let definitions = vec![
+            ExternalDiscoveryDefinition {
+                path: root_path.join("rust"),
+                languages: Some(vec![Language::Rust]),
+                max_depth: None,
+            },
+            ExternalDiscoveryDefinition {
+                path: root_path.join("mixed"),
+                languages: None,
+                max_depth: None,
+            },
+        ];
+        let mut discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .collect();
+        discovery_results.projects.sort_by_key(|r| r.path.clone());
+
+        let projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| r.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            projects,
+            vec![
+                root_path.join("mixed/app/target"),
+                root_path.join("mixed/app/venv"),
+                root_path.join("rust/app/target"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_sbt() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/scala/target/scala-3.3.1")).unwrap();
+        fs::create_dir_all(root_path.join("projects/scala/target/debug/build")).unwrap();
+        fs::write(
+            root_path.join("projects/scala/build.sbt"),
+            "name := \"foo\"",
+        )
+        .unwrap();
+        fs::create_dir_all(root_path.join("projects/rust/target/debug/build")).unwrap();
+        fs::write(root_path.join("projects/rust/Cargo.toml"), "[package]").unwrap();
+
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .collect();
+
+        let mut projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| (r.path.clone(), r.lang))
+            .collect::<Vec<_>>();
+        projects.sort();
+        assert_eq!(
+            projects,
+            vec![
+                (root_path.join("projects/rust/target"), Language::Rust),
+                (root_path.join("projects/scala/target"), Language::Scala),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_clojure() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/clojure/.cpcache")).unwrap();
+        fs::create_dir_all(root_path.join("projects/clojure/target/classes")).unwrap();
+        fs::write(root_path.join("projects/clojure/deps.edn"), "{:deps {}}").unwrap();
+        fs::create_dir_all(root_path.join("projects/other/.cpcache")).unwrap();
+
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .collect();
+
+        let mut projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| (r.path.clone(), r.lang))
+            .collect::<Vec<_>>();
+        projects.sort();
+        assert_eq!(
+            projects,
+            vec![
+                (
+                    root_path.join("projects/clojure/.cpcache"),
+                    Language::Clojure
+                ),
+                (root_path.join("projects/clojure/target"), Language::Clojure),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_crystal() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/crystal/lib/kemal")).unwrap();
+        fs::write(root_path.join("projects/crystal/shard.yml"), "name: foo").unwrap();
+        fs::create_dir_all(root_path.join("projects/c/lib")).unwrap();
+        fs::write(root_path.join("projects/c/Makefile"), "all:").unwrap();
+
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .collect();
+
+        let projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| (r.path.clone(), r.lang))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            projects,
+            vec![(root_path.join("projects/crystal/lib"), Language::Crystal)]
+        );
+
+        // Every enabled detector reports its matches, even when it found nothing
+        assert_eq!(
+            discovery_results.detector_matches.len(),
+            DETECTOR_NAMES.len()
+        );
+        assert_eq!(discovery_results.detector_matches[0], ("python_venv", 0));
+        assert!(
+            discovery_results
+                .detector_matches
+                .contains(&(CrystalLibDetector::NAME, 1))
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_skip_hidden() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/web/.next/cache")).unwrap();
+        fs::create_dir_all(root_path.join("projects/web/dist")).unwrap();
+        fs::write(root_path.join("projects/web/package.json"), "{}").unwrap();
+
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .skip_hidden(true)
+            .collect();
+
+        let projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| r.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(projects, vec![root_path.join("projects/web/dist")]);
+        let db = discovery_results.db.unwrap();
+        assert!(!db.exists(&root_path.join("projects/web/.next")));
+    }
+
+    #[test]
+    fn test_discovery_manager_vcs_kinds() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/git_repo/.git/objects")).unwrap();
+        fs::write(root_path.join("projects/git_repo/.git/HEAD"), "ref").unwrap();
+        fs::create_dir_all(root_path.join("projects/hg_repo/.hg/store")).unwrap();
+        fs::write(root_path.join("projects/hg_repo/.hg/store/data"), "hg data").unwrap();
+        fs::create_dir_all(root_path.join("projects/svn_repo/.svn")).unwrap();
+        fs::create_dir_all(root_path.join("projects/svn_repo/src/.svn")).unwrap();
+
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let mut discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .collect();
+        discovery_results.vcs.sort_by_key(|r| r.path.clone());
+
+        let vcs = discovery_results
+            .vcs
+            .iter()
+            .map(|r| (r.path.clone(), r.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vcs,
+            vec![
+                (root_path.join("projects/git_repo"), VcsKind::Git),
+                (root_path.join("projects/hg_repo"), VcsKind::Mercurial),
+                (root_path.join("projects/svn_repo"), VcsKind::Subversion),
+            ]
+        );
+        let hg_dirs_size: u64 = [".hg", ".hg/store"]
+            .iter()
+            .map(|p| {
+                fs::metadata(root_path.join("projects/hg_repo").join(p))
+                    .unwrap()
+                    .len()
+            })
+            .sum();
+        assert_eq!(discovery_results.vcs[1].vcs_size, hg_dirs_size + 7);
+    }
+
+    #[test]
+    fn test_discovery_manager_auto_roots() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/rust/target/release/build")).unwrap();
+        fs::create_dir_all(root_path.join("dev/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("dev/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+
+        // `dev` is already configured, so it's not added for the second time
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: PathBuf::from("dev/python"),
+            languages: None,
+            max_depth: None,
+        }];
+        let mut discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .add_auto_roots()
+            .collect();
+        discovery_results.projects.sort_by_key(|r| r.path.clone());
+
+        let projects = discovery_results
+            .projects
+            .iter()
+            .map(|r| r.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            projects,
+            vec![
+                root_path.join("dev/python/venv"),
+                root_path.join("projects/rust/target"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discovery_manager_disabled_detector() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects/rust/target/release/build")).unwrap();
+        fs::create_dir_all(root_path.join("projects/python/venv/bin")).unwrap();
+        fs::write(
+            root_path.join("projects/python/venv/bin/python"),
+            "Python executable mock",
+        )
+        .unwrap();
+
+        let definitions = vec![ExternalDiscoveryDefinition {
+            path: root_path.join("projects"),
+            languages: None,
+            max_depth: None,
+        }];
+        let discovery_manager = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .select_detectors(None, &[String::from("python_venv"), String::from("foo")]);
+        let progress = discovery_manager.subscribe();
+        let discovery_results = discovery_manager.collect();
+
+        assert_eq!(discovery_results.projects.len(), 1);
+        assert_eq!(discovery_results.projects[0].lang, Language::Rust);
+        let progress_report = progress.iter().collect::<Vec<_>>();
+        let (total, checked) = discovery_progress(&progress_report);
+        assert_eq!(checked, total);
+        let all_detectors = DiscoveryManager::new(root_path).add_definitions(&definitions);
+        let all_progress = all_detectors.subscribe();
+        all_detectors.collect();
+        let (all_total, _) = discovery_progress(&all_progress.iter().collect::<Vec<_>>());
+        // The disabled detector doesn't check anything
+        assert!(total < all_total);
+
+        let discovery_results = DiscoveryManager::new(root_path)
+            .add_definitions(&definitions)
+            .select_detectors(Some(&[String::from("python_venv")]), &[])
+            .collect();
+        assert_eq!(discovery_results.projects.len(), 1);
+        assert_eq!(discovery_results.projects[0].lang, Language::Python);
+    }
 }
@@ -7,9 +7,15 @@ mod results;
 mod types;
 
 pub use default_definitions::default_discovery_definitions;
-pub use discovery_definitions::ExternalDiscoveryDefinition;
-pub use discovery_manager::{DiscoveryManager, PathLoader};
+pub use detectors::DETECTOR_NAMES;
+pub use discovery_definitions::{ExternalDiscoveryDefinition, expand_path};
+pub use discovery_manager::{DiscoveryManager, PathLoader, ScanPath, WalkOptions};
 pub use progress::{ProgressEvent, ProgressReporter};
-pub use results::{DiscoveryResults, ProjectResult, ToolingResult, VcsResult};
 #[allow(unused)]
-pub use types::{Language, TypesError};
+pub use results::ParentInfo;
+pub use results::{
+    DiscoveryResultEnvelop, DiscoveryResults, ProjectResult, ToolingResult, VcsResult,
+    is_updated_since, pick_size, resolve_symlinks,
+};
+#[allow(unused)]
+pub use types::{Language, TypesError, VcsKind};
@@ -10,10 +10,10 @@ pub enum ProgressEvent {
     WalkAdvance,
     /// Complete scan has finished
     WalkFinished,
-    /// Discovery started, and a count of detectors is provided
+    /// Discovery started, and a count of directories to check by all detectors is provided
     DiscoveryStart { count: usize },
-    /// Discovery has advanced, one detector finished
-    DiscoveryAdvance,
+    /// Discovery has advanced, more directories were checked
+    DiscoveryAdvance { count: usize },
     /// Discovery has finished
     DiscoveryFinished,
 }
@@ -1,7 +1,11 @@
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
-use crate::discovery::Language;
+use crate::discovery::discovery_definitions::CleanCommand;
+use crate::discovery::{Language, VcsKind};
+use crate::display_tools::StaleFilter;
+use crate::file_info::SizeMode;
 use crate::files_db::FilesDB;
 
 #[derive(Debug)]
@@ -10,6 +14,41 @@ pub struct DiscoveryResults {
     pub tools: Vec<ToolingResult>,
     pub vcs: Vec<VcsResult>,
     pub db: Option<FilesDB>,
+    /// Number of matched paths of each enabled dynamic detector
+    pub detector_matches: Vec<(&'static str, usize)>,
+    /// Some results were dropped because of the maximum number of results
+    pub truncated: bool,
+    /// Paths the scan started from
+    pub scan_roots: Vec<PathBuf>,
+}
+
+impl DiscoveryResults {
+    /// Drop results located in any of the `excluded` directories
+    pub fn exclude_paths(&mut self, excluded: &[PathBuf]) {
+        if excluded.is_empty() {
+            return;
+        }
+        self.projects.retain(|r| !is_excluded(&r.path, excluded));
+        self.tools.retain(|r| !is_excluded(&r.path, excluded));
+        self.vcs.retain(|r| !is_excluded(&r.path, excluded));
+    }
+
+    /// Keep only results which weren't updated for a long time
+    pub fn retain_stale(&mut self, filter: &StaleFilter, now: &SystemTime) {
+        self.projects
+            .retain(|r| filter.is_stale(now, &r.last_update));
+        self.tools.retain(|r| filter.is_stale(now, &r.last_update));
+        self.vcs.retain(|r| filter.is_stale(now, &r.last_update));
+    }
+
+    /// Keep only results updated at `since` or later; results without a last update are dropped
+    pub fn retain_since(&mut self, since: SystemTime) {
+        self.projects
+            .retain(|r| is_updated_since(&r.last_update, since));
+        self.tools
+            .retain(|r| is_updated_since(&r.last_update, since));
+        self.vcs.retain(|r| is_updated_since(&r.last_update, since));
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -17,8 +56,16 @@ pub struct ProjectResult {
     pub path: PathBuf,
     pub lang: Language,
     pub size: u64,
+    /// Space taken on the disk, see [`SizeMode::Allocated`]
+    pub allocated_size: u64,
     pub last_update: Option<SystemTime>,
     pub parent: Option<ParentInfo>,
+    /// Size of the VCS metadata (e.g. `.git`, `.hg`) of the repository containing the project
+    pub vcs_size: Option<u64>,
+    /// Virtualenv whose project doesn't exist anymore
+    pub orphaned: bool,
+    /// Deepest scanned path containing the project
+    pub root: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -27,14 +74,76 @@ pub struct ToolingResult {
     pub path: PathBuf,
     pub lang: Language,
     pub size: u64,
+    pub allocated_size: u64,
     pub last_update: Option<SystemTime>,
     pub info: Option<&'static str>,
+    pub clean: Option<CleanCommand>,
+}
+
+impl ProjectResult {
+    pub fn size_in(&self, mode: SizeMode) -> u64 {
+        pick_size(mode, self.size, self.allocated_size)
+    }
+
+    /// Shell command reclaiming the space of the project
+    pub fn clean_command(&self) -> String {
+        match (self.lang, self.path.parent()) {
+            (Language::Rust, Some(project)) => format!(
+                "cargo clean --manifest-path {}",
+                shell_quote(&project.join("Cargo.toml"))
+            ),
+            _ => remove_command(&self.path),
+        }
+    }
+}
+
+impl ToolingResult {
+    pub fn size_in(&self, mode: SizeMode) -> u64 {
+        pick_size(mode, self.size, self.allocated_size)
+    }
+
+    /// Shell command reclaiming the space of the tool, if there is a safe one
+    pub fn clean_command(&self) -> Option<String> {
+        self.clean.map(|clean| match clean {
+            CleanCommand::Run(command) => command.to_string(),
+            CleanCommand::RemoveDir => remove_command(&self.path),
+        })
+    }
+}
+
+pub fn is_updated_since(last_update: &Option<SystemTime>, since: SystemTime) -> bool {
+    last_update.is_some_and(|time| time >= since)
+}
+
+/// Either of the sizes measured for a result
+pub fn pick_size(mode: SizeMode, apparent: u64, allocated: u64) -> u64 {
+    match mode {
+        SizeMode::Apparent => apparent,
+        SizeMode::Allocated => allocated,
+    }
+}
+
+fn remove_command(path: &Path) -> String {
+    format!("rm -rf {}", shell_quote(path))
+}
+
+/// Quote `path` for POSIX shells when it contains anything else than safe characters
+fn shell_quote(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "/._-+~:@%,".contains(c);
+    if !path.is_empty() && path.chars().all(is_safe) {
+        path.into_owned()
+    } else {
+        format!("'{}'", path.replace('\'', r"'\''"))
+    }
 }
 
 #[derive(Debug)]
 pub struct VcsResult {
     pub path: PathBuf,
+    pub kind: VcsKind,
     pub size: u64,
+    pub allocated_size: u64,
     pub last_update: Option<SystemTime>,
     pub vcs_size: u64,
 }
@@ -43,11 +152,167 @@ pub struct VcsResult {
 pub struct ParentInfo {
     pub path: PathBuf,
     pub size: u64,
+    pub allocated_size: u64,
+}
+
+impl ParentInfo {
+    pub fn size_in(&self, mode: SizeMode) -> u64 {
+        pick_size(mode, self.size, self.allocated_size)
+    }
 }
 
 #[derive(Debug)]
-pub(super) enum DiscoveryResultEnvelop {
+pub enum DiscoveryResultEnvelop {
     Project(ProjectResult),
     Tool(ToolingResult),
     Vcs(VcsResult),
 }
+
+impl DiscoveryResultEnvelop {
+    pub fn is_excluded(&self, excluded: &[PathBuf]) -> bool {
+        let path = match self {
+            DiscoveryResultEnvelop::Project(r) => &r.path,
+            DiscoveryResultEnvelop::Tool(r) => &r.path,
+            DiscoveryResultEnvelop::Vcs(r) => &r.path,
+        };
+        is_excluded(path, excluded)
+    }
+
+    /// Report the real locations of the paths instead of the symlinks leading to them
+    pub fn resolve_symlinks(&mut self) {
+        match self {
+            DiscoveryResultEnvelop::Project(r) => {
+                r.path = resolve_symlinks(&r.path);
+                if let Some(parent) = r.parent.as_mut() {
+                    parent.path = resolve_symlinks(&parent.path);
+                }
+            }
+            DiscoveryResultEnvelop::Tool(r) => r.path = resolve_symlinks(&r.path),
+            DiscoveryResultEnvelop::Vcs(r) => r.path = resolve_symlinks(&r.path),
+        }
+    }
+
+    pub fn last_update(&self) -> Option<SystemTime> {
+        match self {
+            DiscoveryResultEnvelop::Project(r) => r.last_update,
+            DiscoveryResultEnvelop::Tool(r) => r.last_update,
+            DiscoveryResultEnvelop::Vcs(r) => r.last_update,
+        }
+    }
+}
+
+/// Canonical form of `path`; paths which can't be resolved, like broken links, are kept
+pub fn resolve_symlinks(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn is_excluded(path: &Path, excluded: &[PathBuf]) -> bool {
+    excluded.iter().any(|prefix| path.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(path: &str) -> ProjectResult {
+        ProjectResult {
+            path: PathBuf::from(path),
+            lang: Language::Rust,
+            size: 1,
+            allocated_size: 1,
+            last_update: None,
+            parent: None,
+            vcs_size: None,
+            orphaned: false,
+            root: None,
+        }
+    }
+
+    #[test]
+    fn test_exclude_paths() {
+        let mut results = DiscoveryResults {
+            projects: vec![
+                project("/p/foo/target"),
+                project("/p/foobar/target"),
+                project("/p/bar/target"),
+            ],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+
+        results.exclude_paths(&[PathBuf::from("/p/foo"), PathBuf::from("/q")]);
+        let paths = results
+            .projects
+            .iter()
+            .map(|r| r.path.to_str().unwrap())
+            .collect::<Vec<_>>();
+        // Prefixes match whole path components only
+        assert_eq!(paths, vec!["/p/foobar/target", "/p/bar/target"]);
+    }
+
+    #[test]
+    fn test_retain_since() {
+        let since = SystemTime::UNIX_EPOCH + std::time::Duration::from_days(20_000);
+        let updated = |path: &str, last_update| ProjectResult {
+            last_update,
+            ..project(path)
+        };
+        let mut results = DiscoveryResults {
+            projects: vec![
+                updated("/p/before", Some(since - std::time::Duration::from_secs(1))),
+                updated("/p/exactly", Some(since)),
+                updated("/p/after", Some(since + std::time::Duration::from_days(1))),
+                updated("/p/undated", None),
+            ],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+
+        results.retain_since(since);
+        let paths = results
+            .projects
+            .iter()
+            .map(|r| r.path.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/p/exactly", "/p/after"]);
+    }
+
+    #[test]
+    fn test_clean_command() {
+        assert_eq!(
+            project("/p/foo/target").clean_command(),
+            "cargo clean --manifest-path /p/foo/Cargo.toml"
+        );
+
+        let mut venv = project("/p/it's mine/.venv");
+        venv.lang = Language::Python;
+        assert_eq!(venv.clean_command(), r"rm -rf '/p/it'\''s mine/.venv'");
+
+        let mut tool = ToolingResult {
+            description: "uv cache",
+            path: PathBuf::from("/home/foo/.cache/uv"),
+            lang: Language::Python,
+            size: 1,
+            allocated_size: 1,
+            last_update: None,
+            info: None,
+            clean: Some(CleanCommand::Run("uv cache clean")),
+        };
+        assert_eq!(tool.clean_command().as_deref(), Some("uv cache clean"));
+        tool.clean = Some(CleanCommand::RemoveDir);
+        assert_eq!(
+            tool.clean_command().as_deref(),
+            Some("rm -rf /home/foo/.cache/uv")
+        );
+        tool.clean = None;
+        assert_eq!(tool.clean_command(), None);
+    }
+}
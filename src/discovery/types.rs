@@ -6,11 +6,49 @@ pub enum TypesError {
     UnknownLanguage(String),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// Languages are ordered as declared, which keeps the results of the same language together
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Language {
     Python,
     Rust,
     JS,
+    Dart,
+    Haskell,
+    Zig,
+    OCaml,
+    Swift,
+    Julia,
+    Nim,
+    R,
+    Crystal,
+    Scala,
+    Clojure,
+    Android,
+    GameDev,
+}
+
+impl Language {
+    /// Name accepted by `Language::try_from`, used in machine-readable output
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Python => "python",
+            Language::Rust => "rust",
+            Language::JS => "javascript",
+            Language::Dart => "dart",
+            Language::Haskell => "haskell",
+            Language::Zig => "zig",
+            Language::OCaml => "ocaml",
+            Language::Swift => "swift",
+            Language::Julia => "julia",
+            Language::Nim => "nim",
+            Language::R => "r",
+            Language::Crystal => "crystal",
+            Language::Scala => "scala",
+            Language::Clojure => "clojure",
+            Language::Android => "android",
+            Language::GameDev => "gamedev",
+        }
+    }
 }
 
 impl Display for Language {
@@ -19,6 +57,50 @@ impl Display for Language {
             Language::Python => write!(f, "🐍"),
             Language::Rust => write!(f, "🦀"),
             Language::JS => write!(f, "🟨"),
+            Language::Dart => write!(f, "🎯"),
+            Language::Haskell => write!(f, "🟪"),
+            Language::Zig => write!(f, "⚡"),
+            Language::OCaml => write!(f, "🐫"),
+            Language::Swift => write!(f, "🐦"),
+            Language::Julia => write!(f, "🟣"),
+            Language::Nim => write!(f, "👑"),
+            Language::R => write!(f, "📊"),
+            Language::Crystal => write!(f, "🔮"),
+            Language::Scala => write!(f, "🟥"),
+            Language::Clojure => write!(f, "🍀"),
+            Language::Android => write!(f, "🤖"),
+            Language::GameDev => write!(f, "🎮"),
+        }
+    }
+}
+
+/// Version control system owning a repository
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VcsKind {
+    Git,
+    Mercurial,
+    Subversion,
+}
+
+impl VcsKind {
+    pub const ALL: [VcsKind; 3] = [VcsKind::Git, VcsKind::Mercurial, VcsKind::Subversion];
+
+    /// Name of the metadata directory in the repository root
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            VcsKind::Git => ".git",
+            VcsKind::Mercurial => ".hg",
+            VcsKind::Subversion => ".svn",
+        }
+    }
+}
+
+impl Display for VcsKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VcsKind::Git => write!(f, "git"),
+            VcsKind::Mercurial => write!(f, "hg"),
+            VcsKind::Subversion => write!(f, "svn"),
         }
     }
 }
@@ -32,6 +114,19 @@ impl TryFrom<&str> for Language {
             "python" => Ok(Language::Python),
             "rust" => Ok(Language::Rust),
             "javascript" => Ok(Language::JS),
+            "dart" | "flutter" => Ok(Language::Dart),
+            "haskell" => Ok(Language::Haskell),
+            "zig" => Ok(Language::Zig),
+            "ocaml" => Ok(Language::OCaml),
+            "swift" => Ok(Language::Swift),
+            "julia" => Ok(Language::Julia),
+            "nim" => Ok(Language::Nim),
+            "r" | "rlang" => Ok(Language::R),
+            "crystal" => Ok(Language::Crystal),
+            "scala" | "sbt" => Ok(Language::Scala),
+            "clojure" | "clj" => Ok(Language::Clojure),
+            "android" => Ok(Language::Android),
+            "gamedev" | "unity" | "unreal" => Ok(Language::GameDev),
             _ => Err(TypesError::UnknownLanguage(value.to_string())),
         }
     }
@@ -49,6 +144,50 @@ impl TryFrom<&String> for Language {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_language_name_round_trip() {
+        for lang in [
+            Language::Python,
+            Language::Rust,
+            Language::JS,
+            Language::Dart,
+            Language::Haskell,
+            Language::Zig,
+            Language::OCaml,
+            Language::Swift,
+            Language::Julia,
+            Language::Nim,
+            Language::R,
+            Language::Crystal,
+            Language::Scala,
+            Language::Clojure,
+            Language::Android,
+            Language::GameDev,
+        ] {
+            assert_eq!(Language::try_from(lang.name()).unwrap(), lang);
+        }
+    }
+
+    #[test]
+    fn test_language_order() {
+        let mut langs = vec![
+            Language::GameDev,
+            Language::Rust,
+            Language::JS,
+            Language::Python,
+        ];
+        langs.sort();
+        assert_eq!(
+            langs,
+            vec![
+                Language::Python,
+                Language::Rust,
+                Language::JS,
+                Language::GameDev
+            ]
+        );
+    }
+
     #[test]
     fn test_language_try_from_python() {
         assert_eq!(Language::try_from("python").unwrap(), Language::Python);
@@ -68,6 +207,93 @@ mod tests {
         assert!(Language::try_from("foo").is_err());
     }
 
+    #[test]
+    fn test_language_try_from_dart() {
+        assert_eq!(Language::try_from("dart").unwrap(), Language::Dart);
+        assert_eq!(Language::try_from("Flutter").unwrap(), Language::Dart);
+    }
+
+    #[test]
+    fn test_language_try_from_haskell() {
+        assert_eq!(Language::try_from("haskell").unwrap(), Language::Haskell);
+        assert_eq!(Language::try_from("Haskell").unwrap(), Language::Haskell);
+    }
+
+    #[test]
+    fn test_language_try_from_zig() {
+        assert_eq!(Language::try_from("zig").unwrap(), Language::Zig);
+        assert_eq!(Language::try_from("Zig").unwrap(), Language::Zig);
+    }
+
+    #[test]
+    fn test_language_try_from_ocaml() {
+        assert_eq!(Language::try_from("ocaml").unwrap(), Language::OCaml);
+        assert_eq!(Language::try_from("OCaml").unwrap(), Language::OCaml);
+    }
+
+    #[test]
+    fn test_language_try_from_swift() {
+        assert_eq!(Language::try_from("swift").unwrap(), Language::Swift);
+        assert_eq!(Language::try_from("Swift").unwrap(), Language::Swift);
+    }
+
+    #[test]
+    fn test_language_try_from_julia() {
+        assert_eq!(Language::try_from("julia").unwrap(), Language::Julia);
+        assert_eq!(Language::try_from("Julia").unwrap(), Language::Julia);
+    }
+
+    #[test]
+    fn test_language_try_from_nim() {
+        assert_eq!(Language::try_from("nim").unwrap(), Language::Nim);
+        assert_eq!(Language::try_from("Nim").unwrap(), Language::Nim);
+    }
+
+    #[test]
+    fn test_language_try_from_r() {
+        assert_eq!(Language::try_from("r").unwrap(), Language::R);
+        assert_eq!(Language::try_from("RLang").unwrap(), Language::R);
+    }
+
+    #[test]
+    fn test_language_try_from_crystal() {
+        assert_eq!(Language::try_from("crystal").unwrap(), Language::Crystal);
+        assert_eq!(Language::try_from("Crystal").unwrap(), Language::Crystal);
+    }
+
+    #[test]
+    fn test_language_try_from_scala() {
+        assert_eq!(Language::try_from("scala").unwrap(), Language::Scala);
+        assert_eq!(Language::try_from("SBT").unwrap(), Language::Scala);
+    }
+
+    #[test]
+    fn test_language_try_from_clojure() {
+        assert_eq!(Language::try_from("clojure").unwrap(), Language::Clojure);
+        assert_eq!(Language::try_from("CLJ").unwrap(), Language::Clojure);
+    }
+
+    #[test]
+    fn test_language_try_from_android() {
+        assert_eq!(Language::try_from("android").unwrap(), Language::Android);
+        assert_eq!(Language::try_from("Android").unwrap(), Language::Android);
+    }
+
+    #[test]
+    fn test_language_try_from_gamedev() {
+        assert_eq!(Language::try_from("gamedev").unwrap(), Language::GameDev);
+        assert_eq!(Language::try_from("Unity").unwrap(), Language::GameDev);
+        assert_eq!(Language::try_from("unreal").unwrap(), Language::GameDev);
+    }
+
+    #[test]
+    fn test_vcs_kind_dir_name() {
+        assert_eq!(VcsKind::Git.dir_name(), ".git");
+        assert_eq!(VcsKind::Mercurial.dir_name(), ".hg");
+        assert_eq!(VcsKind::Subversion.dir_name(), ".svn");
+        assert_eq!(VcsKind::Mercurial.to_string(), "hg");
+    }
+
     #[test]
     fn test_language_try_from_err() {
         assert!(Language::try_from("foo").is_err());
@@ -1,73 +1,711 @@
-use std::io::Write;
-use std::time::{Duration, SystemTime};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use chrono::{DateTime, Local};
 use crossbeam::channel::Receiver;
-use humansize::{DECIMAL, format_size};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use tabled::settings::object::Rows;
-use tabled::settings::{Alignment, Color, Modify, Panel, Style, object::Cell};
+use serde::Serialize;
+use tabled::settings::location::ByColumnName;
+use tabled::settings::object::{Row, Rows};
+use tabled::settings::{Alignment, Color, Modify, Panel, Remove, Style, object::Cell};
 use tabled::{Table, Tabled};
 use tracing::instrument;
 
-use crate::discovery::ProgressEvent;
-use crate::discovery::{DiscoveryResults, ProjectResult, ToolingResult};
-use crate::display_tools::{ColorCode, get_size_color_code, get_time_color_code};
+use crate::discovery::{DiscoveryResultEnvelop, DiscoveryResults, ProjectResult, ToolingResult};
+use crate::discovery::{Language, ProgressEvent};
+use crate::display_tools::{
+    ColorCode, ColorMode, Labels, SizeStats, SizeUnit, TimeFormat, format_delta,
+    format_last_update, format_size_in, get_size_color_code, get_time_color_code,
+};
+use crate::duplicates::DuplicateGroup;
+use crate::history::History;
+use crate::largest_dirs::DirSize;
 
-#[instrument(level = "debug", skip(out, discovery_results))]
-pub fn print_results<W: Write>(out: &mut W, discovery_results: DiscoveryResults) {
-    let projects_data: Vec<Record> = discovery_results
-        .projects
-        .iter()
-        .map(Record::from)
-        .collect();
-    let tooling_data: Vec<ToolingRecord> = discovery_results
-        .tools
+/// Header of the optional column with the commands cleaning the results
+const CLEAN_COLUMN: &str = "Clean with";
+/// Header of the column with the configured labels, shown only when any result has one
+const LABEL_COLUMN: &str = "Label";
+/// Appended to the paths of virtualenvs whose project doesn't exist anymore
+pub const ORPHANED_MARK: &str = "(orphaned)";
+
+/// How the result tables are printed
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TableOptions {
+    pub time_format: TimeFormat,
+    pub group_by: Option<GroupBy>,
+    pub sort: SortOrder,
+    pub color_mode: ColorMode,
+    /// Show only the N biggest items of each table
+    pub top: Option<usize>,
+    pub hide_tooling: bool,
+    pub hide_projects: bool,
+    /// Show the commands reclaiming the space of each result
+    pub with_commands: bool,
+    /// Print all sizes in this unit instead of scaling them to their magnitude
+    pub unit: Option<SizeUnit>,
+}
+
+#[instrument(level = "debug", skip(out, discovery_results, history, labels))]
+pub fn print_results<W: Write>(
+    out: &mut W,
+    discovery_results: DiscoveryResults,
+    options: &TableOptions,
+    history: &History,
+    labels: &Labels,
+) {
+    let now = SystemTime::now();
+
+    if !options.hide_tooling {
+        print_tooling_table(out, &discovery_results.tools, &now, options, history);
+    }
+    if !options.hide_projects {
+        print_projects_table(
+            out,
+            &discovery_results.projects,
+            &now,
+            options,
+            history,
+            labels,
+        );
+    }
+}
+
+fn print_tooling_table<W: Write>(
+    out: &mut W,
+    tools: &[ToolingResult],
+    now: &SystemTime,
+    options: &TableOptions,
+    history: &History,
+) {
+    let mut tooling_data: Vec<ToolingRecord> = tools
         .iter()
-        .map(ToolingRecord::from)
+        .map(|r| ToolingRecord::new(r, now, options, history))
         .collect();
 
-    let projects_sum: u64 = discovery_results.projects.iter().map(|r| r.size).sum();
-    let tooling_sum: u64 = discovery_results.tools.iter().map(|r| r.size).sum();
+    let tooling_hidden = keep_top(&mut tooling_data, options.top, |d| d.record.size);
+    options.sort.sort(&mut tooling_data, |d| &d.record);
 
-    let now = SystemTime::now();
+    let tooling_stats = match options.color_mode {
+        ColorMode::Absolute => None,
+        ColorMode::Relative => SizeStats::from_sizes(tooling_data.iter().map(|d| d.record.size)),
+    };
+
+    let tooling_sum: u64 = tools.iter().map(|r| r.size).sum();
 
     let mut table_tooling_build = Table::new(&tooling_data);
+    if !options.with_commands {
+        table_tooling_build.with(Remove::column(ByColumnName::new(CLEAN_COLUMN)));
+    }
+    table_tooling_build.with(Remove::column(ByColumnName::new(LABEL_COLUMN)));
     table_tooling_build.with(Panel::header("Tooling"));
-    table_tooling_build.with(Panel::footer(format_size(tooling_sum, DECIMAL)));
+    if let Some(hidden) = tooling_hidden {
+        table_tooling_build.with(Panel::footer(hidden.format(options.unit)));
+    }
+    table_tooling_build.with(Panel::footer(format_size_in(tooling_sum, options.unit)));
     table_tooling_build.with(Modify::new(Rows::last()).with(Color::BOLD));
     table_tooling_build.with(Modify::new(Rows::last()).with(Alignment::right()));
     table_tooling_build.with(Modify::new(Cell::new(0, 0)).with(Color::BOLD));
     table_tooling_build.with(Style::empty());
     tooling_data.iter().enumerate().for_each(|(i, d)| {
+        // Shown only with `--all`, to tell where the tooling was looked for
+        if d.record.size == 0 {
+            table_tooling_build.with(Modify::new(Row::from(i + 2)).with(Color::FG_BRIGHT_BLACK));
+            return;
+        }
         table_tooling_build
-            .with(Modify::new(Cell::new(i + 2, 3)).with(time_color_coded(&now, &d.record.time)));
-        table_tooling_build
-            .with(Modify::new(Cell::new(i + 2, 4)).with(size_color_coded(d.record.size)));
+            .with(Modify::new(Cell::new(i + 2, 3)).with(time_color_coded(now, &d.record.time)));
+        table_tooling_build.with(
+            Modify::new(Cell::new(i + 2, 4))
+                .with(size_color_coded(d.record.size, tooling_stats.as_ref())),
+        );
+        if options.with_commands {
+            table_tooling_build.with(Modify::new(Cell::new(i + 2, 6)).with(Color::FG_BRIGHT_BLACK));
+        }
     });
     let table_tooling = table_tooling_build.to_string();
     writeln!(out, "{table_tooling}").expect("Cannot write to stdout");
+}
+
+fn print_projects_table<W: Write>(
+    out: &mut W,
+    projects: &[ProjectResult],
+    now: &SystemTime,
+    options: &TableOptions,
+    history: &History,
+    labels: &Labels,
+) {
+    let mut projects_data: Vec<Record> = match options.group_by {
+        None => projects
+            .iter()
+            .map(|r| Record::new(r, now, options, history, labels))
+            .collect(),
+        Some(GroupBy::Parent) => group_by_parent(projects, now, options, history, labels),
+    };
+
+    let projects_hidden = keep_top(&mut projects_data, options.top, |d| d.size);
+    options.sort.sort(&mut projects_data, |d| d);
+
+    let projects_stats = match options.color_mode {
+        ColorMode::Absolute => None,
+        ColorMode::Relative => SizeStats::from_sizes(projects_data.iter().map(|d| d.size)),
+    };
+
+    let projects_sum: u64 = projects.iter().map(|r| r.size).sum();
 
     let mut table_projects_build = Table::new(&projects_data);
+    if !options.with_commands {
+        table_projects_build.with(Remove::column(ByColumnName::new(CLEAN_COLUMN)));
+    }
+    if projects_data.iter().all(|d| d.label.is_none()) {
+        table_projects_build.with(Remove::column(ByColumnName::new(LABEL_COLUMN)));
+    }
     table_projects_build.with(Panel::header("Projects"));
-    table_projects_build.with(Panel::footer(format_size(projects_sum, DECIMAL)));
+    if let Some(hidden) = projects_hidden {
+        table_projects_build.with(Panel::footer(hidden.format(options.unit)));
+    }
+    for (root, size) in root_subtotals(projects) {
+        table_projects_build.with(Panel::footer(format!(
+            "{}: {}",
+            root.display(),
+            format_size_in(size, options.unit)
+        )));
+    }
+    table_projects_build.with(Panel::footer(format_size_in(projects_sum, options.unit)));
     table_projects_build.with(Modify::new(Rows::last()).with(Color::BOLD));
     table_projects_build.with(Modify::new(Rows::last()).with(Alignment::right()));
     table_projects_build.with(Modify::new(Cell::new(0, 0)).with(Color::BOLD));
     table_projects_build.with(Style::empty());
     projects_data.iter().enumerate().for_each(|(i, d)| {
         table_projects_build
-            .with(Modify::new(Cell::new(i + 2, 2)).with(time_color_coded(&now, &d.time)));
-        table_projects_build.with(Modify::new(Cell::new(i + 2, 3)).with(size_color_coded(d.size)));
+            .with(Modify::new(Cell::new(i + 2, 2)).with(time_color_coded(now, &d.time)));
+        table_projects_build.with(
+            Modify::new(Cell::new(i + 2, 3))
+                .with(size_color_coded(d.size, projects_stats.as_ref())),
+        );
+        if options.with_commands {
+            table_projects_build
+                .with(Modify::new(Cell::new(i + 2, 5)).with(Color::FG_BRIGHT_BLACK));
+        }
     });
     let table_projects = table_projects_build.to_string();
     writeln!(out, "{table_projects}").expect("Cannot write to stdout");
 }
 
+/// Size of the projects found in each scanned path, the biggest first
+///
+/// Returns nothing when all the projects come from a single path, as the subtotal would
+/// just repeat the total.
+fn root_subtotals(projects: &[ProjectResult]) -> Vec<(PathBuf, u64)> {
+    let mut subtotals: BTreeMap<&Path, u64> = BTreeMap::new();
+    for project in projects {
+        if let Some(root) = &project.root {
+            *subtotals.entry(root).or_default() += project.size;
+        }
+    }
+    if subtotals.len() < 2 {
+        return vec![];
+    }
+
+    let mut subtotals: Vec<_> = subtotals
+        .into_iter()
+        .map(|(root, size)| (root.to_path_buf(), size))
+        .collect();
+    subtotals.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    subtotals
+}
+
+/// Sizes of the results of a single language
+pub struct LanguageRecord {
+    lang: Language,
+    projects: usize,
+    projects_size: u64,
+    tooling_size: u64,
+    total: u64,
+}
+
+/// Row of the `--by-language` table, with the sizes formatted in the requested unit
+#[derive(Tabled)]
+struct LanguageRow {
+    #[tabled(rename = "Lang")]
+    lang: Language,
+    #[tabled(rename = "Projects")]
+    projects: usize,
+    #[tabled(rename = "Projects size")]
+    projects_size: String,
+    #[tabled(rename = "Tooling size")]
+    tooling_size: String,
+    #[tabled(rename = "Total")]
+    total: String,
+}
+
+/// Sum the results per language, the biggest languages first
+pub fn language_summary(discovery_results: &DiscoveryResults) -> Vec<LanguageRecord> {
+    let projects = discovery_results
+        .projects
+        .iter()
+        .map(|r| (r.lang, r.size, true));
+    let tools = discovery_results
+        .tools
+        .iter()
+        .map(|r| (r.lang, r.size, false));
+
+    let mut records: Vec<LanguageRecord> = vec![];
+    for (lang, size, is_project) in projects.chain(tools) {
+        let idx = records
+            .iter()
+            .position(|r| r.lang == lang)
+            .unwrap_or_else(|| {
+                records.push(LanguageRecord {
+                    lang,
+                    projects: 0,
+                    projects_size: 0,
+                    tooling_size: 0,
+                    total: 0,
+                });
+                records.len() - 1
+            });
+        let record = &mut records[idx];
+        if is_project {
+            record.projects += 1;
+            record.projects_size += size;
+        } else {
+            record.tooling_size += size;
+        }
+        record.total += size;
+    }
+
+    records.sort_by_key(|r| std::cmp::Reverse(r.total));
+    records
+}
+
+pub fn print_language_summary<W: Write>(
+    out: &mut W,
+    summary: &[LanguageRecord],
+    unit: Option<SizeUnit>,
+) {
+    let rows = summary.iter().map(|r| LanguageRow {
+        lang: r.lang,
+        projects: r.projects,
+        projects_size: format_size_in(r.projects_size, unit),
+        tooling_size: format_size_in(r.tooling_size, unit),
+        total: format_size_in(r.total, unit),
+    });
+    let mut table = Table::new(rows);
+    table.with(Panel::header("By language"));
+    table.with(Modify::new(Cell::new(0, 0)).with(Color::BOLD));
+    table.with(Style::empty());
+    writeln!(out, "{table}").expect("Cannot write to stdout");
+}
+
+/// Directory reported by `--largest-dirs`
+#[derive(Tabled)]
+struct DirRecord {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Size")]
+    size: String,
+}
+
+pub fn print_largest_dirs<W: Write>(out: &mut W, dirs: &[DirSize], unit: Option<SizeUnit>) {
+    let records = dirs.iter().map(|dir| DirRecord {
+        path: dir.path.display().to_string(),
+        size: format_size_in(dir.size, unit),
+    });
+    let mut table = Table::new(records);
+    table.with(Panel::header("Largest directories"));
+    table.with(Modify::new(Cell::new(0, 0)).with(Color::BOLD));
+    table.with(Style::empty());
+    writeln!(out, "{table}").expect("Cannot write to stdout");
+}
+
+/// Records left out of the output by `--top`
+struct HiddenRecords {
+    count: usize,
+    size: u64,
+}
+
+impl HiddenRecords {
+    fn format(&self, unit: Option<SizeUnit>) -> String {
+        format!(
+            "… and {} more ({} total)",
+            self.count,
+            format_size_in(self.size, unit)
+        )
+    }
+}
+
+/// Keep only the `top` biggest records, sorted by size in descending order
+fn keep_top<T>(
+    records: &mut Vec<T>,
+    top: Option<usize>,
+    size: impl Fn(&T) -> u64,
+) -> Option<HiddenRecords> {
+    let top = top?;
+    records.sort_by_key(|r| std::cmp::Reverse(size(r)));
+    if records.len() <= top {
+        return None;
+    }
+
+    let hidden = records.split_off(top);
+    Some(HiddenRecords {
+        count: hidden.len(),
+        size: hidden.iter().map(size).sum(),
+    })
+}
+
+/// Number of duplicate groups printed when `--top` isn't given
+const DUPLICATES_TOP: usize = 10;
+
+/// Print the groups of duplicate files wasting the most space
+pub fn print_duplicates<W: Write>(
+    out: &mut W,
+    groups: &[DuplicateGroup],
+    top: Option<usize>,
+    unit: Option<SizeUnit>,
+) {
+    let wasted: u64 = groups.iter().map(DuplicateGroup::reclaimable).sum();
+    writeln!(
+        out,
+        "Duplicate files: {} reclaimable in {} groups",
+        format_size_in(wasted, unit),
+        groups.len()
+    )
+    .expect("Cannot write to stdout");
+
+    let top = top.unwrap_or(DUPLICATES_TOP);
+    for group in groups.iter().take(top) {
+        writeln!(
+            out,
+            "  {} reclaimable ({} copies of {})",
+            format_size_in(group.reclaimable(), unit),
+            group.paths.len(),
+            format_size_in(group.size, unit)
+        )
+        .expect("Cannot write to stdout");
+        for path in &group.paths {
+            writeln!(out, "    {}", path.display()).expect("Cannot write to stdout");
+        }
+    }
+    if groups.len() > top {
+        let hidden = &groups[top..];
+        writeln!(
+            out,
+            "  … and {} more groups ({} reclaimable)",
+            hidden.len(),
+            format_size_in(
+                hidden.iter().map(DuplicateGroup::reclaimable).sum::<u64>(),
+                unit
+            )
+        )
+        .expect("Cannot write to stdout");
+    }
+}
+
+pub fn print_detector_matches<W: Write>(out: &mut W, matches: &[(&'static str, usize)]) {
+    writeln!(out, "Matches per detector:").expect("Cannot write to stdout");
+    for (name, count) in matches {
+        writeln!(out, "  {name}: {count}").expect("Cannot write to stdout");
+    }
+}
+
+pub fn print_discovered<W: Write>(
+    out: &mut W,
+    result: &DiscoveryResultEnvelop,
+    unit: Option<SizeUnit>,
+) {
+    let (lang, path, size) = match result {
+        DiscoveryResultEnvelop::Project(r) => (r.lang, &r.path, r.size),
+        DiscoveryResultEnvelop::Tool(r) => (r.lang, &r.path, r.size),
+        DiscoveryResultEnvelop::Vcs(_) => return,
+    };
+    writeln!(
+        out,
+        "found: {lang} {} ({})",
+        path.display(),
+        format_size_in(size, unit)
+    )
+    .expect("Cannot write to stdout");
+}
+
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable tables
+    #[default]
+    Table,
+    /// One JSON object per result and line, written as the results are found
+    Jsonl,
+    /// Indented tree of the results grouped by their common path prefixes
+    Tree,
+}
+
+/// Node of the path trie printed by `--format tree`
+#[derive(Default)]
+struct TreeNode {
+    /// Language and size of the result found exactly at this path
+    result: Option<(Language, u64)>,
+    children: BTreeMap<OsString, TreeNode>,
+}
+
+impl TreeNode {
+    fn insert(&mut self, path: &Path, lang: Language, size: u64) {
+        let node = path.components().fold(self, |node, component| {
+            node.children
+                .entry(component.as_os_str().to_os_string())
+                .or_default()
+        });
+        node.result = Some((lang, size));
+    }
+
+    /// Size of the result at this node, or the sum of the results below it
+    fn size(&self) -> u64 {
+        match self.result {
+            Some((_, size)) => size,
+            None => self.children.values().map(TreeNode::size).sum(),
+        }
+    }
+
+    /// Children sorted from the biggest, with chains of single directories joined into one path
+    fn sorted_children(&self) -> Vec<(PathBuf, &TreeNode)> {
+        let mut children = self
+            .children
+            .iter()
+            .map(|(name, child)| {
+                let mut path = PathBuf::from(name);
+                let mut node = child;
+                while node.result.is_none() && node.children.len() == 1 {
+                    let (name, child) = node.children.iter().next().unwrap();
+                    path.push(name);
+                    node = child;
+                }
+                (path, node)
+            })
+            .collect::<Vec<_>>();
+        children.sort_by_key(|(_, node)| std::cmp::Reverse(node.size()));
+        children
+    }
+}
+
+/// Print the projects and tools as a tree of their paths, with summed sizes at each node
+pub fn print_tree<W: Write>(
+    out: &mut W,
+    discovery_results: &DiscoveryResults,
+    options: &TableOptions,
+) {
+    let mut root = TreeNode::default();
+    if !options.hide_tooling {
+        for r in &discovery_results.tools {
+            root.insert(&r.path, r.lang, r.size);
+        }
+    }
+    if !options.hide_projects {
+        for r in &discovery_results.projects {
+            root.insert(&r.path, r.lang, r.size);
+        }
+    }
+
+    for (path, node) in root.sorted_children() {
+        print_tree_node(out, &path, node, "", "", options.unit);
+    }
+}
+
+fn print_tree_node<W: Write>(
+    out: &mut W,
+    path: &Path,
+    node: &TreeNode,
+    prefix: &str,
+    children_prefix: &str,
+    unit: Option<SizeUnit>,
+) {
+    let glyph = match node.result {
+        Some((lang, _)) => format!("{lang} "),
+        None => String::new(),
+    };
+    writeln!(
+        out,
+        "{prefix}{glyph}{} ({})",
+        path.display(),
+        format_size_in(node.size(), unit)
+    )
+    .expect("Cannot write to stdout");
+
+    let children = node.sorted_children();
+    let last = children.len().saturating_sub(1);
+    for (i, (path, child)) in children.iter().enumerate() {
+        let (branch, indent) = if i == last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        print_tree_node(
+            out,
+            path,
+            child,
+            &format!("{children_prefix}{branch}"),
+            &format!("{children_prefix}{indent}"),
+            unit,
+        );
+    }
+}
+
+/// A single result in the machine-readable output
+#[derive(Serialize, Debug)]
+struct JsonRecord<'a> {
+    kind: &'static str,
+    path: &'a Path,
+    language: &'static str,
+    size: u64,
+    /// Seconds since the Unix epoch
+    last_update: Option<u64>,
+    description: Option<&'static str>,
+}
+
+impl<'a> JsonRecord<'a> {
+    fn new(result: &'a DiscoveryResultEnvelop) -> Option<Self> {
+        let unix_time = |time: Option<SystemTime>| {
+            time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+        };
+        match result {
+            DiscoveryResultEnvelop::Project(r) => Some(Self {
+                kind: "project",
+                path: &r.path,
+                language: r.lang.name(),
+                size: r.size,
+                last_update: unix_time(r.last_update),
+                description: None,
+            }),
+            DiscoveryResultEnvelop::Tool(r) => Some(Self {
+                kind: "tool",
+                path: &r.path,
+                language: r.lang.name(),
+                size: r.size,
+                last_update: unix_time(r.last_update),
+                description: Some(r.description),
+            }),
+            DiscoveryResultEnvelop::Vcs(_) => None,
+        }
+    }
+}
+
+/// Write the result as a single line of JSON and flush it right away
+pub fn print_jsonl<W: Write>(out: &mut W, result: &DiscoveryResultEnvelop) {
+    let Some(record) = JsonRecord::new(result) else {
+        return;
+    };
+    let line = serde_json::to_string(&record).expect("Result is always serializable");
+    writeln!(out, "{line}").expect("Cannot write to stdout");
+    out.flush().expect("Cannot write to stdout");
+}
+
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// The biggest results first; results of the same size by language
+    #[default]
+    Size,
+    /// Results of the same language together, the biggest first
+    Lang,
+}
+
+impl SortOrder {
+    fn sort<T>(&self, records: &mut [T], record: impl Fn(&T) -> &Record) {
+        match self {
+            SortOrder::Size => records.sort_by_key(|r| {
+                let r = record(r);
+                (std::cmp::Reverse(r.size), r.language)
+            }),
+            SortOrder::Lang => records.sort_by_key(|r| {
+                let r = record(r);
+                (r.language, std::cmp::Reverse(r.size))
+            }),
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Sum results sharing the same parent directory
+    Parent,
+}
+
+/// Aggregate projects by their parent directory
+///
+/// Projects without a sibling stay as they are, as well as projects without a known parent.
+fn group_by_parent(
+    projects: &[ProjectResult],
+    now: &SystemTime,
+    options: &TableOptions,
+    history: &History,
+    labels: &Labels,
+) -> Vec<Record> {
+    let mut groups: BTreeMap<&Path, Vec<&ProjectResult>> = BTreeMap::new();
+    let mut ungrouped = vec![];
+    for project in projects {
+        match project.parent.as_ref() {
+            Some(parent) => groups
+                .entry(parent.path.as_path())
+                .or_default()
+                .push(project),
+            None => ungrouped.push(project),
+        }
+    }
+
+    let mut records = vec![];
+    for (parent, members) in groups {
+        if let [project] = members.as_slice() {
+            records.push(Record::new(project, now, options, history, labels));
+            continue;
+        }
+
+        let mut langs = vec![];
+        for project in members.iter() {
+            if !langs.contains(&project.lang) {
+                langs.push(project.lang);
+            }
+        }
+        let time = members.iter().filter_map(|p| p.last_update).max();
+        let size = members.iter().map(|p| p.size).sum();
+        // Known only when all members were seen in the previous run
+        let delta = members
+            .iter()
+            .map(|p| history.delta(&p.path, p.size))
+            .sum::<Option<i64>>();
+        records.push(Record {
+            lang: langs.iter().map(|l| l.to_string()).collect(),
+            language: *langs.iter().min().expect("Groups have several members"),
+            path: format!("{} ({} items)", parent.display(), members.len()),
+            human_time: format_last_update(now, time, options.time_format),
+            time,
+            human_size: format_size_in(size, options.unit),
+            size,
+            human_delta: delta.map(|delta| format_delta(delta, options.unit)),
+            clean_command: Some(
+                members
+                    .iter()
+                    .map(|p| p.clean_command())
+                    .collect::<Vec<_>>()
+                    .join(" && "),
+            ),
+            label: labels.get(parent).map(String::from),
+        });
+    }
+    records.extend(
+        ungrouped
+            .into_iter()
+            .map(|project| Record::new(project, now, options, history, labels)),
+    );
+
+    records
+}
+
 #[derive(Tabled)]
 struct Record {
     #[tabled(rename = "Lang")]
     lang: String,
+    /// Used for sorting; the first language of grouped records
+    #[tabled(skip)]
+    language: Language,
     #[tabled(rename = "Path")]
     path: String,
     #[tabled(rename = "Last change", display("tabled::derive::display::option", ""))]
@@ -78,6 +716,15 @@ struct Record {
     human_size: String,
     #[tabled(skip)]
     size: u64,
+    #[tabled(
+        rename = "Since last run",
+        display("tabled::derive::display::option", "")
+    )]
+    human_delta: Option<String>,
+    #[tabled(rename = "Clean with", display("tabled::derive::display::option", ""))]
+    clean_command: Option<String>,
+    #[tabled(rename = "Label", display("tabled::derive::display::option", ""))]
+    label: Option<String>,
 }
 
 #[derive(Tabled)]
@@ -88,45 +735,64 @@ struct ToolingRecord {
     record: Record,
 }
 
-impl From<&ProjectResult> for Record {
-    fn from(value: &ProjectResult) -> Self {
+impl Record {
+    fn new(
+        value: &ProjectResult,
+        now: &SystemTime,
+        options: &TableOptions,
+        history: &History,
+        labels: &Labels,
+    ) -> Self {
         Self {
             lang: value.lang.to_string(),
+            language: value.lang,
             time: value.last_update,
-            human_time: value.last_update.map(|t| {
-                DateTime::<Local>::from(t)
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string()
-            }),
-            path: value.path.display().to_string(),
-            human_size: format_size(value.size, DECIMAL),
+            human_time: format_last_update(now, value.last_update, options.time_format),
+            path: if value.orphaned {
+                format!("{} {ORPHANED_MARK}", value.path.display())
+            } else {
+                value.path.display().to_string()
+            },
+            human_size: format_size_in(value.size, options.unit),
             size: value.size,
+            human_delta: history
+                .delta(&value.path, value.size)
+                .map(|delta| format_delta(delta, options.unit)),
+            clean_command: Some(value.clean_command()),
+            label: labels.get(&value.path).map(String::from),
         }
     }
 }
 
-impl From<&ToolingResult> for ToolingRecord {
-    fn from(value: &ToolingResult) -> Self {
+impl ToolingRecord {
+    fn new(
+        value: &ToolingResult,
+        now: &SystemTime,
+        options: &TableOptions,
+        history: &History,
+    ) -> Self {
         Self {
             description: value.description,
             record: Record {
                 lang: value.lang.to_string(),
+                language: value.lang,
                 time: value.last_update,
-                human_time: value.last_update.map(|t| {
-                    DateTime::<Local>::from(t)
-                        .format("%Y-%m-%d %H:%M:%S")
-                        .to_string()
-                }),
+                human_time: format_last_update(now, value.last_update, options.time_format),
                 path: value.path.display().to_string(),
-                human_size: format_size(value.size, DECIMAL),
+                human_size: format_size_in(value.size, options.unit),
                 size: value.size,
+                human_delta: history
+                    .delta(&value.path, value.size)
+                    .map(|delta| format_delta(delta, options.unit)),
+                clean_command: value.clean_command(),
+                label: None,
             },
         }
     }
 }
 
-fn size_color_coded(size: u64) -> Color {
-    match get_size_color_code(size) {
+fn size_color_coded(size: u64, stats: Option<&SizeStats>) -> Color {
+    match get_size_color_code(size, stats) {
         ColorCode::None => Color::FG_WHITE,
         ColorCode::Low => Color::FG_GREEN,
         ColorCode::Medium => Color::FG_YELLOW,
@@ -143,7 +809,44 @@ fn time_color_coded(now: &SystemTime, time: &Option<SystemTime>) -> Color {
     }
 }
 
-pub fn display_progress_bar(events_receiver: Receiver<ProgressEvent>) {
+/// Number of loaded paths between two lines of the plain progress log
+const PROGRESS_LOG_INTERVAL: usize = 50_000;
+
+/// Show the progress as a bar in a terminal, or as occasional plain lines when redirected
+pub fn display_progress(events_receiver: Receiver<ProgressEvent>) {
+    if io::stderr().is_terminal() {
+        display_progress_bar(events_receiver);
+    } else {
+        display_progress_log(&mut io::stderr(), events_receiver);
+    }
+}
+
+/// Write the progress as plain lines, suitable for logs of non-interactive runs
+fn display_progress_log<W: Write>(out: &mut W, events_receiver: Receiver<ProgressEvent>) {
+    let mut loaded = 0;
+    for event in events_receiver.iter() {
+        let line = match event {
+            ProgressEvent::WalkStart { count } => format!("Scanning {count} paths"),
+            ProgressEvent::WalkAdvance => {
+                loaded += 1;
+                if loaded % PROGRESS_LOG_INTERVAL != 0 {
+                    continue;
+                }
+                format!("Scanned {loaded} files")
+            }
+            ProgressEvent::WalkFinished => format!("Scan finished: {loaded} files"),
+            ProgressEvent::DiscoveryStart { count } => format!("Checking {count} directories"),
+            ProgressEvent::DiscoveryFinished => String::from("Discovery finished"),
+            ProgressEvent::WalkAddPaths { .. } | ProgressEvent::DiscoveryAdvance { .. } => {
+                continue;
+            }
+        };
+        // Progress is best effort; a closed stderr mustn't stop the scan
+        let _ = writeln!(out, "{line}");
+    }
+}
+
+fn display_progress_bar(events_receiver: Receiver<ProgressEvent>) {
     let mp = MultiProgress::new();
 
     let spinner_style =
@@ -214,13 +917,13 @@ pub fn display_progress_bar(events_receiver: Receiver<ProgressEvent>) {
                 discover_parent = Some(parent);
 
                 let pb = mp.add(ProgressBar::new(count as u64));
-                pb.set_prefix("detectors");
+                pb.set_prefix("directories");
                 pb.set_style(bar_style.clone());
                 detectors_pb = Some(pb);
             }
-            ProgressEvent::DiscoveryAdvance => {
+            ProgressEvent::DiscoveryAdvance { count } => {
                 if let Some(pb) = &detectors_pb {
-                    pb.inc(1);
+                    pb.inc(count as u64);
                 }
             }
             ProgressEvent::DiscoveryFinished => {
@@ -239,22 +942,631 @@ pub fn display_progress_bar(events_receiver: Receiver<ProgressEvent>) {
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use crossbeam::channel;
 
     use super::*;
+    use crate::discovery::ParentInfo;
+
+    fn project(path: &str, parent: Option<&str>, lang: Language, size: u64) -> ProjectResult {
+        ProjectResult {
+            path: PathBuf::from(path),
+            lang,
+            size,
+            allocated_size: size,
+            last_update: None,
+            parent: parent.map(|p| ParentInfo {
+                path: PathBuf::from(p),
+                size: 0,
+                allocated_size: 0,
+            }),
+            vcs_size: None,
+            orphaned: false,
+            root: None,
+        }
+    }
+
+    #[test]
+    fn test_group_by_parent() {
+        let projects = vec![
+            project("/p/foo/target", Some("/p/foo"), Language::Rust, 100),
+            project("/p/foo/node_modules", Some("/p/foo"), Language::JS, 50),
+            project(
+                "/p/foo/web/node_modules",
+                Some("/p/foo/web"),
+                Language::JS,
+                10,
+            ),
+            project("/venv", None, Language::Python, 5),
+        ];
+        let mut history = History::default();
+        history.record(
+            &DiscoveryResults {
+                projects: vec![
+                    project("/p/foo/target", None, Language::Rust, 90),
+                    project("/p/foo/node_modules", None, Language::JS, 50),
+                    project("/venv", None, Language::Python, 6),
+                ],
+                tools: vec![],
+                vcs: vec![],
+                db: None,
+                detector_matches: vec![],
+                truncated: false,
+                scan_roots: vec![],
+            },
+            &SystemTime::now(),
+        );
+
+        let records = group_by_parent(
+            &projects,
+            &SystemTime::now(),
+            &TableOptions::default(),
+            &history,
+            &Labels::default(),
+        );
+        let records = records
+            .iter()
+            .map(|r| {
+                (
+                    r.lang.as_str(),
+                    r.path.as_str(),
+                    r.size,
+                    r.human_delta.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            vec![
+                ("🦀🟨", "/p/foo (2 items)", 150, Some("+10 B")),
+                ("🟨", "/p/foo/web/node_modules", 10, None),
+                ("🐍", "/venv", 5, Some("-1 B")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_print_results_top() {
+        let results = DiscoveryResults {
+            projects: vec![
+                project("/p/small/target", None, Language::Rust, 1_000),
+                project("/p/big/target", None, Language::Rust, 3_000_000),
+                project("/p/medium/venv", None, Language::Python, 2_000_000),
+                project("/p/tiny/venv", None, Language::Python, 500),
+            ],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+
+        let mut buffer = Vec::new();
+        let options = TableOptions {
+            top: Some(2),
+            ..TableOptions::default()
+        };
+        print_results(
+            &mut buffer,
+            results,
+            &options,
+            &History::default(),
+            &Labels::default(),
+        );
+
+        let output = String::from_utf8(buffer).unwrap();
+        let big = output.find("/p/big/target").unwrap();
+        let medium = output.find("/p/medium/venv").unwrap();
+        assert!(big < medium);
+        assert!(!output.contains("/p/small/target"));
+        assert!(!output.contains("/p/tiny/venv"));
+        assert!(output.contains("… and 2 more (1.50 kB total)"));
+        // The total still covers all the results
+        assert!(output.contains("5.00 MB"));
+    }
+
+    #[test]
+    fn test_print_results_root_subtotals() {
+        let in_root = |path: &str, root: &str, size: u64| ProjectResult {
+            root: Some(PathBuf::from(root)),
+            ..project(path, None, Language::Rust, size)
+        };
+        let projects = vec![
+            in_root("/home/projects/a/target", "/home/projects", 1_000_000),
+            in_root("/home/projects/b/target", "/home/projects", 2_000_000),
+            in_root("/home/work/c/target", "/home/work", 4_000_000),
+        ];
+        assert_eq!(
+            root_subtotals(&projects),
+            vec![
+                (PathBuf::from("/home/work"), 4_000_000),
+                (PathBuf::from("/home/projects"), 3_000_000),
+            ]
+        );
+
+        let results = DiscoveryResults {
+            projects,
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+        let mut buffer = Vec::new();
+        print_results(
+            &mut buffer,
+            results,
+            &TableOptions::default(),
+            &History::default(),
+            &Labels::default(),
+        );
+        let output = String::from_utf8(buffer).unwrap();
+        let work = output.find("/home/work: 4 MB").unwrap();
+        let projects_subtotal = output.find("/home/projects: 3 MB").unwrap();
+        let total = output.find("7 MB").unwrap();
+        assert!(work < projects_subtotal && projects_subtotal < total);
+
+        // A single root would just repeat the total
+        let single = [in_root("/home/projects/a/target", "/home/projects", 1)];
+        assert!(root_subtotals(&single).is_empty());
+    }
+
+    #[test]
+    fn test_print_results_fixed_unit() {
+        let results = DiscoveryResults {
+            projects: vec![
+                project("/p/big/target", None, Language::Rust, 1_536_000_000),
+                project("/p/small/venv", None, Language::Python, 2_000),
+            ],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+        let options = TableOptions {
+            unit: Some(SizeUnit::MB),
+            ..TableOptions::default()
+        };
+
+        let mut buffer = Vec::new();
+        print_results(
+            &mut buffer,
+            results,
+            &options,
+            &History::default(),
+            &Labels::default(),
+        );
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("0.0 MB"));
+        // The total is printed in the same unit
+        assert_eq!(output.matches("1536.0 MB").count(), 2);
+        assert!(!output.contains("GB"));
+    }
+
+    #[test]
+    fn test_print_results_labels() {
+        let results = |group_by| {
+            let projects = vec![
+                project("/p/old/target", Some("/p/old"), Language::Rust, 100),
+                project("/p/old/node_modules", Some("/p/old"), Language::JS, 50),
+                project("/p/new/target", Some("/p/new"), Language::Rust, 10),
+            ];
+            let results = DiscoveryResults {
+                projects,
+                tools: vec![],
+                vcs: vec![],
+                db: None,
+                detector_matches: vec![],
+                truncated: false,
+                scan_roots: vec![],
+            };
+            let options = TableOptions {
+                group_by,
+                hide_tooling: true,
+                ..TableOptions::default()
+            };
+            (results, options)
+        };
+        let labels = Labels::new([(PathBuf::from("/p/old"), String::from("Old hobby project"))]);
+        let print = |group_by, labels: &Labels| {
+            let (results, options) = results(group_by);
+            let mut buffer = Vec::new();
+            print_results(&mut buffer, results, &options, &History::default(), labels);
+            String::from_utf8(buffer).unwrap()
+        };
+
+        let output = print(None, &labels);
+        assert!(output.contains(LABEL_COLUMN));
+        assert_eq!(output.matches("Old hobby project").count(), 2);
+
+        let output = print(Some(GroupBy::Parent), &labels);
+        assert_eq!(output.matches("Old hobby project").count(), 1);
+
+        let output = print(None, &Labels::default());
+        assert!(!output.contains(LABEL_COLUMN));
+    }
+
+    #[test]
+    fn test_print_results_with_commands() {
+        let results = || DiscoveryResults {
+            projects: vec![project("/p/a/node_modules", None, Language::JS, 100)],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+
+        let mut buffer = Vec::new();
+        print_results(
+            &mut buffer,
+            results(),
+            &TableOptions::default(),
+            &History::default(),
+            &Labels::default(),
+        );
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(!output.contains(CLEAN_COLUMN));
+        assert!(!output.contains("rm -rf"));
+
+        let mut buffer = Vec::new();
+        let options = TableOptions {
+            with_commands: true,
+            ..TableOptions::default()
+        };
+        print_results(
+            &mut buffer,
+            results(),
+            &options,
+            &History::default(),
+            &Labels::default(),
+        );
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains(CLEAN_COLUMN));
+        assert!(output.contains("rm -rf /p/a/node_modules"));
+    }
+
+    #[test]
+    fn test_print_results_hidden_tables() {
+        let results = || DiscoveryResults {
+            projects: vec![project("/p/a/target", None, Language::Rust, 100)],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+
+        let mut buffer = Vec::new();
+        let options = TableOptions {
+            hide_tooling: true,
+            ..TableOptions::default()
+        };
+        print_results(
+            &mut buffer,
+            results(),
+            &options,
+            &History::default(),
+            &Labels::default(),
+        );
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(!output.contains("Tooling"));
+        assert!(output.contains("/p/a/target"));
+
+        let mut buffer = Vec::new();
+        let options = TableOptions {
+            hide_projects: true,
+            ..TableOptions::default()
+        };
+        print_results(
+            &mut buffer,
+            results(),
+            &options,
+            &History::default(),
+            &Labels::default(),
+        );
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("Tooling"));
+        assert!(!output.contains("/p/a/target"));
+    }
+
+    #[test]
+    fn test_print_results_empty_tooling_is_greyed_out() {
+        let tool = |path: &str, size: u64| ToolingResult {
+            description: "cache",
+            path: PathBuf::from(path),
+            lang: Language::Python,
+            size,
+            allocated_size: size,
+            last_update: None,
+            info: None,
+            clean: None,
+        };
+        let results = DiscoveryResults {
+            projects: vec![],
+            tools: vec![
+                tool("/home/.cache/found", 1_000),
+                tool("/home/.cache/missing", 0),
+            ],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+
+        let mut buffer = Vec::new();
+        print_results(
+            &mut buffer,
+            results,
+            &TableOptions::default(),
+            &History::default(),
+            &Labels::default(),
+        );
+        let output = String::from_utf8(buffer).unwrap();
+        let grey = Color::FG_BRIGHT_BLACK;
+        let line = |path| output.lines().find(|l| l.contains(path)).unwrap();
+        assert!(line("/home/.cache/missing").contains(grey.get_prefix()));
+        assert!(!line("/home/.cache/found").contains(grey.get_prefix()));
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let results = || DiscoveryResults {
+            projects: vec![
+                project("/p/small/target", None, Language::Rust, 10),
+                project("/p/big/node_modules", None, Language::JS, 300),
+                project("/p/venv", None, Language::Python, 100),
+                project("/p/big/target", None, Language::Rust, 200),
+                project("/p/tie/node_modules", None, Language::JS, 100),
+            ],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+        let order = |sort: SortOrder| {
+            let mut buffer = Vec::new();
+            let options = TableOptions {
+                sort,
+                ..TableOptions::default()
+            };
+            print_results(
+                &mut buffer,
+                results(),
+                &options,
+                &History::default(),
+                &Labels::default(),
+            );
+            let output = String::from_utf8(buffer).unwrap();
+            let mut paths = results()
+                .projects
+                .into_iter()
+                .map(|p| p.path.display().to_string())
+                .collect::<Vec<_>>();
+            paths.sort_by_key(|path| output.find(&format!("{path} ")).unwrap());
+            paths
+        };
+
+        assert_eq!(
+            order(SortOrder::Size),
+            vec![
+                "/p/big/node_modules",
+                "/p/big/target",
+                "/p/venv",
+                "/p/tie/node_modules",
+                "/p/small/target",
+            ]
+        );
+        assert_eq!(
+            order(SortOrder::Lang),
+            vec![
+                "/p/venv",
+                "/p/big/target",
+                "/p/small/target",
+                "/p/big/node_modules",
+                "/p/tie/node_modules",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_orphaned_venv_is_marked() {
+        let mut orphaned = project("/venvs/gone-Ef56Gh78", None, Language::Python, 10);
+        orphaned.orphaned = true;
+        let results = DiscoveryResults {
+            projects: vec![orphaned, project("/p/a/.venv", None, Language::Python, 10)],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+
+        let mut buffer = Vec::new();
+        print_results(
+            &mut buffer,
+            results,
+            &TableOptions::default(),
+            &History::default(),
+            &Labels::default(),
+        );
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("/venvs/gone-Ef56Gh78 (orphaned)"));
+        assert!(!output.contains("/p/a/.venv (orphaned)"));
+    }
+
+    #[test]
+    fn test_print_tree() {
+        let results = DiscoveryResults {
+            projects: vec![
+                project("/home/p/a/target", None, Language::Rust, 100),
+                project("/home/p/b/web/node_modules", None, Language::JS, 300),
+                project("/home/p/b/venv", None, Language::Python, 50),
+            ],
+            tools: vec![ToolingResult {
+                description: "uv cache",
+                path: PathBuf::from("/home/.cache/uv"),
+                lang: Language::Python,
+                size: 1_000,
+                allocated_size: 1_000,
+                last_update: None,
+                info: None,
+                clean: None,
+            }],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+        let (rust, js, python) = (Language::Rust, Language::JS, Language::Python);
+
+        let mut buffer = Vec::new();
+        print_tree(&mut buffer, &results, &TableOptions::default());
+        let expected = format!(
+            "/home (1.45 kB)
+├── {python} .cache/uv (1 kB)
+└── p (450 B)
+    ├── b (350 B)
+    │   ├── {js} web/node_modules (300 B)
+    │   └── {python} venv (50 B)
+    └── {rust} a/target (100 B)
+"
+        );
+        assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+
+        let mut buffer = Vec::new();
+        let options = TableOptions {
+            hide_tooling: true,
+            ..TableOptions::default()
+        };
+        print_tree(&mut buffer, &results, &options);
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.starts_with("/home/p (450 B)\n"));
+        assert!(!output.contains(".cache"));
+    }
+
+    #[test]
+    fn test_print_duplicates() {
+        let group = |name: &str, size: u64| DuplicateGroup {
+            size,
+            paths: vec![
+                PathBuf::from("/p/a").join(name),
+                PathBuf::from("/p/b").join(name),
+            ],
+        };
+        let groups = vec![group("big.bin", 2_000_000), group("small.bin", 1_000_000)];
+
+        let mut buffer = Vec::new();
+        print_duplicates(&mut buffer, &groups, Some(1), None);
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert!(output.contains("Duplicate files: 3 MB reclaimable in 2 groups"));
+        assert!(output.contains("2 MB reclaimable (2 copies of 2 MB)"));
+        assert!(output.contains("/p/b/big.bin"));
+        assert!(!output.contains("/p/a/small.bin"));
+        assert!(output.contains("… and 1 more groups (1 MB reclaimable)"));
+    }
+
+    #[test]
+    fn test_print_largest_dirs() {
+        let dirs = vec![
+            DirSize {
+                size: 5_000_000,
+                path: PathBuf::from("/data/media"),
+            },
+            DirSize {
+                size: 1_200,
+                path: PathBuf::from("/data/logs"),
+            },
+        ];
+
+        let mut buffer = Vec::new();
+        print_largest_dirs(&mut buffer, &dirs, None);
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert!(output.contains("Largest directories"));
+        let media = output.find("/data/media").unwrap();
+        let logs = output.find("/data/logs").unwrap();
+        assert!(media < logs);
+        assert!(output.contains("5 MB"));
+        assert!(output.contains("1.20 kB"));
+
+        let mut buffer = Vec::new();
+        print_largest_dirs(&mut buffer, &dirs, Some(SizeUnit::KB));
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("5000.0 kB"));
+        assert!(output.contains("1.2 kB"));
+    }
+
+    #[test]
+    fn test_language_summary() {
+        let results = DiscoveryResults {
+            projects: vec![
+                project("/p/a/target", None, Language::Rust, 100),
+                project("/p/b/target", None, Language::Rust, 200),
+                project("/p/c/venv", None, Language::Python, 50),
+            ],
+            tools: vec![ToolingResult {
+                description: "uv cache",
+                path: PathBuf::from("/home/.cache/uv"),
+                lang: Language::Python,
+                size: 1_000,
+                allocated_size: 1_000,
+                last_update: None,
+                info: None,
+                clean: None,
+            }],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        };
+
+        let summary = language_summary(&results);
+        let rows = summary
+            .iter()
+            .map(|r| (r.lang, r.projects, r.projects_size, r.tooling_size, r.total))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                (Language::Python, 1, 50, 1_000, 1_050),
+                (Language::Rust, 2, 300, 0, 300),
+            ]
+        );
+
+        let mut buffer = Vec::new();
+        print_language_summary(&mut buffer, &summary, None);
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("By language"));
+        assert!(output.contains("1.05 kB"));
+    }
 
     #[test]
     fn test_size_color_coding() {
-        assert_eq!(size_color_coded(1000), Color::FG_GREEN);
-        assert_eq!(size_color_coded(1000 * 1000), Color::FG_GREEN);
-        assert_eq!(size_color_coded(50 * 1000 * 1000), Color::FG_GREEN);
-        assert_eq!(size_color_coded(80 * 1000 * 1000), Color::FG_GREEN);
-        assert_eq!(size_color_coded(90 * 1000 * 1000), Color::FG_YELLOW);
-        assert_eq!(size_color_coded(100 * 1000 * 1000), Color::FG_YELLOW);
-        assert_eq!(size_color_coded(500 * 1000 * 1000), Color::FG_YELLOW);
-        assert_eq!(size_color_coded(800 * 1000 * 1000), Color::FG_YELLOW);
-        assert_eq!(size_color_coded(900 * 1000 * 1000), Color::FG_RED);
-        assert_eq!(size_color_coded(1000 * 1000 * 1000), Color::FG_RED);
+        assert_eq!(size_color_coded(1000, None), Color::FG_GREEN);
+        assert_eq!(size_color_coded(1000 * 1000, None), Color::FG_GREEN);
+        assert_eq!(size_color_coded(50 * 1000 * 1000, None), Color::FG_GREEN);
+        assert_eq!(size_color_coded(80 * 1000 * 1000, None), Color::FG_GREEN);
+        assert_eq!(size_color_coded(90 * 1000 * 1000, None), Color::FG_YELLOW);
+        assert_eq!(size_color_coded(100 * 1000 * 1000, None), Color::FG_YELLOW);
+        assert_eq!(size_color_coded(500 * 1000 * 1000, None), Color::FG_YELLOW);
+        assert_eq!(size_color_coded(800 * 1000 * 1000, None), Color::FG_YELLOW);
+        assert_eq!(size_color_coded(900 * 1000 * 1000, None), Color::FG_RED);
+        assert_eq!(size_color_coded(1000 * 1000 * 1000, None), Color::FG_RED);
     }
 
     #[test]
@@ -288,6 +1600,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_display_progress_log() {
+        let (tx, rx) = channel::unbounded();
+
+        tx.send(ProgressEvent::WalkStart { count: 2 }).unwrap();
+        tx.send(ProgressEvent::WalkAddPaths {
+            count: PROGRESS_LOG_INTERVAL + 1,
+        })
+        .unwrap();
+        for _ in 0..PROGRESS_LOG_INTERVAL + 1 {
+            tx.send(ProgressEvent::WalkAdvance).unwrap();
+        }
+        tx.send(ProgressEvent::WalkFinished).unwrap();
+        tx.send(ProgressEvent::DiscoveryStart { count: 3 }).unwrap();
+        tx.send(ProgressEvent::DiscoveryAdvance { count: 3 })
+            .unwrap();
+        tx.send(ProgressEvent::DiscoveryFinished).unwrap();
+        drop(tx);
+
+        let mut buffer = Vec::new();
+        display_progress_log(&mut buffer, rx);
+
+        let output = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            vec![
+                "Scanning 2 paths",
+                "Scanned 50000 files",
+                "Scan finished: 50001 files",
+                "Checking 3 directories",
+                "Discovery finished",
+            ]
+        );
+    }
+
     #[test]
     fn test_display_progress_bar_consumes_messages() {
         // This test has lower value. It just tests, that display_progress_bar
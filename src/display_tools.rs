@@ -1,5 +1,9 @@
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
+use chrono::{DateTime, Local};
+use humansize::{DECIMAL, format_size};
+
 #[derive(Debug, PartialEq)]
 pub enum ColorCode {
     None,
@@ -8,7 +12,152 @@ pub enum ColorCode {
     High,
 }
 
-pub fn get_size_color_code(size: u64) -> ColorCode {
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// Date and time, e.g. `2024-01-02 15:04:05`
+    #[default]
+    Absolute,
+    /// Time elapsed since now, e.g. `3 months ago`
+    Relative,
+}
+
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Fixed thresholds (90 MB and 900 MB)
+    #[default]
+    Absolute,
+    /// Thresholds derived from the displayed results (median and 90th percentile)
+    Relative,
+}
+
+/// Fixed unit of the printed sizes, decimal like the automatically scaled ones
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnit {
+    B,
+    KB,
+    MB,
+    GB,
+}
+
+impl SizeUnit {
+    fn bytes(&self) -> u64 {
+        match self {
+            SizeUnit::B => 1,
+            SizeUnit::KB => 1000,
+            SizeUnit::MB => 1000 * 1000,
+            SizeUnit::GB => 1000 * 1000 * 1000,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            SizeUnit::B => "B",
+            SizeUnit::KB => "kB",
+            SizeUnit::MB => "MB",
+            SizeUnit::GB => "GB",
+        }
+    }
+}
+
+/// Format `size` in the fixed `unit`, or scaled to its magnitude without any
+pub fn format_size_in(size: u64, unit: Option<SizeUnit>) -> String {
+    match unit {
+        None => format_size(size, DECIMAL),
+        Some(SizeUnit::B) => format!("{size} B"),
+        Some(unit) => format!("{:.1} {}", size as f64 / unit.bytes() as f64, unit.symbol()),
+    }
+}
+
+/// Size thresholds computed from a result set for the relative color mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeStats {
+    median: u64,
+    p90: u64,
+}
+
+impl SizeStats {
+    pub fn from_sizes(sizes: impl IntoIterator<Item = u64>) -> Option<Self> {
+        let mut sizes: Vec<u64> = sizes.into_iter().collect();
+        if sizes.is_empty() {
+            return None;
+        }
+        sizes.sort_unstable();
+        let percentile = |p: usize| sizes[(sizes.len() - 1) * p / 100];
+
+        Some(Self {
+            median: percentile(50),
+            p90: percentile(90),
+        })
+    }
+}
+
+pub fn format_time(now: &SystemTime, time: SystemTime, format: TimeFormat) -> String {
+    match format {
+        TimeFormat::Absolute => DateTime::<Local>::from(time)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        TimeFormat::Relative => humanize_age(now, Some(time)),
+    }
+}
+
+/// Last update in the given format for display; `None` only for an unknown time shown as a date
+pub fn format_last_update(
+    now: &SystemTime,
+    time: Option<SystemTime>,
+    format: TimeFormat,
+) -> Option<String> {
+    match format {
+        TimeFormat::Absolute => time.map(|t| format_time(now, t, format)),
+        TimeFormat::Relative => Some(humanize_age(now, time)),
+    }
+}
+
+/// Time elapsed since `time`, e.g. `2 days ago`, `just now` or `unknown`
+///
+/// Months are approximated by 30 days and years by 365 days.
+pub fn humanize_age(now: &SystemTime, time: Option<SystemTime>) -> String {
+    let Some(time) = time else {
+        return String::from("unknown");
+    };
+    let elapsed =
+        DateTime::<Local>::from(*now).signed_duration_since(DateTime::<Local>::from(time));
+    if elapsed.num_seconds() < 0 {
+        return String::from("in the future");
+    }
+
+    let (count, unit) = if elapsed.num_minutes() < 1 {
+        return String::from("just now");
+    } else if elapsed.num_hours() < 1 {
+        (elapsed.num_minutes(), "minute")
+    } else if elapsed.num_days() < 1 {
+        (elapsed.num_hours(), "hour")
+    } else if elapsed.num_days() < 30 {
+        (elapsed.num_days(), "day")
+    } else if elapsed.num_days() < 365 {
+        (elapsed.num_days() / 30, "month")
+    } else {
+        (elapsed.num_days() / 365, "year")
+    };
+
+    if count == 1 {
+        format!("1 {unit} ago")
+    } else {
+        format!("{count} {unit}s ago")
+    }
+}
+
+/// Color code of the size; relative to the result set when `stats` are given
+pub fn get_size_color_code(size: u64, stats: Option<&SizeStats>) -> ColorCode {
+    if let Some(stats) = stats {
+        return if size <= stats.median {
+            ColorCode::Low
+        } else if size <= stats.p90 {
+            ColorCode::Medium
+        } else {
+            ColorCode::High
+        };
+    }
+
     if size < 1000 * 1000 * 90 {
         ColorCode::Low
     } else if size < 1000 * 1000 * 900 {
@@ -18,6 +167,58 @@ pub fn get_size_color_code(size: u64) -> ColorCode {
     }
 }
 
+/// Signed size change, e.g. `+420 MB` or `-1.1 GB`, optionally in a fixed unit
+pub fn format_delta(delta: i64, unit: Option<SizeUnit>) -> String {
+    let size = format_size_in(delta.unsigned_abs(), unit);
+    match delta {
+        0 => size,
+        1.. => format!("+{size}"),
+        _ => format!("-{size}"),
+    }
+}
+
+/// Selects results which weren't updated for the given number of days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleFilter {
+    pub days: u64,
+    /// Results with unknown last update are selected as well
+    pub include_undated: bool,
+}
+
+impl StaleFilter {
+    /// Threshold used when no explicit number of days is given, the same as the oldest time color
+    pub const DEFAULT_DAYS: u64 = 60;
+
+    /// Last update exactly `days` ago is already stale
+    pub fn is_stale(&self, now: &SystemTime, time: &Option<SystemTime>) -> bool {
+        match time {
+            None => self.include_undated,
+            Some(system_time) => now
+                .duration_since(*system_time)
+                .is_ok_and(|duration| duration >= Duration::from_days(self.days)),
+        }
+    }
+}
+
+/// Friendly names of configured paths, shown next to the results inside them
+#[derive(Debug, Default, Clone)]
+pub struct Labels(Vec<(PathBuf, String)>);
+
+impl Labels {
+    pub fn new(labels: impl IntoIterator<Item = (PathBuf, String)>) -> Self {
+        Self(labels.into_iter().collect())
+    }
+
+    /// Label of the longest configured path containing `path`
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        self.0
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.components().count())
+            .map(|(_, label)| label.as_str())
+    }
+}
+
 pub fn get_time_color_code(now: &SystemTime, time: &Option<SystemTime>) -> ColorCode {
     match time {
         None => ColorCode::None, // Wouldn't be displayed anyway
@@ -40,18 +241,193 @@ pub fn get_time_color_code(now: &SystemTime, time: &Option<SystemTime>) -> Color
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_size_in() {
+        assert_eq!(format_size_in(1_536_000_000, None), "1.54 GB");
+        assert_eq!(
+            format_size_in(1_536_000_000, Some(SizeUnit::MB)),
+            "1536.0 MB"
+        );
+        assert_eq!(format_size_in(1_536_000_000, Some(SizeUnit::GB)), "1.5 GB");
+        assert_eq!(format_size_in(2_500, Some(SizeUnit::KB)), "2.5 kB");
+        assert_eq!(format_size_in(2_500, Some(SizeUnit::B)), "2500 B");
+        assert_eq!(format_size_in(0, Some(SizeUnit::MB)), "0.0 MB");
+    }
+
+    #[test]
+    fn test_labels_longest_prefix() {
+        let labels = Labels::new([
+            (
+                PathBuf::from("/home/foo/projects"),
+                String::from("Projects"),
+            ),
+            (
+                PathBuf::from("/home/foo/projects/old"),
+                String::from("Old hobby project"),
+            ),
+        ]);
+
+        assert_eq!(
+            labels.get(Path::new("/home/foo/projects/old/target")),
+            Some("Old hobby project")
+        );
+        assert_eq!(
+            labels.get(Path::new("/home/foo/projects/new/target")),
+            Some("Projects")
+        );
+        // Prefixes match whole path components only
+        assert_eq!(
+            labels.get(Path::new("/home/foo/projects-archive/target")),
+            None
+        );
+        assert_eq!(Labels::default().get(Path::new("/home/foo")), None);
+    }
+
     #[test]
     fn test_size_color_coding() {
-        assert_eq!(get_size_color_code(1000), ColorCode::Low);
-        assert_eq!(get_size_color_code(1000 * 1000), ColorCode::Low);
-        assert_eq!(get_size_color_code(50 * 1000 * 1000), ColorCode::Low);
-        assert_eq!(get_size_color_code(80 * 1000 * 1000), ColorCode::Low);
-        assert_eq!(get_size_color_code(90 * 1000 * 1000), ColorCode::Medium);
-        assert_eq!(get_size_color_code(100 * 1000 * 1000), ColorCode::Medium);
-        assert_eq!(get_size_color_code(500 * 1000 * 1000), ColorCode::Medium);
-        assert_eq!(get_size_color_code(800 * 1000 * 1000), ColorCode::Medium);
-        assert_eq!(get_size_color_code(900 * 1000 * 1000), ColorCode::High);
-        assert_eq!(get_size_color_code(1000 * 1000 * 1000), ColorCode::High);
+        assert_eq!(get_size_color_code(1000, None), ColorCode::Low);
+        assert_eq!(get_size_color_code(1000 * 1000, None), ColorCode::Low);
+        assert_eq!(get_size_color_code(50 * 1000 * 1000, None), ColorCode::Low);
+        assert_eq!(get_size_color_code(80 * 1000 * 1000, None), ColorCode::Low);
+        assert_eq!(
+            get_size_color_code(90 * 1000 * 1000, None),
+            ColorCode::Medium
+        );
+        assert_eq!(
+            get_size_color_code(100 * 1000 * 1000, None),
+            ColorCode::Medium
+        );
+        assert_eq!(
+            get_size_color_code(500 * 1000 * 1000, None),
+            ColorCode::Medium
+        );
+        assert_eq!(
+            get_size_color_code(800 * 1000 * 1000, None),
+            ColorCode::Medium
+        );
+        assert_eq!(
+            get_size_color_code(900 * 1000 * 1000, None),
+            ColorCode::High
+        );
+        assert_eq!(
+            get_size_color_code(1000 * 1000 * 1000, None),
+            ColorCode::High
+        );
+    }
+
+    #[test]
+    fn test_relative_size_color_coding() {
+        assert_eq!(SizeStats::from_sizes([]), None);
+
+        // Everything is multi-GB; the absolute thresholds would mark all of it red
+        let gb = 1000 * 1000 * 1000;
+        let stats = SizeStats::from_sizes((1..=10).map(|i| i * gb)).unwrap();
+        let code = |size| get_size_color_code(size, Some(&stats));
+        assert_eq!(code(gb), ColorCode::Low);
+        assert_eq!(code(5 * gb), ColorCode::Low);
+        assert_eq!(code(6 * gb), ColorCode::Medium);
+        assert_eq!(code(9 * gb), ColorCode::Medium);
+        assert_eq!(code(10 * gb), ColorCode::High);
+
+        let stats = SizeStats::from_sizes([42]).unwrap();
+        assert_eq!(get_size_color_code(42, Some(&stats)), ColorCode::Low);
+    }
+
+    #[test]
+    fn test_relative_time_format() {
+        let now = SystemTime::now();
+        let relative = |d: Duration| format_time(&now, now - d, TimeFormat::Relative);
+
+        assert_eq!(relative(Duration::from_secs(10)), "just now");
+        assert_eq!(relative(Duration::from_mins(1)), "1 minute ago");
+        assert_eq!(relative(Duration::from_mins(5)), "5 minutes ago");
+        assert_eq!(relative(Duration::from_hours(3)), "3 hours ago");
+        assert_eq!(relative(Duration::from_days(1)), "1 day ago");
+        assert_eq!(relative(Duration::from_days(29)), "29 days ago");
+        assert_eq!(relative(Duration::from_days(95)), "3 months ago");
+        assert_eq!(relative(Duration::from_days(800)), "2 years ago");
+        assert_eq!(
+            format_time(&now, now + Duration::from_days(1), TimeFormat::Relative),
+            "in the future"
+        );
+    }
+
+    #[test]
+    fn test_humanize_age_boundaries() {
+        let now = SystemTime::now();
+        let age = |d: Duration| humanize_age(&now, Some(now - d));
+
+        assert_eq!(humanize_age(&now, None), "unknown");
+        assert_eq!(humanize_age(&now, Some(now)), "just now");
+        assert_eq!(
+            humanize_age(&now, Some(now + Duration::from_secs(1))),
+            "in the future"
+        );
+
+        assert_eq!(age(Duration::from_secs(59)), "just now");
+        assert_eq!(age(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(age(Duration::from_secs(3599)), "59 minutes ago");
+        assert_eq!(age(Duration::from_hours(1)), "1 hour ago");
+        assert_eq!(age(Duration::from_hours(23)), "23 hours ago");
+        assert_eq!(age(Duration::from_hours(24)), "1 day ago");
+        assert_eq!(age(Duration::from_days(29)), "29 days ago");
+        assert_eq!(age(Duration::from_days(30)), "1 month ago");
+        assert_eq!(age(Duration::from_days(364)), "12 months ago");
+        assert_eq!(age(Duration::from_days(365)), "1 year ago");
+        assert_eq!(age(Duration::from_days(3650)), "10 years ago");
+    }
+
+    #[test]
+    fn test_format_last_update() {
+        let now = SystemTime::now();
+        let time = Some(now - Duration::from_days(2));
+
+        assert_eq!(
+            format_last_update(&now, time, TimeFormat::Relative).as_deref(),
+            Some("2 days ago")
+        );
+        assert_eq!(
+            format_last_update(&now, None, TimeFormat::Relative).as_deref(),
+            Some("unknown")
+        );
+        assert!(format_last_update(&now, time, TimeFormat::Absolute).is_some());
+        assert_eq!(format_last_update(&now, None, TimeFormat::Absolute), None);
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(420_000_000, None), "+420 MB");
+        assert_eq!(format_delta(-1_100_000_000, None), "-1.10 GB");
+        assert_eq!(format_delta(0, None), "0 B");
+        assert_eq!(
+            format_delta(-1_100_000_000, Some(SizeUnit::MB)),
+            "-1100.0 MB"
+        );
+    }
+
+    #[test]
+    fn test_stale_filter_boundary() {
+        let now = SystemTime::now();
+        let filter = StaleFilter {
+            days: 30,
+            include_undated: false,
+        };
+
+        assert!(!filter.is_stale(&now, &Some(now - Duration::from_days(29))));
+        assert!(!filter.is_stale(
+            &now,
+            &Some(now - Duration::from_days(30) + Duration::from_secs(1))
+        ));
+        assert!(filter.is_stale(&now, &Some(now - Duration::from_days(30))));
+        assert!(filter.is_stale(&now, &Some(now - Duration::from_days(31))));
+        assert!(!filter.is_stale(&now, &Some(now + Duration::from_days(1))));
+
+        assert!(!filter.is_stale(&now, &None));
+        let filter = StaleFilter {
+            include_undated: true,
+            ..filter
+        };
+        assert!(filter.is_stale(&now, &None));
     }
 
     #[test]
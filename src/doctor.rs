@@ -0,0 +1,199 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use clap::ValueEnum;
+use owo_colors::OwoColorize;
+
+use crate::cli::DoctorArgs;
+use crate::config::{
+    PathDefinition, find_config_file, get_config_file_candidates, load_config_file,
+    requested_config_path, resolve_home_path,
+};
+use crate::discovery::default_discovery_definitions;
+
+pub fn doctor(cmd_args: &DoctorArgs, home_dir: Option<&Path>) {
+    doctor_inner(&mut io::stdout(), cmd_args, home_dir)
+}
+
+fn doctor_inner<W: Write>(out: &mut W, cmd_args: &DoctorArgs, home_dir: Option<&Path>) {
+    let Some(home_dir) = home_dir else {
+        writeln!(
+            out,
+            "{} Home directory couldn't be identified",
+            status(false)
+        )
+        .expect("Failed to write to stdout");
+        return;
+    };
+    writeln!(
+        out,
+        "{} Home directory: {}",
+        status(true),
+        home_dir.display().dimmed()
+    )
+    .expect("Failed to write to stdout");
+
+    writeln!(out, "\n{}", "Configuration".bold()).expect("Failed to write to stdout");
+    let config_path = match requested_config_path(cmd_args.config.as_deref()) {
+        Some(path) => Some(path),
+        None => {
+            let candidates = get_config_file_candidates(home_dir);
+            for candidate in candidates.iter() {
+                writeln!(
+                    out,
+                    "  {} candidate: {}",
+                    status(candidate.exists()),
+                    candidate.display().dimmed()
+                )
+                .expect("Failed to write to stdout");
+            }
+            find_config_file(&candidates)
+        }
+    };
+    let config = match config_path {
+        None => {
+            writeln!(
+                out,
+                "  {} No configuration file found. Use 'generate-config' to create one.",
+                status(false)
+            )
+            .expect("Failed to write to stdout");
+            None
+        }
+        Some(path) => match load_config_file(Some(home_dir), Some(&path)) {
+            Ok(config) => {
+                writeln!(
+                    out,
+                    "  {} Using configuration file: {}",
+                    status(true),
+                    path.display().green()
+                )
+                .expect("Failed to write to stdout");
+                Some(config)
+            }
+            Err(e) => {
+                writeln!(out, "  {} {}", status(false), e).expect("Failed to write to stdout");
+                None
+            }
+        },
+    };
+
+    if let Some(config) = config {
+        writeln!(out, "\n{}", "Configured paths".bold()).expect("Failed to write to stdout");
+        if config.paths.is_empty() {
+            writeln!(out, "  {} No paths configured", status(false))
+                .expect("Failed to write to stdout");
+        }
+        for root in config.paths.iter().flat_map(PathDefinition::roots) {
+            let path = resolve_home_path(Some(home_dir), root)
+                .expect("Paths are always resolved with the home directory");
+            writeln!(
+                out,
+                "  {} {}",
+                status(path.exists()),
+                path.display().dimmed()
+            )
+            .expect("Failed to write to stdout");
+        }
+    }
+
+    writeln!(out, "\n{}", "Default definitions".bold()).expect("Failed to write to stdout");
+    for definition in default_discovery_definitions(home_dir).iter() {
+        writeln!(
+            out,
+            "  {} {} {}: {}",
+            status(definition.path.exists()),
+            definition.lang,
+            definition.description,
+            definition.path.display().dimmed()
+        )
+        .expect("Failed to write to stdout");
+    }
+
+    writeln!(out, "\n{}", "Settings".bold()).expect("Failed to write to stdout");
+    let loader = cmd_args
+        .loader
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default();
+    writeln!(out, "  Loader: {loader}").expect("Failed to write to stdout");
+    writeln!(out, "  Threads: {}", rayon::current_num_threads())
+        .expect("Failed to write to stdout");
+}
+
+fn status(ok: bool) -> String {
+    if ok {
+        "✔".green().to_string()
+    } else {
+        "✘".red().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_doctor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("projects")).unwrap();
+        let cfg_data = r#"
+[[paths]]
+path = "projects/"
+
+[[paths]]
+path = "missing/"
+        "#;
+        fs::write(root_path.join("custom.toml"), cfg_data).unwrap();
+
+        let cmd_args = DoctorArgs {
+            config: Some(root_path.join("custom.toml")),
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        doctor_inner(&mut buffer, &cmd_args, Some(root_path));
+
+        let output = String::from_utf8(buffer).unwrap();
+        let line_with = |needle: &str| {
+            output
+                .lines()
+                .find(|l| l.contains(needle))
+                .unwrap()
+                .to_string()
+        };
+        assert!(line_with("Using configuration file").contains("custom.toml"));
+        assert!(line_with(&root_path.join("projects/").display().to_string()).contains("✔"));
+        assert!(line_with(&root_path.join("missing/").display().to_string()).contains("✘"));
+        assert!(line_with(&root_path.join(".cache/uv").display().to_string()).contains("✘"));
+        assert!(output.contains("Loader: parallel"));
+    }
+
+    #[test]
+    fn test_doctor_reports_missing_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+
+        let cmd_args = DoctorArgs {
+            config: Some(root_path.join("custom.toml")),
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        doctor_inner(&mut buffer, &cmd_args, Some(root_path));
+
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("doesn't exist"));
+        assert!(!output.contains("Configured paths"));
+    }
+
+    #[test]
+    fn test_doctor_without_home() {
+        let mut buffer = Vec::new();
+        doctor_inner(&mut buffer, &DoctorArgs::default(), None);
+
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("Home directory couldn't be identified"));
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use tracing::{debug, debug_span};
+
+use crate::files_db::FilesDB;
+
+/// Only the beginning of the files is compared first, which separates most of the
+/// files of the same size without reading them completely
+const PREFIX_LEN: u64 = 16 * 1024;
+
+/// Files with the same content
+#[derive(Debug, PartialEq)]
+pub struct DuplicateGroup {
+    /// Size of a single copy
+    pub size: u64,
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Space freed by keeping just a single copy
+    pub fn reclaimable(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+/// Find files of at least `min_size` bytes with the same content, the most wasteful first
+///
+/// Only files sharing their size are read, first just their beginning, then the rest of
+/// the files still matching. Hard links of the same file aren't reported, as they don't
+/// waste any space.
+pub fn find_duplicates(db: &FilesDB, min_size: u64) -> Vec<DuplicateGroup> {
+    let _guard = debug_span!("find_duplicates", min_size).entered();
+
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for fi in db.iter_all() {
+        if let Some(size) = fi.size
+            && !fi.is_dir
+            && size >= min_size.max(1)
+        {
+            by_size.entry(size).or_default().push(fi.path);
+        }
+    }
+    debug!("Files with a shared size: {}", by_size.len());
+
+    let mut groups: Vec<DuplicateGroup> = by_size
+        .into_par_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .flat_map_iter(|(size, paths)| {
+            let files = split_by(paths, same_file_key)
+                .into_iter()
+                .filter_map(|links| links.into_iter().min())
+                .collect();
+            split_by(files, |path| content_hash(path, Some(PREFIX_LEN)))
+                .into_iter()
+                .filter(|paths| paths.len() > 1)
+                .flat_map(|paths| split_by(paths, |path| content_hash(path, None)))
+                .filter(|paths| paths.len() > 1)
+                .map(move |mut paths| {
+                    paths.sort();
+                    DuplicateGroup { size, paths }
+                })
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.reclaimable()
+            .cmp(&a.reclaimable())
+            .then_with(|| a.paths.cmp(&b.paths))
+    });
+
+    groups
+}
+
+/// Group `paths` by `key`; paths without a key are left out
+fn split_by<K: Hash + Eq>(
+    paths: Vec<PathBuf>,
+    key: impl Fn(&Path) -> io::Result<K>,
+) -> Vec<Vec<PathBuf>> {
+    let mut groups: HashMap<K, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        match key(&path) {
+            Ok(key) => groups.entry(key).or_default().push(path),
+            Err(e) => debug!("Skipping {}: {e}", path.display()),
+        }
+    }
+    groups.into_values().collect()
+}
+
+/// Identity of the file on the disk, shared by its hard links
+#[cfg(unix)]
+fn same_file_key(path: &Path) -> io::Result<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(path)?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn same_file_key(path: &Path) -> io::Result<PathBuf> {
+    Ok(path.to_path_buf())
+}
+
+/// Hash of the content of the file, or of its first `limit` bytes
+fn content_hash(path: &Path, limit: Option<u64>) -> io::Result<u64> {
+    let mut file = File::open(path)?.take(limit.unwrap_or(u64::MAX));
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer[..read]);
+    }
+
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::discovery::{PathLoader, ProgressEvent, ProgressReporter, WalkOptions};
+    use crate::file_info::get_file_meta;
+    use crate::loader::FullyParallelLoader;
+
+    #[derive(Clone)]
+    struct MockReporter;
+
+    impl ProgressReporter for MockReporter {
+        fn report(&self, _event: ProgressEvent) {}
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let content = vec![7; 2 * PREFIX_LEN as usize];
+        let mut different_end = content.clone();
+        *different_end.last_mut().unwrap() = 8;
+        fs::create_dir_all(root.join("a/node_modules")).unwrap();
+        fs::create_dir_all(root.join("b/node_modules")).unwrap();
+        fs::write(root.join("a/node_modules/big.bin"), &content).unwrap();
+        fs::write(root.join("b/node_modules/big.bin"), &content).unwrap();
+        fs::write(root.join("b/other.bin"), &different_end).unwrap();
+        fs::write(root.join("a/small.txt"), "small").unwrap();
+        fs::write(root.join("b/small.txt"), "small").unwrap();
+        let load = || {
+            FullyParallelLoader.load_multiple_paths(
+                &[(root.to_path_buf(), None)],
+                WalkOptions::default(),
+                None::<MockReporter>,
+            )
+        };
+
+        let db = load();
+        let groups = find_duplicates(&db, 1000);
+        assert_eq!(
+            groups,
+            vec![DuplicateGroup {
+                size: content.len() as u64,
+                paths: vec![
+                    root.join("a/node_modules/big.bin"),
+                    root.join("b/node_modules/big.bin"),
+                ],
+            }]
+        );
+        assert_eq!(groups[0].reclaimable(), content.len() as u64);
+
+        let groups = find_duplicates(&db, 1);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups[1].paths,
+            vec![root.join("a/small.txt"), root.join("b/small.txt")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_are_not_duplicates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("file.bin"), vec![7; 1000]).unwrap();
+        fs::hard_link(root.join("file.bin"), root.join("link.bin")).unwrap();
+        let mut db = FilesDB::new();
+        for name in ["file.bin", "link.bin"] {
+            let path = root.join(name);
+            db.add(path.clone(), get_file_meta(&path).unwrap());
+        }
+
+        assert!(find_duplicates(&db, 1).is_empty());
+
+        fs::write(root.join("copy.bin"), vec![7; 1000]).unwrap();
+        db.add(
+            root.join("copy.bin"),
+            get_file_meta(&root.join("copy.bin")).unwrap(),
+        );
+        let groups = find_duplicates(&db, 1);
+        assert_eq!(
+            groups[0].paths,
+            vec![root.join("copy.bin"), root.join("file.bin")]
+        );
+    }
+}
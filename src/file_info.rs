@@ -2,11 +2,100 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+/// Which timestamp is used to tell when a file was used the last time
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+    /// Modification time
+    #[default]
+    Modified,
+    /// Access time, falling back to the modification time
+    Accessed,
+}
+
+/// Which size of files is measured
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SizeMode {
+    /// Length of the content, as reported by `ls` or `du --apparent-size`
+    #[default]
+    Apparent,
+    /// Space taken on the disk in blocks, as reported by `du`
+    Allocated,
+}
+
+impl SizeMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            SizeMode::Apparent => SizeMode::Allocated,
+            SizeMode::Allocated => SizeMode::Apparent,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SizeMode::Apparent => "apparent",
+            SizeMode::Allocated => "on disk",
+        }
+    }
+}
+
+/// Owner and permission bits, used to find out upfront whether the user can delete a file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Permissions {
+    #[cfg(unix)]
+    pub uid: u32,
+    #[cfg(unix)]
+    pub mode: u32,
+}
+
+impl Permissions {
+    /// Whether the content of a directory with these permissions can be changed by the user
+    ///
+    /// Group permissions aren't considered, as they'd need the groups of the user.
+    #[cfg(unix)]
+    pub fn is_writable_by(&self, uid: u32) -> bool {
+        const OWNER_WRITABLE: u32 = 0o200;
+        const WORLD_WRITABLE: u32 = 0o002;
+        const STICKY: u32 = 0o1000;
+
+        uid == 0
+            || (self.uid == uid && self.mode & OWNER_WRITABLE != 0)
+            || self.mode & (WORLD_WRITABLE | STICKY) == WORLD_WRITABLE
+    }
+}
+
+impl From<&fs::Metadata> for Permissions {
+    #[cfg(unix)]
+    fn from(metadata: &fs::Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            uid: metadata.uid(),
+            mode: metadata.mode(),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn from(_metadata: &fs::Metadata) -> Self {
+        Self {}
+    }
+}
+
+/// Effective user ID of the current process
+#[cfg(unix)]
+pub fn current_uid() -> u32 {
+    // SAFETY: geteuid is always successful and has no side effects
+    unsafe { libc::geteuid() }
+}
+
 #[derive(Debug)]
 pub struct FileMeta {
     pub is_dir: bool,
     pub size: Option<u64>,
+    /// Space allocated on the disk, where the platform reports it
+    pub allocated: Option<u64>,
     pub touched: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    pub permissions: Permissions,
 }
 
 impl From<&fs::Metadata> for FileMeta {
@@ -14,7 +103,34 @@ impl From<&fs::Metadata> for FileMeta {
         Self {
             is_dir: metadata.is_dir(),
             size: Some(metadata.len()),
+            allocated: allocated_size(metadata),
             touched: metadata.modified().ok(),
+            accessed: metadata.accessed().ok(),
+            permissions: Permissions::from(metadata),
+        }
+    }
+}
+
+/// Size of the blocks allocated for the file; sparse files take less than their length
+#[cfg(unix)]
+fn allocated_size(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    // `st_blocks` is always counted in 512-byte units, regardless of the block size
+    Some(metadata.blocks() * 512)
+}
+
+#[cfg(not(unix))]
+fn allocated_size(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+impl FileMeta {
+    /// Size in the given mode; the apparent size stands in for an unknown allocated one
+    pub fn size_in(&self, mode: SizeMode) -> Option<u64> {
+        match mode {
+            SizeMode::Apparent => self.size,
+            SizeMode::Allocated => self.allocated.or(self.size),
         }
     }
 }
@@ -26,15 +142,44 @@ pub fn get_file_meta(path: &Path) -> Result<FileMeta, std::io::Error> {
 
 #[derive(Debug)]
 #[allow(dead_code)]
-pub struct FileInfo<'a> {
-    pub path: &'a PathBuf,
+pub struct FileInfo {
+    pub path: PathBuf,
     pub is_dir: bool,
     pub size: Option<u64>,
+    pub allocated: Option<u64>,
     pub touched: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    pub permissions: Permissions,
+}
+
+impl FileInfo {
+    /// Size in the given mode; the apparent size stands in for an unknown allocated one
+    pub fn size_in(&self, mode: SizeMode) -> Option<u64> {
+        match mode {
+            SizeMode::Apparent => self.size,
+            SizeMode::Allocated => self.allocated.or(self.size),
+        }
+    }
+
+    /// Time of the last use according to the selected source
+    ///
+    /// Access time is unreliable: file systems are often mounted with `noatime`
+    /// (never updated) or `relatime` (updated only when older than mtime), and some
+    /// platforms don't provide it at all. The modification time is a use as well,
+    /// so the later of both is returned, which falls back to mtime when atime is
+    /// missing or stale.
+    pub fn last_time(&self, source: TimeSource) -> Option<SystemTime> {
+        match source {
+            TimeSource::Modified => self.touched,
+            TimeSource::Accessed => self.accessed.max(self.touched),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     #[test]
@@ -49,11 +194,59 @@ mod tests {
         let file_info = get_file_meta(&file_path).unwrap();
         assert!(!file_info.is_dir);
         assert_eq!(file_info.size, Some(13));
+        #[cfg(unix)]
+        assert!(file_info.allocated.is_some_and(|size| size % 512 == 0));
         assert!(file_info.touched.is_some());
+        assert!(file_info.accessed.is_some());
 
         let file_info = get_file_meta(&dir_path).unwrap();
         assert!(file_info.is_dir);
         assert_eq!(file_info.size, Some(dir_size));
         assert!(file_info.touched.is_some());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let meta = get_file_meta(tmp.path()).unwrap();
+        assert_eq!(meta.permissions.uid, current_uid());
+        assert!(meta.permissions.is_writable_by(current_uid()));
+
+        let foreign = |mode| Permissions { uid: 1234, mode };
+        assert!(!foreign(0o40755).is_writable_by(1000));
+        assert!(foreign(0o40755).is_writable_by(1234));
+        // Owning a directory isn't enough without the write permission
+        assert!(!foreign(0o40555).is_writable_by(1234));
+        // root can delete anything
+        assert!(foreign(0o40755).is_writable_by(0));
+        assert!(foreign(0o40777).is_writable_by(1000));
+        // Like /tmp, only own files can be deleted
+        assert!(!foreign(0o41777).is_writable_by(1000));
+    }
+
+    #[test]
+    fn test_last_time_falls_back_to_modification_time() {
+        let path = PathBuf::from("file");
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let mut file_info = FileInfo {
+            path,
+            is_dir: false,
+            size: None,
+            allocated: None,
+            touched: Some(modified),
+            accessed: None,
+            permissions: Permissions::default(),
+        };
+        assert_eq!(file_info.last_time(TimeSource::Accessed), Some(modified));
+
+        // Stale access time, e.g. on `noatime` mounts
+        file_info.accessed = Some(modified - Duration::from_secs(50));
+        assert_eq!(file_info.last_time(TimeSource::Accessed), Some(modified));
+
+        let accessed = modified + Duration::from_secs(50);
+        file_info.accessed = Some(accessed);
+        assert_eq!(file_info.last_time(TimeSource::Accessed), Some(accessed));
+        assert_eq!(file_info.last_time(TimeSource::Modified), Some(modified));
+    }
 }
@@ -1,25 +1,279 @@
 #![allow(dead_code)]
-use std::collections::BTreeMap;
-use std::collections::btree_map::Cursor;
-use std::ops::Bound;
-use std::path::PathBuf;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::btree_map::Range;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::file_info::{FileInfo, FileMeta, SizeMode, TimeSource};
+
+/// Id under which the content of a directory is stored
+type DirId = u32;
+
+/// Parent of the first path components, like `/`
+const ROOT_DIR: DirId = 0;
+
+/// Entries are keyed by the id of their parent directory and their name
+///
+/// Storing full paths repeats the same prefixes over and over, which takes
+/// a lot of memory on big trees. Entries of the same directory are next to
+/// each other and ordered by name, so the content can still be walked in
+/// the order of full paths.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    dir: DirId,
+    name: Box<OsStr>,
+}
+
+impl Key {
+    /// Bound of the content of `dir`, as empty names sort first
+    fn first_of(dir: DirId) -> Self {
+        Self {
+            dir,
+            name: Box::from(OsStr::new("")),
+        }
+    }
+}
+
+/// Lets `Key` be looked up by a borrowed name, so lookups don't allocate
+trait AsKey {
+    fn as_key(&self) -> (DirId, &OsStr);
+}
 
-use crate::file_info::{FileInfo, FileMeta};
+impl AsKey for Key {
+    fn as_key(&self) -> (DirId, &OsStr) {
+        (self.dir, &self.name)
+    }
+}
+
+impl AsKey for (DirId, &OsStr) {
+    fn as_key(&self) -> (DirId, &OsStr) {
+        *self
+    }
+}
+
+impl<'a> Borrow<dyn AsKey + 'a> for Key {
+    fn borrow(&self) -> &(dyn AsKey + 'a) {
+        self
+    }
+}
+
+impl PartialEq for dyn AsKey + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_key() == other.as_key()
+    }
+}
+
+impl Eq for dyn AsKey + '_ {}
+
+impl PartialOrd for dyn AsKey + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for dyn AsKey + '_ {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_key().cmp(&other.as_key())
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    /// `None` for directories known only from their content
+    meta: Option<FileMeta>,
+    /// Assigned when the first path inside is added
+    dir: Option<DirId>,
+}
 
 #[derive(Debug)]
 pub struct FilesDB {
-    files: BTreeMap<PathBuf, FileMeta>,
+    files: BTreeMap<Key, Entry>,
+    next_dir: DirId,
+    len: usize,
+    // Memoized results of `dir_size_in`
+    size_cache: Mutex<HashMap<(PathBuf, SizeMode), u64>>,
 }
 
 impl FilesDB {
     pub fn new() -> Self {
         Self {
             files: BTreeMap::new(),
+            next_dir: ROOT_DIR + 1,
+            len: 0,
+            size_cache: Mutex::new(HashMap::new()),
         }
     }
 
     pub fn add(&mut self, path: PathBuf, meta: FileMeta) {
-        self.files.insert(path, meta);
+        let cache = self.size_cache.get_mut().unwrap();
+        if !cache.is_empty() {
+            cache.retain(|(cached, _), _| !path.starts_with(cached));
+        }
+        self.insert(&path, meta);
+    }
+
+    /// Store `meta` of `path` without invalidating the cached sizes
+    fn insert(&mut self, path: &Path, meta: FileMeta) {
+        let mut dir = ROOT_DIR;
+        let mut components = path.components().peekable();
+        while let Some(component) = components.next() {
+            let key: &dyn AsKey = &(dir, component.as_os_str());
+            if !self.files.contains_key(key) {
+                self.files.insert(
+                    Key {
+                        dir,
+                        name: Box::from(component.as_os_str()),
+                    },
+                    Entry {
+                        meta: None,
+                        dir: None,
+                    },
+                );
+            }
+            let entry = self.files.get_mut(key).unwrap();
+            if components.peek().is_none() {
+                if entry.meta.replace(meta).is_none() {
+                    self.len += 1;
+                }
+                return;
+            }
+            dir = *entry.dir.get_or_insert_with(|| {
+                self.next_dir += 1;
+                self.next_dir - 1
+            });
+        }
+    }
+
+    /// Entry of `path`; the root yields no entry, but the id of its content
+    fn find(&self, path: &Path) -> Option<(Option<&Entry>, Option<DirId>)> {
+        let mut found = (None, Some(ROOT_DIR));
+        for component in path.components() {
+            let dir = found.1?;
+            let entry = self
+                .files
+                .get(&(dir, component.as_os_str()) as &dyn AsKey)?;
+            found = (Some(entry), entry.dir);
+        }
+        Some(found)
+    }
+
+    fn meta(&self, path: &Path) -> Option<&FileMeta> {
+        self.find(path)?.0?.meta.as_ref()
+    }
+
+    /// Remove `path` and its complete content
+    ///
+    /// Cached sizes of the path, its content, and all its parents are invalidated.
+    pub fn remove_subtree(&mut self, path: &Path) {
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            return;
+        };
+        let Some((_, Some(parent_dir))) = self.find(parent) else {
+            return;
+        };
+        let Some(entry) = self.files.remove(&(parent_dir, name) as &dyn AsKey) else {
+            return;
+        };
+        self.len -= usize::from(entry.meta.is_some());
+
+        let mut dirs = Vec::from_iter(entry.dir);
+        while let Some(dir) = dirs.pop() {
+            let range = Key::first_of(dir)..Key::first_of(dir + 1);
+            for (_, entry) in self.files.extract_if(range, |_, _| true) {
+                self.len -= usize::from(entry.meta.is_some());
+                dirs.extend(entry.dir);
+            }
+        }
+
+        self.size_cache
+            .get_mut()
+            .unwrap()
+            .retain(|(cached, _), _| !cached.starts_with(path) && !path.starts_with(cached));
+    }
+
+    /// Add all entries of `other`; its metadata wins for paths present in both
+    ///
+    /// Lets roots be scanned independently, e.g. one of them loaded from a cache and
+    /// another one freshly scanned, and combined afterward.
+    pub fn merge(&mut self, other: FilesDB) {
+        if other.len == 0 {
+            return;
+        }
+        self.size_cache.get_mut().unwrap().clear();
+
+        // Directory ids are assigned before their content is added, so the parent of
+        // every entry is visited before the entry itself
+        let mut dir_paths = HashMap::from([(ROOT_DIR, PathBuf::new())]);
+        for (key, entry) in other.files {
+            let path = dir_paths[&key.dir].join(&*key.name);
+            if let Some(meta) = entry.meta {
+                self.insert(&path, meta);
+            }
+            if let Some(dir) = entry.dir {
+                dir_paths.insert(dir, path);
+            }
+        }
+    }
+
+    /// Sum of sizes of the complete content of `lookup_path`
+    ///
+    /// Results are memoized, so repeated lookups of the same path are cheap.
+    pub fn dir_size(&self, lookup_path: &Path) -> u64 {
+        self.dir_size_in(lookup_path, SizeMode::Apparent)
+    }
+
+    /// Like [`FilesDB::dir_size`], measuring the sizes in `mode`
+    pub fn dir_size_in(&self, lookup_path: &Path, mode: SizeMode) -> u64 {
+        let cache_key = (lookup_path.to_path_buf(), mode);
+        if let Some(size) = self.size_cache.lock().unwrap().get(&cache_key) {
+            return *size;
+        }
+
+        let size = match self.find(lookup_path) {
+            None => 0,
+            Some((entry, dir)) => {
+                let own_size = entry
+                    .and_then(|e| e.meta.as_ref())
+                    .and_then(|m| m.size_in(mode));
+                own_size.unwrap_or_default()
+                    + dir.map(|d| self.content_size(d, mode)).unwrap_or_default()
+            }
+        };
+        self.size_cache.lock().unwrap().insert(cache_key, size);
+
+        size
+    }
+
+    /// Sum of sizes inside `lookup_path`, without its own entry and without memoizing
+    ///
+    /// Meant for one-off totals, which would only grow the cache.
+    pub fn content_size_in(&self, lookup_path: &Path, mode: SizeMode) -> u64 {
+        self.find(lookup_path)
+            .and_then(|(_, dir)| dir)
+            .map(|d| self.content_size(d, mode))
+            .unwrap_or_default()
+    }
+
+    /// Sum of sizes inside `dir` without building the paths
+    fn content_size(&self, dir: DirId, mode: SizeMode) -> u64 {
+        self.children(dir)
+            .map(|(_, entry)| {
+                let own_size = entry.meta.as_ref().and_then(|m| m.size_in(mode));
+                own_size.unwrap_or_default()
+                    + entry
+                        .dir
+                        .map(|d| self.content_size(d, mode))
+                        .unwrap_or_default()
+            })
+            .sum()
+    }
+
+    fn children(&self, dir: DirId) -> Range<'_, Key, Entry> {
+        self.files.range(Key::first_of(dir)..Key::first_of(dir + 1))
     }
 
     /// Iterate over complete content of `lookup_path`
@@ -31,20 +285,60 @@ impl FilesDB {
     /// let lookup_path = "/foo/bar";
     /// db.iter_dir.filter_map(|fi| fi.size).sum();
     /// ```
-    pub fn iter_dir<'a, 'b>(&'a self, lookup_path: &'b PathBuf) -> DirectoryIter<'a, 'b> {
-        DirectoryIter {
-            cursor: self.files.lower_bound(Bound::Included(lookup_path)),
-            lookup_path,
+    pub fn iter_dir(&self, lookup_path: &Path) -> DirectoryIter<'_> {
+        self.walk(lookup_path, lookup_path.to_path_buf(), false)
+    }
+
+    /// Time of the last use of anything in `lookup_path`, itself included
+    ///
+    /// Directory timestamps change whenever an entry is added or removed, e.g. by a build
+    /// cleaning up its temporary files. With `files_only`, they're used only when there
+    /// are no files at all.
+    pub fn last_update(
+        &self,
+        lookup_path: &Path,
+        source: TimeSource,
+        files_only: bool,
+    ) -> Option<SystemTime> {
+        let (mut files, mut dirs) = (None, None);
+        for fi in self.iter_dir(lookup_path) {
+            let time = fi.last_time(source);
+            if fi.is_dir {
+                dirs = dirs.max(time);
+            } else {
+                files = files.max(time);
+            }
         }
+        if files_only {
+            files.or(dirs)
+        } else {
+            files.max(dirs)
+        }
+    }
+
+    /// Iterate over complete content of `root` with paths relative to it
+    ///
+    /// The same as `iter_dir`, but the `root` prefix is stripped from every path,
+    /// so `root` itself is yielded as an empty path. Useful for exports and
+    /// breadcrumbs, where the full absolute paths are just noise.
+    pub fn iter_dir_relative(&self, root: &Path) -> DirectoryIter<'_> {
+        self.walk(root, PathBuf::new(), false)
+    }
+
+    /// Iterate over everything stored in the DB
+    pub fn iter_all(&self) -> DirectoryIter<'_> {
+        self.walk(Path::new(""), PathBuf::new(), false)
     }
 
     /// Iterate over items on current level
     ///
-    /// This is basically `ls PATH` operation on FilesDB.
-    pub fn iter_level<'a, 'b>(&'a self, lookup_path: &'b PathBuf) -> LevelIter<'a, 'b> {
+    /// This is basically `ls PATH` operation on FilesDB. The content of every directory
+    /// is a single range of the map, so the subtrees below the level are never visited.
+    pub fn iter_level(&self, lookup_path: &Path) -> LevelIter<'_> {
+        let dir = self.find(lookup_path).and_then(|(_, dir)| dir);
         LevelIter {
-            cursor: self.files.lower_bound(Bound::Included(lookup_path)),
-            lookup_path,
+            children: dir.map(|d| self.children(d)),
+            path: lookup_path.to_path_buf(),
         }
     }
 
@@ -52,96 +346,104 @@ impl FilesDB {
     ///
     /// This enables you to get all directories, so you can perform specialised
     /// checks for different files/directories on some prefix.
-    pub fn iter_directories<'a, 'b>(&'a self, lookup_path: &'b PathBuf) -> AllDirsIter<'a, 'b> {
-        AllDirsIter {
-            cursor: self.files.lower_bound(Bound::Included(lookup_path)),
-            lookup_path,
-        }
+    pub fn iter_directories(&self, lookup_path: &Path) -> DirectoryIter<'_> {
+        self.walk(lookup_path, lookup_path.to_path_buf(), true)
     }
 
-    pub fn is_dir(&self, path: &PathBuf) -> bool {
-        match self.files.get(path) {
-            None => false,
-            Some(f) => f.is_dir,
+    fn walk(&self, lookup_path: &Path, path: PathBuf, dirs_only: bool) -> DirectoryIter<'_> {
+        let (entry, dir) = self.find(lookup_path).unwrap_or_default();
+        let root = entry
+            .and_then(|e| e.meta.as_ref())
+            .filter(|meta| !dirs_only || meta.is_dir)
+            .map(|meta| file_info(path.clone(), meta));
+        DirectoryIter {
+            db: self,
+            root,
+            stack: Vec::from_iter(dir.map(|d| self.children(d))),
+            path,
+            dirs_only,
         }
     }
 
-    pub fn exists(&self, path: &PathBuf) -> bool {
-        self.files.contains_key(path)
+    pub fn is_dir(&self, path: &Path) -> bool {
+        self.meta(path).is_some_and(|m| m.is_dir)
     }
 
-    pub fn len(&self) -> usize {
-        self.files.len()
+    pub fn exists(&self, path: &Path) -> bool {
+        self.meta(path).is_some()
     }
-}
 
-pub struct DirectoryIter<'a, 'b> {
-    cursor: Cursor<'a, PathBuf, FileMeta>,
-    lookup_path: &'b PathBuf,
+    pub fn len(&self) -> usize {
+        self.len
+    }
 }
 
-impl<'a> Iterator for DirectoryIter<'a, '_> {
-    type Item = FileInfo<'a>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let (path, meta) = self.cursor.next()?;
-        if !path.starts_with(self.lookup_path) {
-            return None;
-        }
-
-        Some(FileInfo {
-            path,
-            is_dir: meta.is_dir,
-            size: meta.size,
-            touched: meta.touched,
-        })
+fn file_info(path: PathBuf, meta: &FileMeta) -> FileInfo {
+    FileInfo {
+        path,
+        is_dir: meta.is_dir,
+        size: meta.size,
+        allocated: meta.allocated,
+        touched: meta.touched,
+        accessed: meta.accessed,
+        permissions: meta.permissions,
     }
 }
 
-pub struct LevelIter<'a, 'b> {
-    cursor: Cursor<'a, PathBuf, FileMeta>,
-    lookup_path: &'b PathBuf,
+/// Depth-first walk over a subtree in the order of full paths
+pub struct DirectoryIter<'a> {
+    db: &'a FilesDB,
+    /// The looked up path itself, yielded first
+    root: Option<FileInfo>,
+    /// Remaining content of the directories being walked, the innermost last
+    stack: Vec<Range<'a, Key, Entry>>,
+    /// Path of the innermost directory being walked
+    path: PathBuf,
+    dirs_only: bool,
 }
 
-impl<'a> Iterator for LevelIter<'a, '_> {
-    type Item = FileInfo<'a>;
+impl Iterator for DirectoryIter<'_> {
+    type Item = FileInfo;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some((path, meta)) = self.cursor.next() {
-            if path.parent()? == self.lookup_path {
-                return Some(FileInfo {
-                    path,
-                    is_dir: meta.is_dir,
-                    size: meta.size,
-                    touched: meta.touched,
-                });
-            }
+        if let Some(root) = self.root.take() {
+            return Some(root);
         }
 
-        None
+        loop {
+            let Some((key, entry)) = self.stack.last_mut()?.next() else {
+                self.stack.pop();
+                self.path.pop();
+                continue;
+            };
+            let path = entry
+                .meta
+                .as_ref()
+                .filter(|meta| !self.dirs_only || meta.is_dir)
+                .map(|meta| file_info(self.path.join(&*key.name), meta));
+            if let Some(dir) = entry.dir {
+                self.stack.push(self.db.children(dir));
+                self.path.push(&*key.name);
+            }
+            if path.is_some() {
+                return path;
+            }
+        }
     }
 }
 
-pub struct AllDirsIter<'a, 'b> {
-    cursor: Cursor<'a, PathBuf, FileMeta>,
-    lookup_path: &'b PathBuf,
+pub struct LevelIter<'a> {
+    children: Option<Range<'a, Key, Entry>>,
+    path: PathBuf,
 }
 
-impl<'a> Iterator for AllDirsIter<'a, '_> {
-    type Item = FileInfo<'a>;
+impl Iterator for LevelIter<'_> {
+    type Item = FileInfo;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some((path, meta)) = self.cursor.next() {
-            if !path.starts_with(self.lookup_path) {
-                return None;
-            }
-            if meta.is_dir {
-                return Some(FileInfo {
-                    path,
-                    is_dir: meta.is_dir,
-                    size: meta.size,
-                    touched: meta.touched,
-                });
+        for (key, entry) in self.children.as_mut()? {
+            if let Some(meta) = &entry.meta {
+                return Some(file_info(self.path.join(&*key.name), meta));
             }
         }
 
@@ -152,7 +454,7 @@ impl<'a> Iterator for AllDirsIter<'a, '_> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::file_info::FileMeta;
+    use crate::file_info::{FileMeta, Permissions};
 
     fn build_test_structure() -> FilesDB {
         let mut db = FilesDB::new();
@@ -161,7 +463,10 @@ mod tests {
             FileMeta {
                 is_dir: true,
                 size: None,
+                allocated: None,
                 touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
             },
         );
         db.add(
@@ -169,7 +474,10 @@ mod tests {
             FileMeta {
                 is_dir: false,
                 size: Some(10),
+                allocated: None,
                 touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
             },
         );
         db.add(
@@ -177,7 +485,10 @@ mod tests {
             FileMeta {
                 is_dir: true,
                 size: None,
+                allocated: None,
                 touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
             },
         );
         db.add(
@@ -185,7 +496,10 @@ mod tests {
             FileMeta {
                 is_dir: true,
                 size: None,
+                allocated: None,
                 touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
             },
         );
         db.add(
@@ -193,7 +507,10 @@ mod tests {
             FileMeta {
                 is_dir: true,
                 size: None,
+                allocated: None,
                 touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
             },
         );
         db.add(
@@ -201,7 +518,10 @@ mod tests {
             FileMeta {
                 is_dir: false,
                 size: Some(20),
+                allocated: None,
                 touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
             },
         );
 
@@ -231,40 +551,214 @@ mod tests {
         let q = PathBuf::from("/foo/baz");
         let mut it = db.iter_dir(&q);
 
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/baz"));
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/baz/b.txt"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/baz"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/baz/b.txt"));
         assert_eq!(it.next().is_none(), true);
     }
 
+    #[test]
+    fn iter_dir_relative() {
+        let db = build_test_structure();
+        let q = PathBuf::from("/foo");
+        let paths = db
+            .iter_dir_relative(&q)
+            .map(|fi| (fi.path, fi.size))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths,
+            vec![
+                (PathBuf::from(""), None),
+                (PathBuf::from("a.txt"), Some(10)),
+                (PathBuf::from("bar"), None),
+                (PathBuf::from("bar/empty"), None),
+                (PathBuf::from("baz"), None),
+                (PathBuf::from("baz/b.txt"), Some(20)),
+            ]
+        );
+
+        // Absolute iterators are not affected
+        assert_eq!(
+            db.iter_dir(&q).nth(1).unwrap().path,
+            Path::new("/foo/a.txt")
+        );
+    }
+
     #[test]
     fn iter_level() {
         let db = build_test_structure();
         let q = PathBuf::from("/foo");
         let mut it = db.iter_level(&q);
 
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/a.txt"));
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/bar"));
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/baz"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/a.txt"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/bar"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/baz"));
         assert_eq!(it.next().is_none(), true);
     }
 
+    #[test]
+    fn dir_size() {
+        let db = build_test_structure();
+
+        assert_eq!(db.dir_size(&PathBuf::from("/foo")), 30);
+        assert_eq!(db.dir_size(&PathBuf::from("/foo/baz")), 20);
+        assert_eq!(db.dir_size(&PathBuf::from("/foo/bar")), 0);
+    }
+
+    #[test]
+    fn dir_size_is_cached() {
+        let mut db = build_test_structure();
+        let q = PathBuf::from("/foo/baz");
+        assert_eq!(db.dir_size(&q), 20);
+
+        // Bypass the invalidation, so only the cached value can be returned
+        db.insert(
+            Path::new("/foo/baz/c.txt"),
+            FileMeta {
+                is_dir: false,
+                size: Some(5),
+                allocated: None,
+                touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
+            },
+        );
+        assert_eq!(db.dir_size(&q), 20);
+    }
+
+    #[test]
+    fn dir_size_in_allocated_mode() {
+        let mut db = build_test_structure();
+        db.add(
+            PathBuf::from("/foo/baz/c.txt"),
+            FileMeta {
+                is_dir: false,
+                size: Some(5),
+                allocated: Some(4096),
+                touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
+            },
+        );
+
+        assert_eq!(db.dir_size(&PathBuf::from("/foo/baz")), 25);
+        // Files without a known allocated size count with their apparent size
+        assert_eq!(
+            db.dir_size_in(&PathBuf::from("/foo/baz"), SizeMode::Allocated),
+            4116
+        );
+        assert_eq!(db.dir_size(&PathBuf::from("/foo")), 35);
+    }
+
+    #[test]
+    fn dir_size_cache_is_invalidated() {
+        let mut db = build_test_structure();
+        assert_eq!(db.dir_size(&PathBuf::from("/foo")), 30);
+        assert_eq!(db.dir_size(&PathBuf::from("/foo/baz")), 20);
+
+        db.add(
+            PathBuf::from("/foo/baz/c.txt"),
+            FileMeta {
+                is_dir: false,
+                size: Some(5),
+                allocated: None,
+                touched: None,
+                accessed: None,
+                permissions: Permissions::default(),
+            },
+        );
+        assert_eq!(db.dir_size(&PathBuf::from("/foo")), 35);
+        assert_eq!(db.dir_size(&PathBuf::from("/foo/baz")), 25);
+
+        db.remove_subtree(&PathBuf::from("/foo/baz"));
+        assert_eq!(db.dir_size(&PathBuf::from("/foo")), 10);
+        assert_eq!(db.dir_size(&PathBuf::from("/foo/baz")), 0);
+        assert!(!db.exists(&PathBuf::from("/foo/baz")));
+        assert!(!db.exists(&PathBuf::from("/foo/baz/b.txt")));
+        assert!(db.exists(&PathBuf::from("/foo/bar")));
+    }
+
     #[test]
     fn iter_directories() {
         let db = build_test_structure();
         let q = PathBuf::from("/foo");
         let mut it = db.iter_directories(&q);
 
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo"));
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/bar"));
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/bar/empty"));
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/baz"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/bar"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/bar/empty"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/baz"));
         assert_eq!(it.next().is_none(), true);
 
         let q = PathBuf::from("/foo/bar");
         let mut it = db.iter_directories(&q);
 
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/bar"));
-        assert_eq!(it.next().unwrap().path, &PathBuf::from("/foo/bar/empty"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/bar"));
+        assert_eq!(it.next().unwrap().path, PathBuf::from("/foo/bar/empty"));
         assert_eq!(it.next().is_none(), true);
     }
+
+    #[test]
+    fn iteration_follows_full_paths_order() {
+        let mut db = build_test_structure();
+        let meta = || FileMeta {
+            is_dir: false,
+            size: Some(1),
+            allocated: None,
+            touched: None,
+            accessed: None,
+            permissions: Permissions::default(),
+        };
+        // Added out of order and without their parent directory
+        db.add(PathBuf::from("/foo-bar/z.txt"), meta());
+        db.add(PathBuf::from("/foo-bar/a.txt"), meta());
+
+        let paths = db
+            .iter_dir(Path::new("/"))
+            .map(|fi| fi.path)
+            .collect::<Vec<_>>();
+        let mut sorted = paths.clone();
+        sorted.sort();
+        assert_eq!(paths, sorted);
+        assert_eq!(paths.len(), 8);
+
+        // Directories known only from their content are not reported
+        assert!(!db.exists(Path::new("/foo-bar")));
+        assert!(!db.exists(Path::new("/")));
+        assert_eq!(db.dir_size(&PathBuf::from("/foo-bar")), 2);
+        assert_eq!(db.len(), 8);
+
+        db.remove_subtree(Path::new("/foo"));
+        assert_eq!(db.len(), 2);
+        assert_eq!(db.iter_dir(Path::new("/foo")).count(), 0);
+        assert_eq!(db.iter_level(Path::new("/foo-bar")).count(), 2);
+    }
+
+    #[test]
+    fn merge() {
+        let mut db = build_test_structure();
+        assert_eq!(db.dir_size(&PathBuf::from("/foo")), 30);
+        let file = |size| FileMeta {
+            is_dir: false,
+            size: Some(size),
+            allocated: None,
+            touched: None,
+            accessed: None,
+            permissions: Permissions::default(),
+        };
+        let mut other = FilesDB::new();
+        other.add(PathBuf::from("/foo/baz/b.txt"), file(200));
+        other.add(PathBuf::from("/foo/baz/c.txt"), file(5));
+        other.add(PathBuf::from("/other/d.txt"), file(1));
+
+        db.merge(other);
+
+        // The merged entry wins
+        assert_eq!(db.dir_size(&PathBuf::from("/foo/baz")), 205);
+        assert_eq!(db.dir_size(&PathBuf::from("/foo")), 215);
+        assert!(db.exists(Path::new("/other/d.txt")));
+        assert!(db.is_dir(Path::new("/foo/baz")));
+        assert_eq!(db.len(), 8);
+        assert_eq!(db.iter_dir(Path::new("/")).count(), 8);
+    }
 }
@@ -1,5 +1,5 @@
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 
 use dialoguer::theme::ColorfulTheme;
@@ -7,12 +7,17 @@ use dialoguer::{Confirm, Select};
 use owo_colors::OwoColorize;
 use tracing::debug;
 
-use crate::config::get_config_file_candidates;
+use crate::cli::GenerateConfigArgs;
+use crate::config::{get_config_file_candidates, requested_config_path};
+
+const EXAMPLE_CONFIG: &str = include_str!("../assets/example_config.toml");
 
 #[derive(thiserror::Error, Debug)]
 pub enum GenerateConfigError {
     #[error("Interrupted by user")]
     Interrupted,
+    #[error("Couldn't identify your home directory. Provide the path with --config.")]
+    UnknownHomeDirectory,
     #[error("File already exists. Not overwriting.")]
     AlreadyExist,
     #[error("Config couldn't be saved: {inner}")]
@@ -27,20 +32,62 @@ pub enum GenerateConfigError {
     },
 }
 
-pub fn generate_config(home_dir: &Path) -> Result<(), GenerateConfigError> {
+pub fn generate_config(
+    cmd_args: &GenerateConfigArgs,
+    home_dir: Option<&Path>,
+) -> Result<(), GenerateConfigError> {
+    if cmd_args.stdout {
+        print_example_config(&mut io::stdout());
+        return Ok(());
+    }
     let mut interaction = DialoguerInteraction;
-    generate_config_inner(&mut io::stdout(), &mut interaction, home_dir)
+    generate_config_inner(
+        &mut io::stdout(),
+        &mut interaction,
+        home_dir,
+        requested_config_path(cmd_args.config.as_deref()).as_deref(),
+    )
 }
 
-fn generate_config_inner<W: Write, I: GenerateConfigInteraction>(
+/// Print the example configuration without touching any file, e.g. for diffing or templating
+fn print_example_config<W: Write>(out: &mut W) {
+    write!(out, "{EXAMPLE_CONFIG}").expect("Failed to write to stdout");
+}
+
+/// Offer creating the configuration file when none was found
+///
+/// Returns `true` if the file was created. Non-interactive runs are never prompted.
+pub fn offer_config_generation(home_dir: &Path) -> Result<bool, GenerateConfigError> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    let mut interaction = DialoguerInteraction;
+    offer_config_generation_inner(&mut io::stdout(), &mut interaction, home_dir)
+}
+
+fn offer_config_generation_inner<W: Write, I: GenerateConfigInteraction>(
     out: &mut W,
     interaction: &mut I,
     home_dir: &Path,
-) -> Result<(), GenerateConfigError> {
-    let example_config = include_str!("../assets/example_config.toml");
-    let candidates = get_config_file_candidates(home_dir);
+) -> Result<bool, GenerateConfigError> {
+    if !interaction.confirm_creation()? {
+        return Ok(false);
+    }
+    generate_config_inner(out, interaction, Some(home_dir), None)?;
+    Ok(true)
+}
 
-    let path = interaction.select_path(&candidates)?;
+fn generate_config_inner<W: Write, I: GenerateConfigInteraction>(
+    out: &mut W,
+    interaction: &mut I,
+    home_dir: Option<&Path>,
+    requested_path: Option<&Path>,
+) -> Result<(), GenerateConfigError> {
+    let path = match (requested_path, home_dir) {
+        (Some(path), _) => path.to_path_buf(),
+        (None, Some(home_dir)) => interaction.select_path(&get_config_file_candidates(home_dir))?,
+        (None, None) => return Err(GenerateConfigError::UnknownHomeDirectory),
+    };
     debug!("Looking for a configuration file: {}", path.display());
     if path.exists() {
         let confirmation = interaction.confirm_overwrite()?;
@@ -50,7 +97,10 @@ fn generate_config_inner<W: Write, I: GenerateConfigInteraction>(
     }
 
     debug!("Using configuration file: {}", path.display());
-    fs::write(&path, example_config)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, EXAMPLE_CONFIG)?;
 
     writeln!(
         out,
@@ -71,6 +121,7 @@ fn generate_config_inner<W: Write, I: GenerateConfigInteraction>(
 trait GenerateConfigInteraction {
     fn select_path(&mut self, candidates: &[PathBuf]) -> Result<PathBuf, GenerateConfigError>;
     fn confirm_overwrite(&mut self) -> Result<bool, GenerateConfigError>;
+    fn confirm_creation(&mut self) -> Result<bool, GenerateConfigError>;
 }
 
 struct DialoguerInteraction;
@@ -110,6 +161,19 @@ impl GenerateConfigInteraction for DialoguerInteraction {
 
         Ok(confirmation)
     }
+
+    fn confirm_creation(&mut self) -> Result<bool, GenerateConfigError> {
+        let confirmation = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("No config found — create one now?")
+            .default(false)
+            .show_default(true)
+            .report(true)
+            .wait_for_newline(false)
+            .interact_opt()?
+            .unwrap_or(false);
+
+        Ok(confirmation)
+    }
 }
 
 #[cfg(test)]
@@ -122,6 +186,7 @@ mod tests {
     struct TestsInteraction {
         select: usize,
         confirmation: Option<Result<bool, GenerateConfigError>>,
+        creation: bool,
     }
 
     impl GenerateConfigInteraction for TestsInteraction {
@@ -132,6 +197,21 @@ mod tests {
         fn confirm_overwrite(&mut self) -> Result<bool, GenerateConfigError> {
             self.confirmation.take().unwrap()
         }
+
+        fn confirm_creation(&mut self) -> Result<bool, GenerateConfigError> {
+            Ok(self.creation)
+        }
+    }
+
+    #[test]
+    fn test_print_example_config() {
+        let mut buffer = Vec::new();
+        print_example_config(&mut buffer);
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(output, EXAMPLE_CONFIG);
+        let config: Config = toml::from_str(&output).unwrap();
+        assert!(!config.paths.is_empty());
     }
 
     #[test]
@@ -143,9 +223,10 @@ mod tests {
         let mut interaction = TestsInteraction {
             select: 1,
             confirmation: Some(Ok(false)),
+            creation: false,
         };
 
-        let results = generate_config_inner(&mut buffer, &mut interaction, root_path);
+        let results = generate_config_inner(&mut buffer, &mut interaction, Some(root_path), None);
         assert_eq!(results.unwrap(), ());
 
         let output = String::from_utf8(buffer).unwrap();
@@ -168,9 +249,10 @@ mod tests {
         let mut interaction = TestsInteraction {
             select: 1,
             confirmation: Some(Ok(true)),
+            creation: false,
         };
 
-        let results = generate_config_inner(&mut buffer, &mut interaction, root_path);
+        let results = generate_config_inner(&mut buffer, &mut interaction, Some(root_path), None);
         assert_eq!(results.unwrap(), ());
 
         let output = String::from_utf8(buffer).unwrap();
@@ -193,9 +275,10 @@ mod tests {
         let mut interaction = TestsInteraction {
             select: 1,
             confirmation: Some(Ok(false)),
+            creation: false,
         };
 
-        let results = generate_config_inner(&mut buffer, &mut interaction, root_path);
+        let results = generate_config_inner(&mut buffer, &mut interaction, Some(root_path), None);
         assert!(matches!(results, Err(GenerateConfigError::AlreadyExist)));
 
         let cfg_data = fs::read_to_string(&root_path.join(".ddc.toml")).unwrap();
@@ -216,9 +299,10 @@ mod tests {
         let mut interaction = TestsInteraction {
             select: 1,
             confirmation: Some(Ok(false)),
+            creation: false,
         };
 
-        let results = generate_config_inner(&mut buffer, &mut interaction, root_path);
+        let results = generate_config_inner(&mut buffer, &mut interaction, Some(root_path), None);
         assert!(matches!(results, Err(GenerateConfigError::AlreadyExist)));
 
         let cfg_data = fs::read_to_string(&root_path.join(".ddc.toml")).unwrap();
@@ -239,9 +323,10 @@ mod tests {
         let mut interaction = TestsInteraction {
             select: 1,
             confirmation: Some(Err(GenerateConfigError::Interrupted)),
+            creation: false,
         };
 
-        let results = generate_config_inner(&mut buffer, &mut interaction, root_path);
+        let results = generate_config_inner(&mut buffer, &mut interaction, Some(root_path), None);
         assert!(matches!(results, Err(GenerateConfigError::Interrupted)));
 
         let cfg_data = fs::read_to_string(&root_path.join(".ddc.toml")).unwrap();
@@ -250,4 +335,81 @@ mod tests {
             Err(_)
         ));
     }
+
+    #[test]
+    fn test_generate_config_on_requested_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+        let requested_path = root_path.join("configs/work.toml");
+
+        let mut buffer = Vec::new();
+        // Selection must not be used
+        let mut interaction = TestsInteraction {
+            select: usize::MAX,
+            confirmation: None,
+            creation: false,
+        };
+
+        let results = generate_config_inner(
+            &mut buffer,
+            &mut interaction,
+            Some(root_path),
+            Some(&requested_path),
+        );
+        assert!(results.is_ok());
+
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("work.toml"));
+        assert!(!root_path.join(".ddc.toml").exists());
+
+        let cfg_data = fs::read_to_string(&requested_path).unwrap();
+        assert!(toml::from_str::<Config>(cfg_data.as_str()).is_ok());
+    }
+
+    #[test]
+    fn test_generate_config_without_home_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let requested_path = tmp.path().join("ddc.toml");
+        let mut interaction = TestsInteraction {
+            select: usize::MAX,
+            confirmation: None,
+            creation: false,
+        };
+
+        let results = generate_config_inner(&mut Vec::new(), &mut interaction, None, None);
+        assert!(matches!(
+            results,
+            Err(GenerateConfigError::UnknownHomeDirectory)
+        ));
+
+        let results = generate_config_inner(
+            &mut Vec::new(),
+            &mut interaction,
+            None,
+            Some(&requested_path),
+        );
+        assert!(results.is_ok());
+        assert!(requested_path.exists());
+    }
+
+    #[test]
+    fn test_offer_config_generation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path();
+
+        let mut buffer = Vec::new();
+        let mut interaction = TestsInteraction {
+            select: 1,
+            confirmation: None,
+            creation: false,
+        };
+        let created = offer_config_generation_inner(&mut buffer, &mut interaction, root_path);
+        assert!(!created.unwrap());
+        assert!(!root_path.join(".ddc.toml").exists());
+
+        interaction.creation = true;
+        let created = offer_config_generation_inner(&mut buffer, &mut interaction, root_path);
+        assert!(created.unwrap());
+        assert!(root_path.join(".ddc.toml").exists());
+    }
 }
@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Once;
+
+use tracing::{debug, warn};
+
+use crate::file_info::SizeMode;
+use crate::files_db::FilesDB;
+
+static GIT_MISSING: Once = Once::new();
+
+/// Size of the files ignored by git in the repository at `repo`
+///
+/// Ignored files are mostly build artifacts, so this is roughly the space a clean build
+/// would reclaim, unlike the committed sources. Returns `None` when `repo` isn't
+/// a git repository or git isn't available, so callers can fall back to the size
+/// of the complete subtree. Both the apparent and the allocated size are returned, so
+/// git is run just once.
+pub fn reclaimable_size(db: &FilesDB, repo: &Path) -> Option<(u64, u64)> {
+    // Running git outside a repository would measure an enclosing one, e.g. of dotfiles
+    if !db.exists(&repo.join(".git")) {
+        return None;
+    }
+    let ignored = ignored_paths(repo)?;
+
+    let size_in = |mode| {
+        ignored
+            .iter()
+            .map(|path| db.dir_size_in(&repo.join(path), mode))
+            .sum()
+    };
+    Some((size_in(SizeMode::Apparent), size_in(SizeMode::Allocated)))
+}
+
+/// Size of a git object database
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectsSize {
+    /// Loose objects, not compressed into packs yet
+    pub loose: u64,
+    /// Packs with their indexes
+    pub packed: u64,
+}
+
+impl ObjectsSize {
+    pub fn total(&self) -> u64 {
+        self.loose + self.packed
+    }
+}
+
+/// Size of the history of the git repository at `repo`, i.e. of its object database
+///
+/// Unlike the size of `.git`, this follows `.git` files of worktrees and submodules
+/// to the real object database, includes objects borrowed from alternates, and leaves
+/// out the index, logs and hooks. Returns `None` when the repository can't be opened.
+pub fn objects_size(repo: &Path) -> Option<ObjectsSize> {
+    let repository = gix::open_opts(repo, gix::open::Options::isolated())
+        .inspect_err(|e| debug!("Can't open git repository {}: {e}", repo.display()))
+        .ok()?;
+    let objects_dir = repository.common_dir().join("objects");
+    let alternates = gix::odb::alternate::resolve(objects_dir.clone(), repository.current_dir())
+        .inspect_err(|e| debug!("Can't resolve alternates of {}: {e}", repo.display()))
+        .unwrap_or_default();
+
+    let mut size = ObjectsSize::default();
+    for dir in std::iter::once(objects_dir).chain(alternates) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if name == "pack" {
+                size.packed += files_size(&entry.path());
+            } else if name != "info" {
+                size.loose += files_size(&entry.path());
+            }
+        }
+    }
+    debug!(
+        "Git objects of {}: {} B loose, {} B packed",
+        repo.display(),
+        size.loose,
+        size.packed
+    );
+
+    Some(size)
+}
+
+/// Sum of the sizes of files directly in `dir`
+fn files_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.metadata().ok())
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len())
+                .sum()
+        })
+        .unwrap_or_default()
+}
+
+/// Paths relative to `repo`; completely ignored directories are listed just once
+fn ignored_paths(repo: &Path) -> Option<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "ls-files",
+            "--others",
+            "--ignored",
+            "--exclude-standard",
+            "--directory",
+            "-z",
+        ])
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            GIT_MISSING.call_once(|| {
+                warn!("Unable to run git, full sizes of repositories are used instead: {e}")
+            });
+            return None;
+        }
+    };
+    if !output.status.success() {
+        debug!(
+            "git ls-files failed in {}: {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+
+    Some(parse_paths(&output.stdout))
+}
+
+/// Split the NUL separated output of `git ls-files -z`
+fn parse_paths(stdout: &[u8]) -> Vec<PathBuf> {
+    stdout
+        .split(|b| *b == 0)
+        .filter(|path| !path.is_empty())
+        .map(path_from_bytes)
+        .collect()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::file_info::get_file_meta;
+    use crate::test_utils::CurrentDir;
+
+    fn git(repo: &Path, args: &[&str]) -> bool {
+        Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(args)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        assert_eq!(
+            parse_paths(b"target/\0notes.log\0"),
+            vec![PathBuf::from("target/"), PathBuf::from("notes.log")]
+        );
+        assert!(parse_paths(b"").is_empty());
+    }
+
+    #[test]
+    fn test_reclaimable_size() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("repo");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::create_dir_all(repo.join("target/debug")).unwrap();
+        fs::write(repo.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(repo.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(repo.join("target/debug/app"), vec![0; 1000]).unwrap();
+        fs::write(repo.join("build.log"), vec![0; 100]).unwrap();
+        if !git(&repo, &["init", "-q"]) {
+            // git isn't installed
+            return;
+        }
+
+        let mut db = FilesDB::new();
+        for path in ["target", "target/debug", "target/debug/app", "build.log"] {
+            let path = repo.join(path);
+            db.add(path.clone(), get_file_meta(&path).unwrap());
+        }
+        let dir_size = |path: &str| fs::metadata(repo.join(path)).unwrap().len();
+        let expected = dir_size("target") + dir_size("target/debug") + 1000 + 100;
+
+        // Not a repository in the DB yet
+        assert_eq!(reclaimable_size(&db, &repo), None);
+
+        let git_dir = repo.join(".git");
+        db.add(git_dir.clone(), get_file_meta(&git_dir).unwrap());
+        let (apparent, allocated) = reclaimable_size(&db, &repo).unwrap();
+        assert_eq!(apparent, expected);
+        assert_eq!(
+            allocated,
+            db.dir_size_in(&repo.join("target"), SizeMode::Allocated)
+                + db.dir_size_in(&repo.join("build.log"), SizeMode::Allocated)
+        );
+    }
+
+    #[test]
+    fn test_objects_size() {
+        let tmp = tempfile::tempdir().unwrap();
+        // gix needs an existing working directory
+        let _cwd = CurrentDir::keep();
+        let repo = tmp.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        assert_eq!(objects_size(&repo), None);
+
+        if !git(&repo, &["init", "-q"]) {
+            // git isn't installed
+            return;
+        }
+        let objects = repo.join(".git/objects");
+        fs::create_dir_all(objects.join("ab")).unwrap();
+        fs::write(objects.join("ab/cdef"), vec![0; 100]).unwrap();
+        fs::write(objects.join("pack/pack-1.pack"), vec![0; 1000]).unwrap();
+        fs::write(objects.join("pack/pack-1.idx"), vec![0; 10]).unwrap();
+
+        let size = objects_size(&repo).unwrap();
+        assert_eq!(size.loose, 100);
+        assert_eq!(size.packed, 1010);
+
+        // Objects borrowed from another repository count as well
+        let borrower = tmp.path().join("borrower");
+        fs::create_dir_all(&borrower).unwrap();
+        assert!(git(&borrower, &["init", "-q"]));
+        fs::write(
+            borrower.join(".git/objects/info/alternates"),
+            objects.to_str().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(objects_size(&borrower), Some(size));
+    }
+}
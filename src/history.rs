@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs, io};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::discovery::DiscoveryResults;
+
+#[derive(thiserror::Error, Debug)]
+pub enum HistoryError {
+    #[error("Unable to access the history file: {inner}")]
+    CantAccessHistoryFile {
+        #[from]
+        inner: io::Error,
+    },
+    #[error("Wrong history file format: {inner}")]
+    CannotParseHistoryFile {
+        #[from]
+        inner: serde_json::Error,
+    },
+}
+
+/// Size of a result when it was seen the last time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct HistoryEntry {
+    size: u64,
+    /// Seconds since the Unix epoch
+    timestamp: u64,
+    /// Number of the run which recorded the entry
+    #[serde(default)]
+    run: u64,
+}
+
+/// Results not seen in this many runs are forgotten, so the history doesn't grow forever
+const MAX_RUNS: u64 = 100;
+
+/// Sizes of the results from the previous runs
+///
+/// Results missing in a run keep their older entries, so runs limited by filters
+/// don't erase the history of other paths. Entries not updated in [`MAX_RUNS`] runs
+/// are dropped, e.g. of deleted projects.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    /// Number of the recorded runs
+    #[serde(default)]
+    runs: u64,
+    entries: BTreeMap<PathBuf, HistoryEntry>,
+}
+
+impl History {
+    /// Load the history from `path`; a missing file is an empty history
+    pub fn load(path: &Path) -> Result<Self, HistoryError> {
+        if !path.exists() {
+            debug!("History file doesn't exist yet: {}", path.display());
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), HistoryError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Change of the size since the previous run; `None` when the path wasn't seen before
+    pub fn delta(&self, path: &Path, size: u64) -> Option<i64> {
+        self.entries
+            .get(path)
+            .map(|entry| size as i64 - entry.size as i64)
+    }
+
+    /// Remember the current sizes of all projects and tools
+    pub fn record(&mut self, results: &DiscoveryResults, now: &SystemTime) {
+        let timestamp = now
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let sizes = results
+            .projects
+            .iter()
+            .map(|r| (&r.path, r.size))
+            .chain(results.tools.iter().map(|r| (&r.path, r.size)));
+        self.runs += 1;
+        let run = self.runs;
+        for (path, size) in sizes {
+            self.entries.insert(
+                path.clone(),
+                HistoryEntry {
+                    size,
+                    timestamp,
+                    run,
+                },
+            );
+        }
+        self.entries.retain(|_, entry| entry.run + MAX_RUNS > run);
+    }
+}
+
+/// Location of the history file, following the XDG specification
+pub fn history_file(home_dir: &Path) -> PathBuf {
+    history_file_inner(home_dir, env::var_os("XDG_CACHE_HOME").map(PathBuf::from))
+}
+
+fn history_file_inner(home_dir: &Path, xdg_cache_home: Option<PathBuf>) -> PathBuf {
+    // Relative paths are considered invalid by the XDG specification
+    xdg_cache_home
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home_dir.join(".cache"))
+        .join("ddc/history.json")
+}
+
+/// Record the current results and return the history of the previous runs
+///
+/// Problems with the history file are only reported, as the history isn't essential.
+/// Without a home directory, there is no history at all.
+pub fn update_history(
+    home_dir: Option<&Path>,
+    results: &DiscoveryResults,
+    now: &SystemTime,
+) -> History {
+    match home_dir.map(history_file) {
+        Some(path) => update_history_file(&path, results, now),
+        None => History::default(),
+    }
+}
+
+/// Sizes from the previous runs without recording the current one, e.g. for `--read-only` runs
+pub fn load_history(home_dir: Option<&Path>) -> History {
+    match home_dir.map(history_file) {
+        Some(path) => load_history_file(&path),
+        None => History::default(),
+    }
+}
+
+fn load_history_file(path: &Path) -> History {
+    History::load(path).unwrap_or_else(|e| {
+        warn!("{e} ({})", path.display());
+        History::default()
+    })
+}
+
+fn update_history_file(path: &Path, results: &DiscoveryResults, now: &SystemTime) -> History {
+    let previous = load_history_file(path);
+
+    let mut current = previous.clone();
+    current.record(results, now);
+    if let Err(e) = current.save(path) {
+        warn!("{e} ({})", path.display());
+    }
+
+    previous
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{Language, ProjectResult};
+
+    fn results(size: u64) -> DiscoveryResults {
+        DiscoveryResults {
+            projects: vec![ProjectResult {
+                path: PathBuf::from("/projects/a/target"),
+                lang: Language::Rust,
+                size,
+                allocated_size: size,
+                last_update: None,
+                parent: None,
+                vcs_size: None,
+                orphaned: false,
+                root: None,
+            }],
+            tools: vec![],
+            vcs: vec![],
+            db: None,
+            detector_matches: vec![],
+            truncated: false,
+            scan_roots: vec![],
+        }
+    }
+
+    #[test]
+    fn test_update_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let history_path = history_file_inner(tmp.path(), None);
+        let path = Path::new("/projects/a/target");
+        let now = SystemTime::now();
+
+        // First run
+        let previous = update_history_file(&history_path, &results(100), &now);
+        assert_eq!(previous.delta(path, 100), None);
+
+        let previous = update_history_file(&history_path, &results(40), &now);
+        assert_eq!(previous.delta(path, 40), Some(-60));
+
+        let previous = update_history_file(&history_path, &results(90), &now);
+        assert_eq!(previous.delta(path, 90), Some(50));
+
+        assert_eq!(update_history(None, &results(90), &now), History::default());
+    }
+
+    #[test]
+    fn test_load_history_doesnt_record() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home_dir = tmp.path();
+        let history_path = history_file_inner(home_dir, None);
+
+        assert_eq!(load_history_file(&history_path), History::default());
+        assert!(!history_path.exists());
+
+        update_history_file(&history_path, &results(100), &SystemTime::now());
+        let history = load_history_file(&history_path);
+        assert_eq!(
+            history.delta(Path::new("/projects/a/target"), 40),
+            Some(-60)
+        );
+    }
+
+    #[test]
+    fn test_corrupted_history_is_replaced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home_dir = tmp.path();
+        let history_path = history_file_inner(home_dir, None);
+        fs::create_dir_all(history_path.parent().unwrap()).unwrap();
+        fs::write(&history_path, "not a json").unwrap();
+        assert!(matches!(
+            History::load(&history_path),
+            Err(HistoryError::CannotParseHistoryFile { .. })
+        ));
+
+        // The next run starts from scratch and rewrites the file with a valid history
+        let previous = update_history_file(&history_path, &results(100), &SystemTime::now());
+        assert_eq!(previous, History::default());
+        let history = History::load(&history_path).unwrap();
+        assert_eq!(
+            history.delta(Path::new("/projects/a/target"), 40),
+            Some(-60)
+        );
+    }
+
+    #[test]
+    fn test_history_forgets_results_not_seen_for_long() {
+        let now = SystemTime::now();
+        let mut other = results(10);
+        other.projects[0].path = PathBuf::from("/projects/deleted/target");
+
+        let mut history = History::default();
+        history.record(&other, &now);
+        for _ in 1..MAX_RUNS {
+            history.record(&results(100), &now);
+        }
+        assert_eq!(
+            history.delta(Path::new("/projects/deleted/target"), 10),
+            Some(0)
+        );
+
+        history.record(&results(100), &now);
+        assert_eq!(
+            history.delta(Path::new("/projects/deleted/target"), 10),
+            None
+        );
+        assert_eq!(history.delta(Path::new("/projects/a/target"), 100), Some(0));
+    }
+
+    #[test]
+    fn test_history_file_location() {
+        let home_dir = Path::new("/home/foo");
+        assert_eq!(
+            history_file_inner(home_dir, None),
+            PathBuf::from("/home/foo/.cache/ddc/history.json")
+        );
+        assert_eq!(
+            history_file_inner(home_dir, Some(PathBuf::from("/tmp/cache"))),
+            PathBuf::from("/tmp/cache/ddc/history.json")
+        );
+        assert_eq!(
+            history_file_inner(home_dir, Some(PathBuf::from("cache"))),
+            PathBuf::from("/home/foo/.cache/ddc/history.json")
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::PathBuf;
+
+use tracing::debug_span;
+
+use crate::files_db::FilesDB;
+
+/// Directory with the size of its whole subtree
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DirSize {
+    pub size: u64,
+    pub path: PathBuf,
+}
+
+/// Find the `count` biggest directories inside the scan roots, the biggest first
+///
+/// Unlike the detectors, this doesn't care what the directories contain, so it also
+/// finds media, datasets or logs. The roots themselves aren't reported, and roots
+/// nested in other roots are walked just once.
+///
+/// The subtree sizes are summed bottom-up in a single walk, so measuring every
+/// directory doesn't fill the size cache of the database.
+pub fn largest_dirs(db: &FilesDB, roots: &[PathBuf], count: usize) -> Vec<DirSize> {
+    let _guard = debug_span!("largest_dirs", count).entered();
+    if count == 0 {
+        return vec![];
+    }
+
+    let mut heap = BinaryHeap::with_capacity(count + 1);
+    let outermost = roots.iter().filter(|root| {
+        !roots
+            .iter()
+            .any(|other| other != *root && root.starts_with(other))
+    });
+    for root in outermost {
+        // Directories containing the current entry, with the sizes summed so far
+        let mut open: Vec<DirSize> = vec![];
+        for fi in db.iter_dir(root) {
+            while open
+                .last()
+                .is_some_and(|dir| !fi.path.starts_with(&dir.path))
+            {
+                close_dir(&mut open, roots, &mut heap, count);
+            }
+            let size = fi.size.unwrap_or_default();
+            if fi.is_dir {
+                open.push(DirSize {
+                    size,
+                    path: fi.path,
+                });
+            } else if let Some(dir) = open.last_mut() {
+                dir.size += size;
+            }
+        }
+        while !open.is_empty() {
+            close_dir(&mut open, roots, &mut heap, count);
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(dir)| dir)
+        .collect()
+}
+
+/// Add the size of the innermost open directory to its parent and offer it to the heap
+fn close_dir(
+    open: &mut Vec<DirSize>,
+    roots: &[PathBuf],
+    heap: &mut BinaryHeap<Reverse<DirSize>>,
+    count: usize,
+) {
+    let Some(dir) = open.pop() else {
+        return;
+    };
+    if let Some(parent) = open.last_mut() {
+        parent.size += dir.size;
+    }
+    if roots.contains(&dir.path) {
+        return;
+    }
+    // The heap keeps the smallest of the biggest directories on its top
+    heap.push(Reverse(dir));
+    if heap.len() > count {
+        heap.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::discovery::{PathLoader, ProgressEvent, ProgressReporter, WalkOptions};
+    use crate::loader::FullyParallelLoader;
+
+    #[derive(Clone)]
+    struct MockReporter;
+
+    impl ProgressReporter for MockReporter {
+        fn report(&self, _event: ProgressEvent) {}
+    }
+
+    #[test]
+    fn test_largest_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().to_path_buf();
+        fs::create_dir_all(root.join("media/photos")).unwrap();
+        fs::create_dir_all(root.join("logs")).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::write(root.join("media/photos/a.jpg"), vec![0; 300_000]).unwrap();
+        fs::write(root.join("media/video.mp4"), vec![0; 500_000]).unwrap();
+        fs::write(root.join("logs/app.log"), vec![0; 100_000]).unwrap();
+        let db = FullyParallelLoader.load_multiple_paths(
+            &[(root.clone(), None)],
+            WalkOptions::default(),
+            None::<MockReporter>,
+        );
+
+        let dirs = largest_dirs(&db, &[root.clone(), root.join("logs")], 2);
+        let paths: Vec<_> = dirs.iter().map(|d| d.path.clone()).collect();
+        assert_eq!(paths, vec![root.join("media"), root.join("media/photos")]);
+        assert_eq!(dirs[0].size, db.dir_size(&root.join("media")));
+        assert!(dirs[0].size > 800_000);
+        assert_eq!(dirs[1].size, db.dir_size(&root.join("media/photos")));
+
+        let dirs = largest_dirs(&db, std::slice::from_ref(&root), 10);
+        assert_eq!(dirs.len(), 4);
+        assert_eq!(dirs[2].path, root.join("logs"));
+        assert!(largest_dirs(&db, &[root], 0).is_empty());
+    }
+}
@@ -1,13 +1,25 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
+use std::time::{Duration, Instant};
 
 use crossbeam::channel;
+use ignore::gitignore::Gitignore;
 use jwalk::rayon::prelude::*;
-use jwalk::{Parallelism, WalkDir};
-use tracing::{debug, debug_span};
+use jwalk::{Parallelism, WalkDirGeneric};
+use tracing::{debug, debug_span, warn};
 
-use crate::discovery::{PathLoader, ProgressEvent, ProgressReporter};
-use crate::file_info::get_file_meta;
+use crate::discovery::{PathLoader, ProgressEvent, ProgressReporter, ScanPath, WalkOptions};
+#[cfg(unix)]
+use crate::file_info::current_uid;
+use crate::file_info::{FileMeta, Permissions, get_file_meta};
 use crate::files_db::FilesDB;
 
 #[allow(dead_code)]
@@ -18,40 +30,183 @@ pub enum LoaderError {
         #[from]
         inner: std::io::Error,
     },
+    #[error("Unable to read the manifest '{}': {inner}", path.display())]
+    CannotReadManifest {
+        path: PathBuf,
+        inner: std::io::Error,
+    },
+}
+
+/// Time spent in the loading phases, summed across all threads
+#[derive(Debug, Default)]
+pub struct LoaderProfile {
+    walk_ns: AtomicU64,
+    stat_ns: AtomicU64,
+    files: AtomicUsize,
+}
+
+impl LoaderProfile {
+    fn add_walk(&self, duration: Duration) {
+        self.walk_ns
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn add_stat(&self, duration: Duration) {
+        self.stat_ns
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+        self.files.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Display for LoaderProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let walk = Duration::from_nanos(self.walk_ns.load(Ordering::Relaxed));
+        let stat = Duration::from_nanos(self.stat_ns.load(Ordering::Relaxed));
+        let files = self.files.load(Ordering::Relaxed);
+        let files = if files >= 1000 {
+            format!("{}k", files / 1000)
+        } else {
+            files.to_string()
+        };
+        write!(
+            f,
+            "walk: {:.1}s, stat: {:.1}s, {files} files",
+            walk.as_secs_f64(),
+            stat.as_secs_f64()
+        )
+    }
+}
+
+/// Run `f` and add its duration to the profile by `add`, if profiling is enabled
+fn measure<T>(
+    profile: Option<&LoaderProfile>,
+    add: fn(&LoaderProfile, Duration),
+    f: impl FnOnce() -> T,
+) -> T {
+    let Some(profile) = profile else {
+        return f();
+    };
+    let started = Instant::now();
+    let result = f();
+    add(profile, started.elapsed());
+    result
+}
+
+fn report_profile(profile: Option<&LoaderProfile>) {
+    if let Some(profile) = profile {
+        eprintln!("Scan profile (summed across threads): {profile}");
+    }
+}
+
+/// Name of the files with gitignore-like patterns of paths excluded from scanning
+const IGNORE_FILE_NAME: &str = ".ddcignore";
+
+/// Ignore files of the directory being read and of its ancestors, the deepest last
+type IgnoreStack = Vec<Arc<Gitignore>>;
+
+/// Whether the deepest ignore file with a matching pattern ignores the path
+fn is_ignored(ignores: &IgnoreStack, path: &Path, is_dir: bool) -> bool {
+    ignores
+        .iter()
+        .rev()
+        .map(|ignore| ignore.matched(path, is_dir))
+        .find(|m| !m.is_none())
+        .is_some_and(|m| m.is_ignore())
 }
 
-fn walk_dir_paths(directory: &PathBuf) -> Vec<PathBuf> {
-    WalkDir::new(directory)
+fn walk_dir_paths(
+    directory: &PathBuf,
+    options: WalkOptions,
+    max_depth: Option<usize>,
+) -> Vec<PathBuf> {
+    let mut walk_dir = WalkDirGeneric::<(IgnoreStack, ())>::new(directory)
         .parallelism(Parallelism::Serial)
-        .skip_hidden(false)
+        .skip_hidden(options.skip_hidden);
+    if let Some(max_depth) = max_depth {
+        walk_dir = walk_dir.max_depth(max_depth);
+    }
+    // The device is captured per scan root, so roots on different mounts are all scanned,
+    // but nothing below them crosses into another file system.
+    #[cfg(unix)]
+    let root_dev = options
+        .one_file_system
+        .then(|| std::fs::metadata(directory).map(|m| m.dev()).ok())
+        .flatten();
+
+    walk_dir = walk_dir.process_read_dir(move |_depth, path, ignores, children| {
+        // Hidden ignore files aren't listed when hidden files are skipped
+        let has_ignore_file = if options.skip_hidden {
+            path.join(IGNORE_FILE_NAME).is_file()
+        } else {
+            children.iter().any(|child| {
+                child
+                    .as_ref()
+                    .is_ok_and(|e| e.file_name == IGNORE_FILE_NAME && !e.file_type.is_dir())
+            })
+        };
+        if has_ignore_file {
+            let (ignore, error) = Gitignore::new(path.join(IGNORE_FILE_NAME));
+            if let Some(error) = error {
+                warn!(
+                    "Invalid patterns in {}: {error}",
+                    path.join(IGNORE_FILE_NAME).display()
+                );
+            }
+            ignores.push(Arc::new(ignore));
+        }
+
+        children.retain(|child| {
+            let Ok(entry) = child else {
+                return true;
+            };
+            let is_dir = entry.file_type.is_dir();
+            if is_ignored(ignores, &entry.path(), is_dir) {
+                return false;
+            }
+            #[cfg(unix)]
+            if is_dir && let Some(root_dev) = root_dev {
+                return entry.metadata().is_ok_and(|meta| meta.dev() == root_dev);
+            }
+            true
+        });
+    });
+
+    walk_dir
         .into_iter()
         .filter_map(|res| res.map(|de| de.path()).ok())
         .collect::<Vec<_>>()
 }
 
-#[allow(dead_code)]
 #[derive(Default)]
 pub struct BaseLoader;
 
 impl PathLoader for BaseLoader {
     fn load_multiple_paths<R: ProgressReporter>(
         &self,
-        scan_paths: &[PathBuf],
+        scan_paths: &[ScanPath],
+        options: WalkOptions,
         _progress: Option<R>,
     ) -> FilesDB {
         let (sender, receiver) = channel();
+        let profile = options.profile.then(LoaderProfile::default);
 
         scan_paths
             .into_par_iter()
-            .for_each_with(sender, |sender, path| {
-                let paths = walk_dir_paths(path);
+            .for_each_with(sender, |sender, (path, max_depth)| {
+                let paths = measure(profile.as_ref(), LoaderProfile::add_walk, || {
+                    walk_dir_paths(path, options, *max_depth)
+                });
                 paths
                     .into_iter()
-                    .filter_map(|path| match get_file_meta(&path) {
-                        Ok(meta) => Some((path, meta)),
-                        Err(_e) => {
-                            debug!("Failed to load info for {}", path.display());
-                            None
+                    .filter_map(|path| {
+                        match measure(profile.as_ref(), LoaderProfile::add_stat, || {
+                            get_file_meta(&path)
+                        }) {
+                            Ok(meta) => Some((path, meta)),
+                            Err(_e) => {
+                                debug!("Failed to load info for {}", path.display());
+                                None
+                            }
                         }
                     })
                     .for_each(|(path, meta)| sender.send((path, meta)).unwrap());
@@ -61,6 +216,7 @@ impl PathLoader for BaseLoader {
         receiver.iter().for_each(|(path, meta)| {
             db.add(path, meta);
         });
+        report_profile(profile.as_ref());
 
         db
     }
@@ -77,12 +233,14 @@ impl FullyParallelLoader {
 impl PathLoader for FullyParallelLoader {
     fn load_multiple_paths<R: ProgressReporter>(
         &self,
-        scan_paths: &[PathBuf],
+        scan_paths: &[ScanPath],
+        options: WalkOptions,
         progress: Option<R>,
     ) -> FilesDB {
         let (sources_sender, sources_receiver) = channel::unbounded();
         let (paths_sender, paths_receiver) = channel::unbounded();
         let (infos_sender, infos_receiver) = channel::unbounded();
+        let profile = options.profile.then(|| Arc::new(LoaderProfile::default()));
 
         progress.as_ref().inspect(|r| {
             r.report(ProgressEvent::WalkStart {
@@ -90,8 +248,8 @@ impl PathLoader for FullyParallelLoader {
             })
         });
 
-        scan_paths.iter().for_each(|path| {
-            sources_sender.send(path.clone()).unwrap();
+        scan_paths.iter().for_each(|scan_path| {
+            sources_sender.send(scan_path.clone()).unwrap();
         });
         drop(sources_sender);
 
@@ -99,10 +257,14 @@ impl PathLoader for FullyParallelLoader {
             let my_paths_sender = paths_sender.clone();
             let my_sources_receiver = sources_receiver.clone();
             let my_progress = progress.clone();
+            let my_profile = profile.clone();
             rayon::spawn(move || {
-                my_sources_receiver.iter().for_each(|path| {
+                my_sources_receiver.iter().for_each(|(path, max_depth)| {
                     let _guard = debug_span!("walk_dir", path = ?path).entered();
-                    let loaded_paths = walk_dir_paths(&path);
+                    let loaded_paths =
+                        measure(my_profile.as_deref(), LoaderProfile::add_walk, || {
+                            walk_dir_paths(&path, options, max_depth)
+                        });
                     my_progress.as_ref().inspect(|r| {
                         r.report(ProgressEvent::WalkAddPaths {
                             count: loaded_paths.len(),
@@ -121,12 +283,16 @@ impl PathLoader for FullyParallelLoader {
             let my_paths_receiver = paths_receiver.clone();
             let my_infos_sender = infos_sender.clone();
             let my_progress = progress.clone();
+            let my_profile = profile.clone();
             rayon::spawn(move || {
                 my_paths_receiver.iter().for_each(|path| {
                     my_progress
                         .as_ref()
                         .inspect(|r| r.report(ProgressEvent::WalkAdvance));
-                    if let Ok(meta) = get_file_meta(&path) {
+                    let meta = measure(my_profile.as_deref(), LoaderProfile::add_stat, || {
+                        get_file_meta(&path)
+                    });
+                    if let Ok(meta) = meta {
                         my_infos_sender.send((path, meta)).unwrap();
                     } else {
                         debug!("Failed to load info for {}", path.display());
@@ -142,7 +308,214 @@ impl PathLoader for FullyParallelLoader {
             db.add(path, meta);
         });
         drop(infos_receiver);
+        report_profile(profile.as_deref());
+
+        progress
+            .as_ref()
+            .inspect(|r| r.report(ProgressEvent::WalkFinished));
+
+        db
+    }
+}
+
+/// Loader selectable at runtime
+///
+/// Mostly useful for comparing loaders and for debugging nondeterminism
+/// caused by the parallel loading.
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LoaderKind {
+    /// Walks definitions in parallel, but loads metadata sequentially per definition
+    Base,
+    /// Walks and loads metadata in separate thread pools
+    #[default]
+    Parallel,
+}
+
+impl PathLoader for LoaderKind {
+    fn load_multiple_paths<R: ProgressReporter>(
+        &self,
+        scan_paths: &[ScanPath],
+        options: WalkOptions,
+        progress: Option<R>,
+    ) -> FilesDB {
+        match self {
+            LoaderKind::Base => BaseLoader.load_multiple_paths(scan_paths, options, progress),
+            LoaderKind::Parallel => {
+                FullyParallelLoader.load_multiple_paths(scan_paths, options, progress)
+            }
+        }
+    }
+}
+
+/// Path listed in a manifest, with its size when it's known upfront
+#[derive(Debug, Clone, PartialEq)]
+struct ManifestEntry {
+    path: PathBuf,
+    size: Option<u64>,
+    is_dir: bool,
+}
+
+impl ManifestEntry {
+    /// Parse a `path` or `size<TAB>path` line; relative paths are resolved against
+    /// the current directory
+    fn parse(line: &str) -> Option<Self> {
+        let split = line.split_once('\t');
+        let (size, path) = match split.map(|(size, path)| (size.trim().parse().ok(), path)) {
+            Some((Some(size), path)) => (Some(size), path),
+            _ => (None, line),
+        };
+        if path.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            path: std::path::absolute(path).ok()?,
+            size,
+            is_dir: path.ends_with(std::path::MAIN_SEPARATOR),
+        })
+    }
+
+    /// Metadata of the entry, loaded from the disk unless the size is provided
+    fn meta(&self) -> io::Result<FileMeta> {
+        let Some(size) = self.size else {
+            return get_file_meta(&self.path);
+        };
+        Ok(FileMeta {
+            is_dir: self.is_dir,
+            size: Some(size),
+            allocated: None,
+            touched: None,
+            accessed: None,
+            permissions: assumed_permissions(),
+        })
+    }
+}
+
+/// Listed files aren't touched when their size is provided, so they are assumed to
+/// belong to the user
+fn assumed_permissions() -> Permissions {
+    Permissions {
+        #[cfg(unix)]
+        uid: current_uid(),
+        #[cfg(unix)]
+        mode: 0,
+    }
+}
+
+/// Whether `path` is inside `root` and not deeper than `max_depth` below it
+fn is_within(path: &Path, (root, max_depth): &ScanPath) -> bool {
+    path.strip_prefix(root).is_ok_and(|relative| {
+        max_depth.is_none_or(|max_depth| relative.components().count() <= max_depth)
+    })
+}
+
+/// Loads a prebuilt list of paths instead of walking the disk
+///
+/// Meant for huge trees where another tool (`fd`, `find`, a backup catalog) already
+/// knows the files. Each line holds a path, optionally preceded by its size in bytes
+/// and a tab, as printed by `find -printf '%s\t%p\n'`. Paths with a size are trusted
+/// and never touched, a trailing `/` marks them as directories; the others are stat'ed.
+/// Only paths inside the scanned roots are loaded, and walk options don't apply.
+#[derive(Debug, Default)]
+pub struct ManifestLoader {
+    entries: Vec<ManifestEntry>,
+}
+
+impl ManifestLoader {
+    /// Read the manifest from `path`, or from the standard input when it's `-`
+    pub fn open(path: &Path) -> Result<Self, LoaderError> {
+        let result = if path == Path::new("-") {
+            Self::from_reader(io::stdin().lock())
+        } else {
+            File::open(path).and_then(|file| Self::from_reader(BufReader::new(file)))
+        };
+        result.map_err(|inner| LoaderError::CannotReadManifest {
+            path: path.to_path_buf(),
+            inner,
+        })
+    }
+
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            entries.extend(ManifestEntry::parse(&line?));
+        }
+        Ok(Self { entries })
+    }
+}
+
+impl PathLoader for ManifestLoader {
+    fn load_multiple_paths<R: ProgressReporter>(
+        &self,
+        scan_paths: &[ScanPath],
+        _options: WalkOptions,
+        progress: Option<R>,
+    ) -> FilesDB {
+        progress.as_ref().inspect(|r| {
+            r.report(ProgressEvent::WalkStart {
+                count: scan_paths.len(),
+            })
+        });
+        let listed: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|entry| scan_paths.iter().any(|scan| is_within(&entry.path, scan)))
+            .collect();
+        progress.as_ref().inspect(|r| {
+            r.report(ProgressEvent::WalkAddPaths {
+                count: listed.len(),
+            })
+        });
 
+        let mut metas: HashMap<PathBuf, FileMeta> = listed
+            .into_par_iter()
+            .filter_map(|entry| {
+                progress
+                    .as_ref()
+                    .inspect(|r| r.report(ProgressEvent::WalkAdvance));
+                match entry.meta() {
+                    Ok(meta) => Some((entry.path.clone(), meta)),
+                    Err(e) => {
+                        debug!("Failed to load info for {}: {e}", entry.path.display());
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        // Manifests usually list just files, but the detectors need their directories
+        let listed_paths: Vec<_> = metas.keys().cloned().collect();
+        for path in listed_paths {
+            for ancestor in path.ancestors().skip(1) {
+                if !scan_paths
+                    .iter()
+                    .any(|(root, _)| ancestor.starts_with(root))
+                {
+                    break;
+                }
+                if let Some(meta) = metas.get_mut(ancestor) {
+                    if meta.is_dir {
+                        break;
+                    }
+                    meta.is_dir = true;
+                    continue;
+                }
+                let meta = get_file_meta(ancestor).unwrap_or(FileMeta {
+                    is_dir: true,
+                    size: None,
+                    allocated: None,
+                    touched: None,
+                    accessed: None,
+                    permissions: assumed_permissions(),
+                });
+                metas.insert(ancestor.to_path_buf(), meta);
+            }
+        }
+
+        let mut db = FilesDB::new();
+        metas
+            .into_iter()
+            .for_each(|(path, meta)| db.add(path, meta));
         progress
             .as_ref()
             .inspect(|r| r.report(ProgressEvent::WalkFinished));
@@ -151,6 +524,49 @@ impl PathLoader for FullyParallelLoader {
     }
 }
 
+/// Loader used for the scan, either walking the disk or reading a manifest
+#[derive(Debug)]
+pub enum SelectedLoader {
+    Walk(LoaderKind),
+    Manifest(ManifestLoader),
+}
+
+impl Default for SelectedLoader {
+    fn default() -> Self {
+        Self::Walk(LoaderKind::default())
+    }
+}
+
+impl PathLoader for SelectedLoader {
+    fn load_multiple_paths<R: ProgressReporter>(
+        &self,
+        scan_paths: &[ScanPath],
+        options: WalkOptions,
+        progress: Option<R>,
+    ) -> FilesDB {
+        match self {
+            SelectedLoader::Walk(kind) => kind.load_multiple_paths(scan_paths, options, progress),
+            SelectedLoader::Manifest(manifest) => {
+                manifest.load_multiple_paths(scan_paths, options, progress)
+            }
+        }
+    }
+}
+
+/// Replace the subtree of `path` in `db` with the current state of the disk
+///
+/// Useful for refreshing a single path without rescanning everything. When the path
+/// doesn't exist anymore, it's just removed from `db`.
+pub fn reload_subtree(db: &mut FilesDB, path: &PathBuf, options: WalkOptions) {
+    db.remove_subtree(path);
+    walk_dir_paths(path, options, None)
+        .into_iter()
+        .for_each(|path| match get_file_meta(&path) {
+            Ok(meta) => db.add(path, meta),
+            Err(e) => debug!("Unable to load metadata for {}: {e}", path.display()),
+        });
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -158,6 +574,7 @@ mod tests {
     use tempfile::tempdir;
 
     use super::*;
+    use crate::test_utils::CurrentDir;
 
     #[derive(Clone)]
     struct MockReporter;
@@ -176,6 +593,12 @@ mod tests {
         perform_test(FullyParallelLoader::default());
     }
 
+    #[test]
+    fn test_selectable_loader() {
+        perform_test(LoaderKind::Base);
+        perform_test(LoaderKind::Parallel);
+    }
+
     fn perform_test<L: PathLoader>(loader: L) {
         let tmp = tempdir().unwrap();
         let root_path = tmp.path();
@@ -184,7 +607,11 @@ mod tests {
         fs::create_dir_all(&dir_path).unwrap();
         fs::write(&file_path, "Hello, World!").unwrap();
 
-        let db = loader.load_multiple_paths(&[root_path.to_path_buf()], Some(MockReporter));
+        let db = loader.load_multiple_paths(
+            &[(root_path.to_path_buf(), None)],
+            WalkOptions::default(),
+            Some(MockReporter),
+        );
 
         assert!(db.exists(&root_path.join("foo")));
         assert!(db.is_dir(&root_path.join("foo")));
@@ -193,4 +620,214 @@ mod tests {
         assert!(db.exists(&root_path.join("foo/bar/baz.txt")));
         assert!(!db.is_dir(&root_path.join("foo/bar/baz.txt")));
     }
+
+    #[test]
+    fn test_manifest_entry_parse() {
+        let _cwd = CurrentDir::keep();
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(
+            ManifestEntry::parse("123\t/data/foo.bin"),
+            Some(ManifestEntry {
+                path: PathBuf::from("/data/foo.bin"),
+                size: Some(123),
+                is_dir: false,
+            })
+        );
+        assert_eq!(
+            ManifestEntry::parse("4096\t/data/bar/"),
+            Some(ManifestEntry {
+                path: PathBuf::from("/data/bar"),
+                size: Some(4096),
+                is_dir: true,
+            })
+        );
+        assert_eq!(
+            ManifestEntry::parse("relative/with\ttab"),
+            Some(ManifestEntry {
+                path: cwd.join("relative/with\ttab"),
+                size: None,
+                is_dir: false,
+            })
+        );
+        assert_eq!(ManifestEntry::parse(""), None);
+    }
+
+    #[test]
+    fn test_manifest_loader() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path();
+        fs::create_dir_all(root_path.join("foo/bar")).unwrap();
+        fs::write(root_path.join("foo/bar/baz.txt"), "Hello, World!").unwrap();
+        let manifest = format!(
+            "{root}/foo/bar/baz.txt\n\
+             1000\t{root}/project/target/app\n\
+             1000\t{root}/project/target/deep/lib.rlib\n\
+             \n\
+             1000\t/outside/file.bin\n",
+            root = root_path.display()
+        );
+        let loader = ManifestLoader::from_reader(manifest.as_bytes()).unwrap();
+
+        let db = loader.load_multiple_paths(
+            &[(root_path.to_path_buf(), None)],
+            WalkOptions::default(),
+            Some(MockReporter),
+        );
+        assert!(db.is_dir(&root_path.join("foo/bar")));
+        assert!(!db.is_dir(&root_path.join("foo/bar/baz.txt")));
+        assert_eq!(db.dir_size(&root_path.join("foo/bar/baz.txt")), 13);
+        // Not on the disk, but listed with sizes
+        assert!(db.is_dir(&root_path.join("project/target")));
+        assert_eq!(db.dir_size(&root_path.join("project")), 2000);
+        assert!(!db.exists(Path::new("/outside/file.bin")));
+
+        let db = loader.load_multiple_paths(
+            &[(root_path.join("project"), Some(2))],
+            WalkOptions::default(),
+            None::<MockReporter>,
+        );
+        assert!(db.exists(&root_path.join("project/target/app")));
+        assert!(!db.exists(&root_path.join("project/target/deep/lib.rlib")));
+        assert!(!db.exists(&root_path.join("foo/bar/baz.txt")));
+    }
+
+    #[test]
+    fn test_manifest_loader_open() {
+        let tmp = tempdir().unwrap();
+        let path = tmp.path().join("manifest.txt");
+        assert!(matches!(
+            ManifestLoader::open(&path),
+            Err(LoaderError::CannotReadManifest { .. })
+        ));
+
+        fs::write(&path, "1\t/a\n2\t/b\n").unwrap();
+        assert_eq!(ManifestLoader::open(&path).unwrap().entries.len(), 2);
+    }
+
+    #[test]
+    fn test_loader_profile() {
+        let profile = LoaderProfile::default();
+        assert_eq!(profile.to_string(), "walk: 0.0s, stat: 0.0s, 0 files");
+
+        profile.add_walk(Duration::from_millis(2100));
+        for _ in 0..182_500 {
+            profile.add_stat(Duration::from_nanos(23_562));
+        }
+        assert_eq!(profile.to_string(), "walk: 2.1s, stat: 4.3s, 182k files");
+
+        let result = measure(Some(&profile), LoaderProfile::add_stat, || 42);
+        assert_eq!(result, 42);
+        assert_eq!(profile.files.load(Ordering::Relaxed), 182_501);
+    }
+
+    #[test]
+    fn test_walk_dir_paths_skip_hidden() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path().join(".root");
+        fs::create_dir_all(root_path.join(".git/objects")).unwrap();
+        fs::create_dir_all(root_path.join("src")).unwrap();
+
+        let paths = walk_dir_paths(&root_path, WalkOptions::default(), None);
+        assert!(paths.contains(&root_path.join(".git/objects")));
+
+        let paths = walk_dir_paths(
+            &root_path,
+            WalkOptions {
+                skip_hidden: true,
+                ..Default::default()
+            },
+            None,
+        );
+        assert!(paths.contains(&root_path));
+        assert!(paths.contains(&root_path.join("src")));
+        assert!(!paths.contains(&root_path.join(".git")));
+    }
+
+    #[test]
+    fn test_walk_dir_paths_max_depth() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path().to_path_buf();
+        fs::create_dir_all(root_path.join("a/b/c")).unwrap();
+
+        let paths = walk_dir_paths(&root_path, WalkOptions::default(), Some(1));
+        assert!(paths.contains(&root_path));
+        assert!(paths.contains(&root_path.join("a")));
+        assert!(!paths.contains(&root_path.join("a/b")));
+
+        let paths = walk_dir_paths(&root_path, WalkOptions::default(), None);
+        assert!(paths.contains(&root_path.join("a/b/c")));
+    }
+
+    #[test]
+    fn test_reload_subtree() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path().to_path_buf();
+        let project = root_path.join("project");
+        fs::create_dir_all(project.join("target")).unwrap();
+        fs::write(project.join("target/old.o"), "old").unwrap();
+        let mut db = BaseLoader.load_multiple_paths(
+            &[(root_path.clone(), None)],
+            WalkOptions::default(),
+            None::<MockReporter>,
+        );
+
+        fs::remove_file(project.join("target/old.o")).unwrap();
+        fs::write(project.join("target/new.o"), "new!").unwrap();
+        reload_subtree(&mut db, &project, WalkOptions::default());
+        assert!(!db.exists(&project.join("target/old.o")));
+        assert!(db.exists(&project.join("target/new.o")));
+        assert!(db.exists(&root_path));
+
+        fs::remove_dir_all(&project).unwrap();
+        reload_subtree(&mut db, &project, WalkOptions::default());
+        assert!(!db.exists(&project));
+        assert!(db.exists(&root_path));
+    }
+
+    #[test]
+    fn test_walk_dir_paths_ddcignore() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path().to_path_buf();
+        fs::create_dir_all(root_path.join("archive/old/target")).unwrap();
+        fs::create_dir_all(root_path.join("project/target")).unwrap();
+        fs::create_dir_all(root_path.join("project/data")).unwrap();
+        fs::write(root_path.join(".ddcignore"), "archive/\n*.log\n").unwrap();
+        fs::write(root_path.join("project/.ddcignore"), "data\n!keep.log\n").unwrap();
+        fs::write(root_path.join("project/build.log"), "log").unwrap();
+        fs::write(root_path.join("project/keep.log"), "log").unwrap();
+
+        for skip_hidden in [false, true] {
+            let options = WalkOptions {
+                skip_hidden,
+                ..Default::default()
+            };
+            let paths = walk_dir_paths(&root_path, options, None);
+            assert!(!paths.contains(&root_path.join("archive")));
+            assert!(!paths.contains(&root_path.join("archive/old/target")));
+            assert!(!paths.contains(&root_path.join("project/data")));
+            assert!(!paths.contains(&root_path.join("project/build.log")));
+            assert!(paths.contains(&root_path.join("project/keep.log")));
+            assert!(paths.contains(&root_path.join("project/target")));
+        }
+    }
+
+    #[test]
+    fn test_walk_dir_paths_one_file_system() {
+        let tmp = tempdir().unwrap();
+        let root_path = tmp.path().to_path_buf();
+        fs::create_dir_all(root_path.join("src/nested")).unwrap();
+        fs::write(root_path.join("src/nested/main.rs"), "fn main() {}").unwrap();
+
+        // Everything is on the same device as the root, so nothing is skipped
+        let options = WalkOptions {
+            one_file_system: true,
+            ..Default::default()
+        };
+        let mut paths = walk_dir_paths(&root_path, options, None);
+        let mut expected = walk_dir_paths(&root_path, WalkOptions::default(), None);
+        paths.sort();
+        expected.sort();
+        assert_eq!(paths, expected);
+        assert!(paths.contains(&root_path.join("src/nested/main.rs")));
+    }
 }
@@ -15,6 +15,8 @@ impl FormatTime for SinceStart {
 #[allow(dead_code)]
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum LoggingLevel {
+    /// Only errors, e.g. when the output is consumed by scripts
+    Quiet,
     #[default]
     Silent,
     Verbose,
@@ -35,6 +37,7 @@ pub fn setup_logging(level: LoggingLevel) -> Result<()> {
     let timer = SinceStart(Instant::now());
 
     let (log_level, tracing_level) = match level {
+        LoggingLevel::Quiet => (tracing::Level::ERROR, FmtSpan::NONE),
         LoggingLevel::Silent => (tracing::Level::WARN, FmtSpan::NONE),
         LoggingLevel::Verbose => (tracing::Level::DEBUG, FmtSpan::NONE),
         LoggingLevel::Traces => (tracing::Level::DEBUG, FmtSpan::CLOSE | FmtSpan::ENTER),
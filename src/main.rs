@@ -1,36 +1,52 @@
-#![feature(btree_cursors)]
 #![feature(duration_constructors)]
 
+use std::path::Path;
+
 use anyhow::Context;
 use clap::Parser;
 use home::home_dir;
 use tracing::{debug, debug_span};
 
-use crate::analyze::{analyze, show_default_definitions};
+use crate::analyze::{AnalyzeError, analyze, show_default_definitions};
 use crate::browse::browse;
 use crate::cli::{AnalyzeArgs, CliArgs, Commands, UiConfig};
-use crate::generate_config::generate_config;
-use crate::logging::{LoggingLevel, setup_logging};
+use crate::config::{ConfigError, DefaultCommand, load_general_config};
+use crate::config_check::config_check;
+use crate::doctor::doctor;
+use crate::generate_config::{GenerateConfigError, generate_config, offer_config_generation};
+use crate::logging::setup_logging;
 
 mod analyze;
 mod browse;
 mod browse_tui;
 mod cli;
 mod config;
+mod config_check;
 mod discovery;
 mod display;
 mod display_tools;
+mod doctor;
+mod duplicates;
 mod file_info;
 mod files_db;
 mod generate_config;
+mod git_size;
+mod history;
+mod largest_dirs;
 mod loader;
 mod logging;
+#[cfg(test)]
+mod test_utils;
 mod vcs_postprocess;
+mod venv_postprocess;
 
 fn main() -> anyhow::Result<()> {
     let args = CliArgs::parse();
+    if let Err(e) = args.check_conflicts() {
+        e.exit();
+    }
 
-    setup_logging(LoggingLevel::from(args.verbosity)).context("Failed to set up logging")?;
+    setup_logging(args.logging_level()).context("Failed to set up logging")?;
     {
         let _guard = debug_span!("creating_thread_pool").entered();
         rayon::ThreadPoolBuilder::new()
@@ -38,17 +54,168 @@ fn main() -> anyhow::Result<()> {
             .context("Failed to create a thread pool")?;
     }
 
-    let home_dir = home_dir().context("Couldn't identify your home directory.")?;
-    debug!("Home directory resolved as: {}", &home_dir.display());
+    // Only some code paths need the home directory, so it's required only there
+    let home_dir = home_dir();
+    match home_dir.as_deref() {
+        Some(home_dir) => debug!("Home directory resolved as: {}", home_dir.display()),
+        None => debug!("Home directory couldn't be resolved"),
+    }
+    let home_dir = home_dir.as_deref();
 
     let ui_config = UiConfig::from(&args);
-    match args.command {
-        Some(Commands::GenerateConfig) => generate_config(&home_dir)?,
-        Some(Commands::ShowDefinitions) => show_default_definitions(&home_dir),
-        Some(Commands::Analyze(cmd_args)) => analyze(&cmd_args, &ui_config, &home_dir)?,
-        Some(Commands::Browse(cmd_args)) => browse(&cmd_args, &ui_config, &home_dir)?,
-        None => analyze(&AnalyzeArgs::default(), &ui_config, &home_dir)?,
+    let command = match args.command {
+        Some(command) => command,
+        None => default_command(home_dir),
+    };
+    match command {
+        Commands::GenerateConfig(cmd_args) => generate_config(&cmd_args, home_dir)?,
+        Commands::ShowDefinitions => {
+            show_default_definitions(home_dir.context("Couldn't identify your home directory.")?)
+        }
+        Commands::Analyze(cmd_args) => run_analyze(&cmd_args, &ui_config, home_dir)?,
+        Commands::Browse(cmd_args) => browse(&cmd_args, &ui_config, home_dir)?,
+        Commands::Doctor(cmd_args) => doctor(&cmd_args, home_dir),
+        Commands::ConfigCheck(cmd_args) => config_check(&cmd_args, home_dir)?,
     };
 
     Ok(())
 }
+
+/// Command run when `ddc` is called without any subcommand, as configured in `[general]`
+///
+/// The command is parsed as if it was given on the command line, so its arguments
+/// read the environment variables, e.g. `DDC_LOADER`, and the configuration file
+/// is picked the same way as for the explicit command.
+fn default_command(home_dir: Option<&Path>) -> Commands {
+    let name = match load_general_config(home_dir, None).default_command {
+        DefaultCommand::Analyze => "analyze",
+        DefaultCommand::Browse => "browse",
+    };
+    CliArgs::parse_from(["ddc", name])
+        .command
+        .expect("The subcommand is always given")
+}
+
+fn run_analyze(
+    cmd_args: &AnalyzeArgs,
+    ui_config: &UiConfig,
+    home_dir: Option<&Path>,
+) -> anyhow::Result<()> {
+    let result = analyze_on_first_run(cmd_args, ui_config, home_dir, offer_config_generation)?;
+    match result {
+        Err(e @ AnalyzeError::SizeLimitExceeded { .. }) => {
+            eprintln!("Error: {e}");
+            std::process::exit(AnalyzeError::SIZE_LIMIT_EXCEEDED_EXIT_CODE);
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Run the analysis; on the first run, offer creating the configuration instead of just failing
+///
+/// Nothing is offered with `--read-only`, as the configuration file would be written.
+fn analyze_on_first_run<F>(
+    cmd_args: &AnalyzeArgs,
+    ui_config: &UiConfig,
+    home_dir: Option<&Path>,
+    offer: F,
+) -> Result<Result<(), AnalyzeError>, GenerateConfigError>
+where
+    F: FnOnce(&Path) -> Result<bool, GenerateConfigError>,
+{
+    let result = match analyze(cmd_args, ui_config, home_dir) {
+        Err(AnalyzeError::ConfigError {
+            inner: ConfigError::ConfigurationFileNotFound,
+        }) if !cmd_args.shared.read_only
+            && let Some(home_dir) = home_dir
+            && offer(home_dir)? =>
+        {
+            analyze(cmd_args, ui_config, Some(home_dir))
+        }
+        result => result,
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+    use crate::config::{CONFIG_PATH_ENV, load_config_file};
+    use crate::loader::LoaderKind;
+    use crate::test_utils::{CurrentDir, is_child_test, run_with_env};
+
+    #[test]
+    fn test_read_only_first_run_doesnt_offer_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home_path = tmp.path();
+        let _cwd = CurrentDir::set(home_path);
+        let mut cmd_args = AnalyzeArgs::default();
+        cmd_args.shared.read_only = true;
+
+        let result = analyze_on_first_run(&cmd_args, &UiConfig::default(), Some(home_path), |_| {
+            panic!("Configuration must not be offered in the read-only mode")
+        })
+        .unwrap();
+
+        assert!(matches!(
+            result,
+            Err(AnalyzeError::ConfigError {
+                inner: ConfigError::ConfigurationFileNotFound
+            })
+        ));
+        assert!(!home_path.join(".config/ddc.toml").exists());
+        assert!(!home_path.join(".ddc.toml").exists());
+
+        // Without the read-only mode, the configuration is offered
+        cmd_args.shared.read_only = false;
+        let mut offered = false;
+        let result = analyze_on_first_run(&cmd_args, &UiConfig::default(), Some(home_path), |_| {
+            offered = true;
+            Ok(false)
+        })
+        .unwrap();
+        assert!(offered);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_default_command_uses_config_from_env() {
+        if is_child_test() {
+            let config_path = env::var_os(CONFIG_PATH_ENV).unwrap();
+            let home_path = Path::new(&config_path).parent().unwrap();
+            let Commands::Browse(cmd_args) = default_command(Some(home_path)) else {
+                panic!("Browse command expected");
+            };
+            assert_eq!(cmd_args.shared.loader, LoaderKind::Base);
+            let config = load_config_file(Some(home_path), cmd_args.shared.config.as_deref());
+            assert_eq!(config.unwrap().paths[0].roots(), [Path::new("custom/")]);
+            return;
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        let home_path = tmp.path();
+        fs::write(
+            home_path.join(".ddc.toml"),
+            "[[paths]]\npath = \"projects/\"",
+        )
+        .unwrap();
+        fs::write(
+            home_path.join("custom.toml"),
+            "[general]\ndefault_command = \"browse\"\n\n[[paths]]\npath = \"custom/\"",
+        )
+        .unwrap();
+
+        let config_path = home_path.join("custom.toml");
+        assert!(run_with_env(
+            "tests::test_default_command_uses_config_from_env",
+            &[
+                (CONFIG_PATH_ENV, config_path.as_os_str()),
+                ("DDC_LOADER", "base".as_ref()),
+            ]
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard};
+
+static CURRENT_DIR_LOCK: Mutex<()> = Mutex::new(());
+
+/// Working directory of the process for the lifetime of the guard
+///
+/// The working directory is shared by all tests running in parallel, so the tests
+/// depending on it are serialized. The previous directory is restored on drop, so
+/// the process never stays in a removed temporary directory, which breaks e.g. gix.
+pub struct CurrentDir {
+    previous: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl CurrentDir {
+    /// Switch to `path`; the guard must be dropped before `path` is removed
+    pub fn set(path: &Path) -> Self {
+        let guard = Self::keep();
+        env::set_current_dir(path).unwrap();
+        guard
+    }
+
+    /// Keep the current working directory unchanged
+    pub fn keep() -> Self {
+        let lock = CURRENT_DIR_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Self {
+            previous: env::current_dir().unwrap(),
+            _lock: lock,
+        }
+    }
+}
+
+impl Drop for CurrentDir {
+    fn drop(&mut self) {
+        let _ = env::set_current_dir(&self.previous);
+    }
+}
+
+/// Variable marking the child process started by [`run_with_env`]
+const CHILD_TEST_ENV: &str = "DDC_TEST_CHILD";
+
+/// Run the test `name` again in a child process with the environment variables `vars` set
+///
+/// The environment is shared by all tests running in parallel, so a test depending
+/// on a variable sets it only for a child running just this test. The test checks
+/// [`is_child_test`] to tell which part it runs. Returns whether the child passed.
+pub fn run_with_env<V: AsRef<OsStr>>(name: &str, vars: &[(&str, V)]) -> bool {
+    let output = Command::new(env::current_exe().unwrap())
+        .args([name, "--exact", "--test-threads=1"])
+        .envs(vars.iter().map(|(key, value)| (key, value)))
+        .env(CHILD_TEST_ENV, "1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    output.status.success() && stdout.contains("1 passed")
+}
+
+/// Whether the current test runs in the child process started by [`run_with_env`]
+pub fn is_child_test() -> bool {
+    env::var_os(CHILD_TEST_ENV).is_some()
+}
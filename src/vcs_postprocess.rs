@@ -3,12 +3,15 @@ use std::time::SystemTime;
 
 use tracing::debug_span;
 
-use crate::discovery::{ProjectResult, VcsResult};
+use crate::discovery::{ProjectResult, VcsKind, VcsResult, pick_size};
+use crate::file_info::SizeMode;
 
 #[derive(Debug)]
 pub struct EnrichedVcsResult {
     pub path: PathBuf,
+    pub kind: VcsKind,
     pub size: u64,
+    pub allocated_size: u64,
     pub last_update: Option<SystemTime>,
     pub vcs_size: u64,
     pub matched_projects: Vec<ProjectResult>,
@@ -18,7 +21,9 @@ impl From<VcsResult> for EnrichedVcsResult {
     fn from(vcs: VcsResult) -> Self {
         EnrichedVcsResult {
             path: vcs.path,
+            kind: vcs.kind,
             size: vcs.size,
+            allocated_size: vcs.allocated_size,
             last_update: vcs.last_update,
             vcs_size: vcs.vcs_size,
             matched_projects: vec![],
@@ -26,6 +31,12 @@ impl From<VcsResult> for EnrichedVcsResult {
     }
 }
 
+impl EnrichedVcsResult {
+    pub fn size_in(&self, mode: SizeMode) -> u64 {
+        pick_size(mode, self.size, self.allocated_size)
+    }
+}
+
 pub fn vcs_postprocess(
     projects: &[ProjectResult],
     vcs_results: Vec<VcsResult>,
@@ -48,12 +59,27 @@ pub fn vcs_postprocess(
     results
 }
 
+/// Assign the VCS metadata size of the closest repository containing each project
+///
+/// Projects outside any repository keep `vcs_size` empty.
+pub fn assign_vcs_sizes(projects: &mut [ProjectResult], vcs_results: &[VcsResult]) {
+    let _guard = debug_span!("assign_vcs_sizes", projects = ?projects.len(), vcs_results = ?vcs_results.len()).entered();
+
+    for project in projects.iter_mut() {
+        project.vcs_size = vcs_results
+            .iter()
+            .filter(|vcs_dir| project.path.starts_with(&vcs_dir.path))
+            .max_by_key(|vcs_dir| vcs_dir.path.components().count())
+            .map(|vcs_dir| vcs_dir.vcs_size);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
-    use super::vcs_postprocess;
-    use crate::discovery::{Language, ProjectResult, VcsResult};
+    use super::{assign_vcs_sizes, vcs_postprocess};
+    use crate::discovery::{Language, ProjectResult, VcsKind, VcsResult};
 
     #[test]
     fn test_vcs_postprocess() {
@@ -62,27 +88,39 @@ mod tests {
                 path: PathBuf::from("/home/user/projects/rust/target"),
                 lang: Language::Rust,
                 size: 100,
+                allocated_size: 100,
                 last_update: None,
                 parent: None,
+                vcs_size: None,
+                orphaned: false,
+                root: None,
             },
             ProjectResult {
                 path: PathBuf::from("/home/user/projects/python/.venv"),
                 lang: Language::Python,
                 size: 200,
+                allocated_size: 200,
                 last_update: None,
                 parent: None,
+                vcs_size: None,
+                orphaned: false,
+                root: None,
             },
         ];
         let vcs_results = vec![
             VcsResult {
                 path: PathBuf::from("/home/user/projects/rust"),
+                kind: VcsKind::Git,
                 size: 1000,
+                allocated_size: 1000,
                 last_update: None,
                 vcs_size: 10,
             },
             VcsResult {
                 path: PathBuf::from("/home/user/projects/experiments"),
+                kind: VcsKind::Mercurial,
                 size: 1000,
+                allocated_size: 1000,
                 last_update: None,
                 vcs_size: 20,
             },
@@ -98,4 +136,42 @@ mod tests {
         );
         assert_eq!(enriched[1].matched_projects.len(), 0);
     }
+
+    #[test]
+    fn test_assign_vcs_sizes() {
+        let project = |path: &str| ProjectResult {
+            path: PathBuf::from(path),
+            lang: Language::Rust,
+            size: 100,
+            allocated_size: 100,
+            last_update: None,
+            parent: None,
+            vcs_size: None,
+            orphaned: false,
+            root: None,
+        };
+        let vcs = |path: &str, vcs_size: u64| VcsResult {
+            path: PathBuf::from(path),
+            kind: VcsKind::Git,
+            size: 1000,
+            allocated_size: 1000,
+            last_update: None,
+            vcs_size,
+        };
+        let mut projects = vec![
+            project("/home/user/projects/rust/target"),
+            project("/home/user/projects/rust/vendor/lib/target"),
+            project("/home/user/projects/other/target"),
+        ];
+        let vcs_results = vec![
+            vcs("/home/user/projects/rust", 10),
+            vcs("/home/user/projects/rust/vendor/lib", 20),
+        ];
+
+        assign_vcs_sizes(&mut projects, &vcs_results);
+
+        assert_eq!(projects[0].vcs_size, Some(10));
+        assert_eq!(projects[1].vcs_size, Some(20));
+        assert_eq!(projects[2].vcs_size, None);
+    }
 }
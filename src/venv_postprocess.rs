@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tracing::{debug, debug_span};
+
+use crate::discovery::{Language, ProjectResult};
+
+/// File with the project path, written into the virtualenv by pipenv and virtualenvwrapper
+const PROJECT_FILE: &str = ".project";
+
+/// Flag virtualenvs whose project was removed from the disk
+///
+/// Only virtualenvs recording their project path can be checked. Poetry names its
+/// virtualenvs by a hash of the project path, so they are never flagged.
+pub fn venv_postprocess(projects: &mut [ProjectResult]) {
+    let _guard = debug_span!("venv_postprocess", projects = ?projects.len()).entered();
+
+    for project in projects
+        .iter_mut()
+        .filter(|project| project.lang == Language::Python)
+    {
+        if let Some(source) = venv_source(&project.path)
+            && !source.exists()
+        {
+            debug!(
+                "Virtualenv {} belongs to a missing project {}",
+                project.path.display(),
+                source.display()
+            );
+            project.orphaned = true;
+        }
+    }
+}
+
+/// Project path recorded in the virtualenv, if any
+fn venv_source(venv: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(venv.join(PROJECT_FILE)).ok()?;
+    let source = content.trim();
+    (!source.is_empty()).then(|| PathBuf::from(source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn venv(path: &Path, lang: Language) -> ProjectResult {
+        ProjectResult {
+            path: path.to_path_buf(),
+            lang,
+            size: 100,
+            allocated_size: 100,
+            last_update: None,
+            parent: None,
+            vcs_size: None,
+            orphaned: false,
+            root: None,
+        }
+    }
+
+    #[test]
+    fn test_venv_postprocess() {
+        let tmp = tempfile::tempdir().unwrap();
+        let envs = tmp.path().join(".local/share/virtualenvs");
+        let project = tmp.path().join("projects/alive");
+        fs::create_dir_all(&project).unwrap();
+        for name in [
+            "alive-Ab12Cd34",
+            "gone-Ef56Gh78",
+            "empty-Ij90Kl12",
+            "poetry-py3.12",
+        ] {
+            fs::create_dir_all(envs.join(name)).unwrap();
+        }
+        fs::write(
+            envs.join("alive-Ab12Cd34/.project"),
+            project.to_str().unwrap(),
+        )
+        .unwrap();
+        let gone = tmp.path().join("projects/gone");
+        fs::write(
+            envs.join("gone-Ef56Gh78/.project"),
+            format!("{}\n", gone.display()),
+        )
+        .unwrap();
+        fs::write(envs.join("empty-Ij90Kl12/.project"), "").unwrap();
+        // Only Python results are checked
+        let rust = tmp.path().join("rust/target");
+        fs::create_dir_all(&rust).unwrap();
+        fs::write(rust.join(".project"), gone.to_str().unwrap()).unwrap();
+
+        let mut projects = vec![
+            venv(&envs.join("alive-Ab12Cd34"), Language::Python),
+            venv(&envs.join("gone-Ef56Gh78"), Language::Python),
+            venv(&envs.join("empty-Ij90Kl12"), Language::Python),
+            venv(&envs.join("poetry-py3.12"), Language::Python),
+            venv(&rust, Language::Rust),
+        ];
+        venv_postprocess(&mut projects);
+
+        let orphaned = projects.iter().map(|p| p.orphaned).collect::<Vec<_>>();
+        assert_eq!(orphaned, vec![false, true, false, false, false]);
+    }
+}